@@ -15,6 +15,8 @@ impl Actor for HomeAssistantClient {
     fn started(&mut self, ctx: &mut Context<Self>) {
         debug!("[{}] HA client started", self.id);
         self.heartbeat(ctx);
+        self.timer_countdown(ctx);
+        self.camera_refresh(ctx);
     }
 
     fn stopped(&mut self, _ctx: &mut Self::Context) {
@@ -0,0 +1,148 @@
+// Copyright (c) 2023 Unfolded Circle ApS, Markus Zehnder <markus.z@unfoldedcircle.com>
+// SPDX-License-Identifier: MPL-2.0
+
+//! Camera snapshot handling.
+//!
+//! Camera snapshots are refreshed with a configurable interval, on motion events of a configured
+//! entity, and whenever HA rotates the access token of the camera.
+
+use std::time::Duration;
+
+use actix::{AsyncContext, Context};
+use log::{debug, error};
+use time::OffsetDateTime;
+use uc_api::EntityType;
+
+use crate::client::entity::{
+    camera_event_to_entity_change, camera_snapshot_entity_change, camera_snapshot_url,
+};
+use crate::client::messages::EntityEvent;
+use crate::client::model::EventData;
+use crate::client::HomeAssistantClient;
+use crate::errors::ServiceError;
+
+impl HomeAssistantClient {
+    /// Start the snapshot refresh intervals of the configured cameras.
+    pub(crate) fn camera_refresh(&self, ctx: &mut Context<Self>) {
+        for (camera_id, settings) in self.entity_settings.cameras.iter() {
+            if settings.refresh_interval == 0 {
+                continue;
+            }
+            let camera_id = camera_id.clone();
+            ctx.run_interval(
+                Duration::from_secs(settings.refresh_interval),
+                move |act, _ctx| act.refresh_camera_snapshot(&camera_id),
+            );
+        }
+    }
+
+    /// Handle a camera state change event.
+    ///
+    /// The state change is either sent as camera media player change, or as snapshot change of the
+    /// entity the camera is attached to.
+    pub(crate) fn handle_camera_event(&mut self, data: EventData) -> Result<(), ServiceError> {
+        self.entity_states
+            .insert(data.entity_id.clone(), data.new_state.clone());
+
+        if self.is_attached_camera(&data.entity_id) {
+            self.refresh_camera_snapshot(&data.entity_id);
+            return Ok(());
+        }
+
+        let entity_change = camera_event_to_entity_change(
+            &self.server,
+            data,
+            OffsetDateTime::now_utc().unix_timestamp(),
+        )?;
+        self.controller_actor.try_send(EntityEvent {
+            client_id: self.id.clone(),
+            entity_change,
+        })?;
+
+        Ok(())
+    }
+
+    /// Refresh the snapshots of the cameras triggered by the given motion entity.
+    ///
+    /// Only a change to the `on` state triggers a refresh.
+    pub(crate) fn handle_camera_motion(&self, entity_id: &str, state: &str) {
+        if state != "on"
+            || self
+                .entity_states
+                .get(entity_id)
+                .map(|s| s.state == "on")
+                .unwrap_or_default()
+        {
+            return;
+        }
+
+        for (camera_id, _) in self
+            .entity_settings
+            .cameras
+            .iter()
+            .filter(|(_, s)| s.motion_entity.as_deref() == Some(entity_id))
+        {
+            debug!("[{}] Motion detected: refreshing {camera_id}", self.id);
+            self.refresh_camera_snapshot(camera_id);
+        }
+    }
+
+    /// Check if the camera snapshot is attached to another entity.
+    pub(crate) fn is_attached_camera(&self, camera_id: &str) -> bool {
+        self.entity_settings
+            .cameras
+            .get(camera_id)
+            .map(|s| s.attach_to.is_some())
+            .unwrap_or_default()
+    }
+
+    /// Get the current snapshot URL of the camera attached to the given entity.
+    pub(crate) fn attached_camera_snapshot(&self, entity_id: &str) -> Option<String> {
+        let (camera_id, _) = self
+            .entity_settings
+            .cameras
+            .iter()
+            .find(|(_, s)| s.attach_to.as_deref() == Some(entity_id))?;
+        self.camera_snapshot(camera_id)
+    }
+
+    fn camera_snapshot(&self, camera_id: &str) -> Option<String> {
+        let state = self.entity_states.get(camera_id)?;
+        if state.state == "unavailable" {
+            return None;
+        }
+        camera_snapshot_url(
+            &self.server,
+            state.attributes.as_ref()?,
+            OffsetDateTime::now_utc().unix_timestamp(),
+        )
+    }
+
+    /// Send a new snapshot URL of the camera to the remote.
+    fn refresh_camera_snapshot(&self, camera_id: &str) {
+        let url = match self.camera_snapshot(camera_id) {
+            Some(url) => url,
+            None => return,
+        };
+
+        let (entity_id, entity_type) = match self
+            .entity_settings
+            .cameras
+            .get(camera_id)
+            .and_then(|s| s.attach_to.as_deref())
+        {
+            Some(entity_id) if entity_id.starts_with("media_player.") => {
+                (entity_id, EntityType::MediaPlayer)
+            }
+            Some(entity_id) => (entity_id, EntityType::Sensor),
+            None => (camera_id, EntityType::MediaPlayer),
+        };
+
+        if let Err(e) = self.controller_actor.try_send(EntityEvent {
+            client_id: self.id.clone(),
+            entity_change: camera_snapshot_entity_change(entity_id, entity_type, url),
+        }) {
+            error!("[{}] Error sending camera snapshot: {:?}", self.id, e);
+        }
+    }
+}
@@ -0,0 +1,97 @@
+// Copyright (c) 2023 Unfolded Circle ApS, Markus Zehnder <markus.z@unfoldedcircle.com>
+// SPDX-License-Identifier: MPL-2.0
+
+//! Home Assistant core configuration with the `get_config` command.
+//!
+//! The HA unit system is used for the temperature conversion of climate entities, weather entities
+//! and temperature sensors to the preferred temperature unit from the configuration file.
+
+use actix::Context;
+use log::{debug, error, warn};
+use serde_json::{json, Map, Value};
+
+use crate::client::entity::{
+    convert_climate_temperatures, convert_sensor_temperature, convert_weather_temperatures,
+    temperature_unit_from_symbol, TemperatureConversion,
+};
+use crate::client::HomeAssistantClient;
+use crate::errors::ServiceError;
+
+impl HomeAssistantClient {
+    /// Request the HA core configuration.
+    pub(crate) fn request_config(
+        &mut self,
+        ctx: &mut Context<HomeAssistantClient>,
+    ) -> Result<(), ServiceError> {
+        let id = self.new_msg_id();
+        self.config_id = Some(id);
+        self.send_json(json!({ "id": id, "type": "get_config" }), ctx)
+    }
+
+    /// Handle the result message of a `get_config` request.
+    pub(crate) fn handle_config_result(&mut self, success: bool, msg: &Map<String, Value>) {
+        if !success {
+            error!(
+                "[{}] get_config request failed: {:?}",
+                self.id,
+                msg.get("error")
+            );
+            return;
+        }
+
+        let unit = msg
+            .get("result")
+            .and_then(|v| v.pointer("/unit_system/temperature"))
+            .and_then(|v| v.as_str());
+        self.temperature_unit = unit.and_then(temperature_unit_from_symbol);
+        match self.temperature_unit {
+            Some(unit) => debug!("[{}] HA temperature unit: {unit:?}", self.id),
+            None => warn!("[{}] Unknown HA temperature unit: {unit:?}", self.id),
+        }
+    }
+
+    /// Get the temperature conversion for climate commands, if the preferred unit differs from the
+    /// HA unit system.
+    pub(crate) fn temperature_conversion(&self) -> Option<TemperatureConversion> {
+        let ha_unit = self.temperature_unit?;
+        let unit = self.entity_settings.temperature_unit?;
+        (unit != ha_unit).then_some(TemperatureConversion { unit, ha_unit })
+    }
+
+    /// Convert the temperatures of a HA entity state to the preferred temperature unit.
+    ///
+    /// Must be called after storing the original state, since commands depend on the HA values.
+    pub(crate) fn convert_temperatures(
+        &self,
+        domain: &str,
+        state: &mut String,
+        ha_attr: Option<&mut Map<String, Value>>,
+    ) {
+        let ha_attr = match ha_attr {
+            Some(ha_attr) => ha_attr,
+            None => return,
+        };
+        let unit = self.entity_settings.temperature_unit;
+
+        match domain {
+            "climate" | "water_heater" => {
+                if let Some(ha_unit) = self.temperature_unit {
+                    convert_climate_temperatures(ha_attr, ha_unit, unit.unwrap_or(ha_unit))
+                }
+            }
+            // HA already converts temperature sensors to the unit system
+            "sensor" => {
+                if let Some(unit) = unit {
+                    convert_sensor_temperature(state, ha_attr, unit)
+                }
+            }
+            // weather entities have their own temperature unit
+            "weather" => {
+                if let Some(unit) = unit {
+                    convert_weather_temperatures(ha_attr, unit)
+                }
+            }
+            _ => {}
+        }
+    }
+}
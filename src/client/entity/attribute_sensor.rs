@@ -0,0 +1,183 @@
+// Copyright (c) 2023 Unfolded Circle ApS, Markus Zehnder <markus.z@unfoldedcircle.com>
+// SPDX-License-Identifier: MPL-2.0
+
+//! Sensor entities created from attributes of other HA entities.
+//!
+//! The sensor entity identifier is the parent entity_id with the attribute path as suffix, e.g.
+//! `climate.living_room.current_humidity`.
+
+use crate::client::entity::device_class_to_label;
+use crate::configuration::AttributeSensorSettings;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use uc_api::intg::{AvailableIntgEntity, EntityChange};
+use uc_api::{EntityType, SensorOption};
+
+/// Get the sensor entity identifier of an attribute sensor.
+pub(crate) fn attribute_sensor_id(settings: &AttributeSensorSettings) -> String {
+    format!("{}.{}", settings.entity_id, settings.attribute)
+}
+
+/// Get an attribute value with a dot separated attribute path.
+///
+/// Array elements are accessed with their index, e.g. `forecast.0.temperature`.
+pub(crate) fn attribute_value<'a>(
+    attributes: &'a Map<String, Value>,
+    path: &str,
+) -> Option<&'a Value> {
+    let (key, sub_path) = match path.split_once('.') {
+        Some((key, sub_path)) => (key, Some(sub_path)),
+        None => (path, None),
+    };
+    let value = attributes.get(key)?;
+    match sub_path {
+        None => Some(value),
+        Some(sub_path) => value.pointer(&format!("/{}", sub_path.replace('.', "/"))),
+    }
+}
+
+fn map_attribute_sensor_attributes(
+    settings: &AttributeSensorSettings,
+    state: &str,
+    ha_attr: Option<&Map<String, Value>>,
+) -> Map<String, Value> {
+    let mut attributes = serde_json::Map::with_capacity(2);
+
+    let value = match state {
+        // propagate the unavailable state of the parent entity
+        "unavailable" | "unknown" => state.into(),
+        _ => ha_attr
+            .and_then(|attr| attribute_value(attr, &settings.attribute))
+            .cloned()
+            .unwrap_or_else(|| "unknown".into()),
+    };
+    attributes.insert("value".into(), value);
+    if let Some(unit) = settings.unit.as_ref() {
+        attributes.insert("unit".into(), unit.clone().into());
+    }
+
+    attributes
+}
+
+/// Create the attribute sensor change event from the parent entity state and attributes.
+pub(crate) fn attribute_sensor_entity_change(
+    settings: &AttributeSensorSettings,
+    state: &str,
+    ha_attr: Option<&Map<String, Value>>,
+) -> EntityChange {
+    EntityChange {
+        device_id: None,
+        entity_type: EntityType::Sensor,
+        entity_id: attribute_sensor_id(settings),
+        attributes: map_attribute_sensor_attributes(settings, state, ha_attr),
+    }
+}
+
+/// Create the attribute sensor entity from the parent entity state and attributes.
+///
+/// The attributes are not modified and must be the original HA attributes of the parent entity.
+pub(crate) fn convert_attribute_sensor(
+    settings: &AttributeSensorSettings,
+    state: &str,
+    ha_attr: Option<&Map<String, Value>>,
+) -> AvailableIntgEntity {
+    let label = settings
+        .label
+        .clone()
+        .or_else(|| device_class_to_label(&settings.attribute.replace('.', " ")))
+        .unwrap_or_default();
+    let name = match settings.name.as_ref() {
+        Some(name) => name.clone(),
+        None => {
+            let friendly_name = ha_attr
+                .and_then(|attr| attr.get("friendly_name"))
+                .and_then(|v| v.as_str())
+                .unwrap_or(&settings.entity_id);
+            format!("{friendly_name} {label}")
+        }
+    };
+
+    let mut options = serde_json::Map::new();
+    options.insert(SensorOption::CustomLabel.to_string(), label.into());
+    if let Some(unit) = settings.unit.as_ref() {
+        options.insert(SensorOption::CustomUnit.to_string(), unit.clone().into());
+    }
+
+    AvailableIntgEntity {
+        entity_id: attribute_sensor_id(settings),
+        device_id: None, // prepared for device_id handling
+        entity_type: EntityType::Sensor,
+        device_class: Some("custom".into()),
+        name: HashMap::from([("en".into(), name)]),
+        features: None,
+        area: None,
+        options: Some(options),
+        attributes: Some(map_attribute_sensor_attributes(settings, state, ha_attr)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use serde_json::json;
+
+    fn new_settings(attribute: &str) -> AttributeSensorSettings {
+        AttributeSensorSettings {
+            entity_id: "climate.living_room".into(),
+            attribute: attribute.into(),
+            name: None,
+            label: None,
+            unit: Some("%".into()),
+        }
+    }
+
+    #[rstest]
+    #[case("current_humidity", Some(json!(48)))]
+    #[case("hvac_modes.1", Some(json!("heat")))]
+    #[case("forecast.0.temperature", Some(json!(21.5)))]
+    #[case("forecast.1.temperature", None)]
+    #[case("missing", None)]
+    fn attribute_value_returns_nested_value(#[case] path: &str, #[case] expected: Option<Value>) {
+        let attr = json!({
+            "current_humidity": 48,
+            "hvac_modes": ["off", "heat"],
+            "forecast": [{ "temperature": 21.5 }]
+        });
+        let attr = attr.as_object().unwrap();
+
+        assert_eq!(expected.as_ref(), attribute_value(attr, path));
+    }
+
+    #[test]
+    fn convert_attribute_sensor_uses_parent_name_and_attribute_label() {
+        let settings = new_settings("current_humidity");
+        let attr = json!({ "friendly_name": "Living room", "current_humidity": 48 });
+        let entity = convert_attribute_sensor(&settings, "heat", attr.as_object());
+
+        assert_eq!("climate.living_room.current_humidity", entity.entity_id);
+        assert_eq!(
+            Some(&"Living room Current humidity".to_string()),
+            entity.name.get("en")
+        );
+        assert_eq!(
+            Some(
+                json!({ "value": 48, "unit": "%" })
+                    .as_object()
+                    .cloned()
+                    .unwrap()
+            ),
+            entity.attributes
+        );
+    }
+
+    #[rstest]
+    #[case("unavailable", json!("unavailable"))]
+    #[case("off", json!("unknown"))]
+    fn entity_change_without_attribute_value(#[case] state: &str, #[case] expected: Value) {
+        let settings = new_settings("current_humidity");
+        let change = attribute_sensor_entity_change(&settings, state, None);
+
+        assert_eq!(Some(&expected), change.attributes.get("value"));
+    }
+}
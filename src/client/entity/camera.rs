@@ -0,0 +1,185 @@
+// Copyright (c) 2023 Unfolded Circle ApS, Markus Zehnder <markus.z@unfoldedcircle.com>
+// SPDX-License-Identifier: MPL-2.0
+
+//! Camera entity specific logic.
+//!
+//! A camera is either exposed as media player showing the latest snapshot as media image, or its
+//! snapshot is attached to another media player or sensor entity.
+//!
+//! The `entity_picture` snapshot URL contains a short-lived access token which is rotated by HA
+//! with a state change event.
+
+use crate::client::entity::entity_picture_url;
+use crate::client::event::convert_ha_onoff_state;
+use crate::client::model::EventData;
+use crate::errors::ServiceError;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use uc_api::intg::{AvailableIntgEntity, EntityChange};
+use uc_api::{EntityType, MediaPlayerFeature};
+use url::Url;
+
+// https://developers.home-assistant.io/docs/core/entity/camera#supported-features
+pub const CAMERA_SUPPORT_ON_OFF: u32 = 1;
+// pub const CAMERA_SUPPORT_STREAM: u32 = 2;
+
+/// Get the snapshot URL of a camera from the `entity_picture` attribute.
+///
+/// The `time` query parameter is only used to force an image reload in the remote when the
+/// snapshot is refreshed. The access token might still be the same.
+pub(crate) fn camera_snapshot_url(
+    server: &Url,
+    ha_attr: &Map<String, Value>,
+    timestamp: i64,
+) -> Option<String> {
+    let url = ha_attr
+        .get("entity_picture")
+        .and_then(|v| v.as_str())
+        .and_then(|v| entity_picture_url(server, v))?;
+    let separator = if url.contains('?') { '&' } else { '?' };
+    Some(format!("{url}{separator}time={timestamp}"))
+}
+
+pub(crate) fn map_camera_attributes(
+    server: &Url,
+    _entity_id: &str,
+    state: &str,
+    ha_attr: Option<&Map<String, Value>>,
+    timestamp: i64,
+) -> Result<Map<String, Value>, ServiceError> {
+    let mut attributes = serde_json::Map::with_capacity(2);
+
+    let state = match state {
+        "idle" | "recording" | "streaming" => "ON".into(),
+        _ => convert_ha_onoff_state(state)?,
+    };
+    attributes.insert("state".into(), state);
+
+    if let Some(url) = ha_attr.and_then(|attr| camera_snapshot_url(server, attr, timestamp)) {
+        attributes.insert("media_image_url".into(), url.into());
+    }
+
+    Ok(attributes)
+}
+
+pub(crate) fn camera_event_to_entity_change(
+    server: &Url,
+    data: EventData,
+    timestamp: i64,
+) -> Result<EntityChange, ServiceError> {
+    let attributes = map_camera_attributes(
+        server,
+        &data.entity_id,
+        &data.new_state.state,
+        data.new_state.attributes.as_ref(),
+        timestamp,
+    )?;
+
+    Ok(EntityChange {
+        device_id: None,
+        entity_type: EntityType::MediaPlayer,
+        entity_id: data.entity_id,
+        attributes,
+    })
+}
+
+/// Create the change event of an entity with an attached camera snapshot.
+///
+/// The snapshot is only set in the `media_image_url` attribute and doesn't affect the other
+/// attributes of the entity.
+pub(crate) fn camera_snapshot_entity_change(
+    entity_id: &str,
+    entity_type: EntityType,
+    url: String,
+) -> EntityChange {
+    let mut attributes = serde_json::Map::with_capacity(1);
+    attributes.insert("media_image_url".into(), url.into());
+
+    EntityChange {
+        device_id: None,
+        entity_type,
+        entity_id: entity_id.into(),
+        attributes,
+    }
+}
+
+pub(crate) fn convert_camera_entity(
+    server: &Url,
+    entity_id: String,
+    state: String,
+    ha_attr: &mut Map<String, Value>,
+    timestamp: i64,
+) -> Result<AvailableIntgEntity, ServiceError> {
+    let friendly_name = ha_attr.get("friendly_name").and_then(|v| v.as_str());
+    let name = HashMap::from([("en".into(), friendly_name.unwrap_or(&entity_id).into())]);
+
+    // handle features
+    let supported_features = ha_attr
+        .get("supported_features")
+        .and_then(|v| v.as_u64())
+        .unwrap_or_default() as u32;
+    let mut media_feats = Vec::with_capacity(2);
+    if supported_features & CAMERA_SUPPORT_ON_OFF > 0 {
+        media_feats.push(MediaPlayerFeature::OnOff);
+    }
+    media_feats.push(MediaPlayerFeature::MediaImageUrl);
+
+    // convert attributes
+    let attributes = Some(map_camera_attributes(
+        server,
+        &entity_id,
+        &state,
+        Some(ha_attr),
+        timestamp,
+    )?);
+
+    Ok(AvailableIntgEntity {
+        entity_id,
+        device_id: None, // prepared for device_id handling
+        entity_type: EntityType::MediaPlayer,
+        device_class: None,
+        name,
+        features: Some(media_feats.into_iter().map(|v| v.to_string()).collect()),
+        area: None,
+        options: None,
+        attributes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use serde_json::json;
+
+    #[rstest]
+    #[case(
+        "/api/camera_proxy/camera.door?token=abc",
+        "http://hassio.local:8123/api/camera_proxy/camera.door?token=abc&time=1690884000"
+    )]
+    #[case(
+        "https://example.com/snapshot.jpg",
+        "https://example.com/snapshot.jpg?time=1690884000"
+    )]
+    fn snapshot_url_forces_reload(#[case] picture: &str, #[case] expected: &str) {
+        let server = Url::parse("http://hassio.local:8123").unwrap();
+        let attr = json!({ "entity_picture": picture });
+
+        assert_eq!(
+            Some(expected.to_string()),
+            camera_snapshot_url(&server, attr.as_object().unwrap(), 1690884000)
+        );
+    }
+
+    #[rstest]
+    #[case("idle", "ON")]
+    #[case("streaming", "ON")]
+    #[case("off", "OFF")]
+    #[case("unavailable", "UNAVAILABLE")]
+    fn camera_state(#[case] state: &str, #[case] expected: &str) {
+        let server = Url::parse("http://hassio.local:8123").unwrap();
+        let attributes = map_camera_attributes(&server, "camera.door", state, None, 0).unwrap();
+
+        assert_eq!(Some(&json!(expected)), attributes.get("state"));
+    }
+}
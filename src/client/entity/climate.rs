@@ -16,13 +16,13 @@ use uc_api::{ClimateFeature, ClimateOption, EntityType};
 // https://developers.home-assistant.io/docs/core/entity/climate#supported-features
 pub const SUPPORT_TARGET_TEMPERATURE: u32 = 1;
 pub const SUPPORT_TARGET_TEMPERATURE_RANGE: u32 = 2;
-/* not yet used constants
 pub const SUPPORT_TARGET_HUMIDITY: u32 = 4;
 pub const SUPPORT_FAN_MODE: u32 = 8;
 pub const SUPPORT_PRESET_MODE: u32 = 16;
 pub const SUPPORT_SWING_MODE: u32 = 32;
-pub const SUPPORT_AUX_HEAT: u32 = 64;
-*/
+// pub const SUPPORT_AUX_HEAT: u32 = 64;
+pub const SUPPORT_TURN_OFF: u32 = 128;
+pub const SUPPORT_TURN_ON: u32 = 256;
 
 pub(crate) fn map_climate_attributes(
     entity_id: &str,
@@ -53,11 +53,32 @@ pub(crate) fn map_climate_attributes(
             "temperature",
             "target_temperature",
         );
-        json::move_entry(ha_attr, &mut attributes, "target_temperature_high");
-        json::move_entry(ha_attr, &mut attributes, "target_temperature_low");
-        if let Some(value) = ha_attr.get("fan_mode").and_then(|v| v.as_str()) {
-            // TODO test and filter fan modes?
-            attributes.insert("fan_mode".into(), value.to_uppercase().into());
+        json::move_value(
+            ha_attr,
+            &mut attributes,
+            "target_temp_high",
+            "target_temperature_high",
+        );
+        json::move_value(
+            ha_attr,
+            &mut attributes,
+            "target_temp_low",
+            "target_temperature_low",
+        );
+        json::move_entry(ha_attr, &mut attributes, "current_humidity");
+        json::move_value(ha_attr, &mut attributes, "humidity", "target_humidity");
+        // modes are integration specific and passed as-is to match the available mode options
+        json::move_entry(ha_attr, &mut attributes, "fan_mode");
+        json::move_entry(ha_attr, &mut attributes, "preset_mode");
+        json::move_entry(ha_attr, &mut attributes, "swing_mode");
+        if let Some(value) = ha_attr.get("hvac_action").and_then(|v| v.as_str()) {
+            match value {
+                "off" | "preheating" | "heating" | "cooling" | "drying" | "idle" | "fan"
+                | "defrosting" => {
+                    attributes.insert("hvac_action".into(), value.to_uppercase().into());
+                }
+                action => warn!("{} Not supported hvac action: {}", entity_id, action),
+            }
         }
     }
 
@@ -94,39 +115,9 @@ pub(crate) fn convert_climate_entity(
         .get("supported_features")
         .and_then(|v| v.as_u64())
         .unwrap_or_default() as u32;
-    let mut climate_feats = Vec::with_capacity(4);
-
-    // TODO not completely tested, need to test "cool"! #11
-    // https://developers.home-assistant.io/docs/core/entity/climate#hvac-modes
-    // Need to find some real climate devices to test...
-    if let Some(hvac_modes) = ha_attr.get("hvac_modes").and_then(|v| v.as_array()) {
-        for hvac_mode in hvac_modes {
-            let feature = match hvac_mode.as_str().unwrap_or_default() {
-                "off" => ClimateFeature::OnOff,
-                "heat" => ClimateFeature::Heat,
-                "cool" => ClimateFeature::Cool,
-                // "fan_only" => ClimateFeature::Fan,
-                &_ => continue,
-            };
-            climate_feats.push(feature);
-        }
-    }
-
-    if supported_features & SUPPORT_TARGET_TEMPERATURE > 0 {
-        climate_feats.push(ClimateFeature::TargetTemperature);
-    }
-    if supported_features & SUPPORT_TARGET_TEMPERATURE_RANGE > 0 {
-        /* sorry, not yet implemented
-            climate_feats.push(ClimateFeature::TargetTemperatureRange)
-        */
-    }
+    let features = climate_features(supported_features, ha_attr);
 
-    // TODO is this the correct way to find out if the device can measure the current temperature? #12
-    if is_float_value(ha_attr, "current_temperature") {
-        climate_feats.push(ClimateFeature::CurrentTemperature);
-    }
-
-    // handle options. TODO untested! Only based on some GitHub issue logs :-) #12
+    // handle options
     let mut options = serde_json::Map::new();
     if let Some(v) = number_value(ha_attr, "min_temp") {
         options.insert(ClimateOption::MinTemperature.to_string(), v);
@@ -137,7 +128,24 @@ pub(crate) fn convert_climate_entity(
     if let Some(v) = number_value(ha_attr, "target_temp_step") {
         options.insert(ClimateOption::TargetTemperatureStep.to_string(), v);
     }
-    // TODO how do we get the HA temperature_unit attribute? Couldn't find an example... #10
+    if let Some(v) = number_value(ha_attr, "min_humidity") {
+        options.insert("min_humidity".into(), v);
+    }
+    if let Some(v) = number_value(ha_attr, "max_humidity") {
+        options.insert("max_humidity".into(), v);
+    }
+    for (feature, key) in [
+        (SUPPORT_FAN_MODE, "fan_modes"),
+        (SUPPORT_PRESET_MODE, "preset_modes"),
+        (SUPPORT_SWING_MODE, "swing_modes"),
+    ] {
+        if supported_features & feature > 0 {
+            if let Some(v) = ha_attr.get(key).filter(|v| v.is_array()) {
+                options.insert(key.into(), v.clone());
+            }
+        }
+    }
+    // set from the HA unit system or the preferred temperature unit
     if let Some(v) = ha_attr.get("temperature_unit") {
         options.insert(ClimateOption::TemperatureUnit.to_string(), v.clone());
     }
@@ -151,7 +159,7 @@ pub(crate) fn convert_climate_entity(
         entity_type: EntityType::Climate,
         device_class: None,
         name,
-        features: Some(climate_feats.into_iter().map(|v| v.to_string()).collect()),
+        features: Some(features),
         area: None,
         options: if options.is_empty() {
             None
@@ -162,8 +170,74 @@ pub(crate) fn convert_climate_entity(
     })
 }
 
+/// Get the climate features from the HVAC modes and the supported features bitmask.
+///
+/// Features which are not yet available in the Integration-API library are added as string.
+fn climate_features(supported_features: u32, ha_attr: &Map<String, Value>) -> Vec<String> {
+    let mut climate_feats = Vec::with_capacity(4);
+    let mut extra_feats = Vec::new();
+
+    // https://developers.home-assistant.io/docs/core/entity/climate#hvac-modes
+    let hvac_modes = ha_attr
+        .get("hvac_modes")
+        .and_then(|v| v.as_array())
+        .map(|v| v.iter().filter_map(|v| v.as_str()).collect::<Vec<_>>())
+        .unwrap_or_default();
+    // newer HA versions announce turn on & off support, older ones only have the `off` mode
+    if hvac_modes.contains(&"off") || supported_features & (SUPPORT_TURN_ON | SUPPORT_TURN_OFF) > 0
+    {
+        climate_feats.push(ClimateFeature::OnOff);
+    }
+    for hvac_mode in hvac_modes {
+        match hvac_mode {
+            "heat" => climate_feats.push(ClimateFeature::Heat),
+            "cool" => climate_feats.push(ClimateFeature::Cool),
+            "heat_cool" | "auto" => extra_feats.push(hvac_mode),
+            "fan_only" => extra_feats.push("fan"),
+            _ => {}
+        }
+    }
+
+    if supported_features & SUPPORT_TARGET_TEMPERATURE > 0 {
+        climate_feats.push(ClimateFeature::TargetTemperature);
+    }
+    // current_temperature is always set, but it is null if the device doesn't have a sensor
+    if is_float_value(ha_attr, "current_temperature") {
+        climate_feats.push(ClimateFeature::CurrentTemperature);
+    }
+
+    if supported_features & SUPPORT_TARGET_TEMPERATURE_RANGE > 0 {
+        extra_feats.push("target_temperature_range");
+    }
+    if supported_features & SUPPORT_TARGET_HUMIDITY > 0 {
+        extra_feats.push("target_humidity");
+    }
+    if ha_attr.contains_key("current_humidity") {
+        extra_feats.push("current_humidity");
+    }
+    if supported_features & SUPPORT_FAN_MODE > 0 {
+        extra_feats.push("fan_mode");
+    }
+    if supported_features & SUPPORT_PRESET_MODE > 0 {
+        extra_feats.push("preset_mode");
+    }
+    if supported_features & SUPPORT_SWING_MODE > 0 {
+        extra_feats.push("swing_mode");
+    }
+    if ha_attr.contains_key("hvac_action") {
+        extra_feats.push("hvac_action");
+    }
+
+    climate_feats
+        .into_iter()
+        .map(|v| v.to_string())
+        .chain(extra_feats.into_iter().map(String::from))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
+    use crate::client::entity::climate::convert_climate_entity;
     use crate::client::entity::climate_event_to_entity_change;
     use crate::client::model::EventData;
     use serde_json::{json, Value};
@@ -244,6 +318,103 @@ mod tests {
         );
     }
 
+    #[test]
+    fn climate_event_heat_cool() {
+        let new_state = json!({
+            "entity_id": "climate.living_room",
+            "state": "heat_cool",
+            "attributes": {
+                "current_temperature": 21.0,
+                "temperature": null,
+                "target_temp_high": 24.0,
+                "target_temp_low": 19.5,
+                "current_humidity": 48,
+                "humidity": 45,
+                "fan_mode": "Auto low",
+                "preset_mode": "eco",
+                "swing_mode": "off",
+                "hvac_action": "heating",
+                "supported_features": 447
+            }
+        });
+        let event = map_new_state(new_state);
+
+        assert_eq!(Some(&json!("HEAT_COOL")), event.attributes.get("state"));
+        assert_eq!(
+            Some(&json!(24.0)),
+            event.attributes.get("target_temperature_high")
+        );
+        assert_eq!(
+            Some(&json!(19.5)),
+            event.attributes.get("target_temperature_low")
+        );
+        assert_eq!(Some(&json!(48)), event.attributes.get("current_humidity"));
+        assert_eq!(Some(&json!(45)), event.attributes.get("target_humidity"));
+        assert_eq!(Some(&json!("Auto low")), event.attributes.get("fan_mode"));
+        assert_eq!(Some(&json!("eco")), event.attributes.get("preset_mode"));
+        assert_eq!(Some(&json!("off")), event.attributes.get("swing_mode"));
+        assert_eq!(Some(&json!("HEATING")), event.attributes.get("hvac_action"));
+    }
+
+    #[test]
+    fn climate_entity_features_and_options() {
+        let attributes = json!({
+            "hvac_modes": ["off", "heat", "cool", "heat_cool", "fan_only"],
+            "min_temp": 7,
+            "max_temp": 35,
+            "target_temp_step": 0.5,
+            "min_humidity": 30,
+            "max_humidity": 99,
+            "fan_modes": ["Auto low", "Low", "High"],
+            "preset_modes": ["none", "eco"],
+            "swing_modes": ["off", "vertical"],
+            "current_temperature": 21.0,
+            "current_humidity": 48,
+            "hvac_action": "idle",
+            "supported_features": 447
+        });
+        let mut attributes = attributes.as_object().cloned().unwrap();
+        let entity =
+            convert_climate_entity("climate.living_room".into(), "off".into(), &mut attributes)
+                .expect("climate entity conversion failed");
+
+        assert_eq!(
+            Some(
+                vec![
+                    "on_off",
+                    "heat",
+                    "cool",
+                    "target_temperature",
+                    "current_temperature",
+                    "heat_cool",
+                    "fan",
+                    "target_temperature_range",
+                    "target_humidity",
+                    "current_humidity",
+                    "fan_mode",
+                    "preset_mode",
+                    "swing_mode",
+                    "hvac_action",
+                ]
+                .into_iter()
+                .map(String::from)
+                .collect()
+            ),
+            entity.features
+        );
+        let options = entity.options.expect("options expected");
+        assert_eq!(
+            Some(&json!(["Auto low", "Low", "High"])),
+            options.get("fan_modes")
+        );
+        assert_eq!(Some(&json!(["none", "eco"])), options.get("preset_modes"));
+        assert_eq!(
+            Some(&json!(["off", "vertical"])),
+            options.get("swing_modes")
+        );
+        assert_eq!(Some(&json!(30)), options.get("min_humidity"));
+    }
+
     fn map_new_state(new_state: Value) -> EntityChange {
         let data = EventData {
             entity_id: "test".into(),
@@ -0,0 +1,83 @@
+// Copyright (c) 2023 Unfolded Circle ApS, Markus Zehnder <markus.z@unfoldedcircle.com>
+// SPDX-License-Identifier: MPL-2.0
+
+//! Counter entity specific logic.
+
+use crate::client::model::EventData;
+use crate::errors::ServiceError;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use uc_api::intg::{AvailableIntgEntity, EntityChange};
+use uc_api::{EntityType, SensorOption};
+
+pub(crate) fn map_counter_attributes(
+    _entity_id: &str,
+    state: &str,
+    _ha_attr: Option<&mut Map<String, Value>>,
+) -> Result<Map<String, Value>, ServiceError> {
+    let mut attributes = serde_json::Map::with_capacity(1);
+
+    let value = match state.parse::<i64>() {
+        Ok(value) => value.into(),
+        // unavailable, unknown
+        Err(_) => state.into(),
+    };
+    attributes.insert("value".into(), value);
+
+    Ok(attributes)
+}
+
+pub(crate) fn counter_event_to_entity_change(
+    mut data: EventData,
+) -> Result<EntityChange, ServiceError> {
+    let attributes = map_counter_attributes(
+        &data.entity_id,
+        &data.new_state.state,
+        data.new_state.attributes.as_mut(),
+    )?;
+
+    Ok(EntityChange {
+        device_id: None,
+        entity_type: EntityType::Sensor,
+        entity_id: data.entity_id,
+        attributes,
+    })
+}
+
+pub(crate) fn convert_counter_entity(
+    entity_id: String,
+    state: String,
+    ha_attr: &mut Map<String, Value>,
+) -> Result<AvailableIntgEntity, ServiceError> {
+    let friendly_name = ha_attr.get("friendly_name").and_then(|v| v.as_str());
+    let name = HashMap::from([("en".into(), friendly_name.unwrap_or(&entity_id).into())]);
+
+    // handle options
+    let mut options = serde_json::Map::new();
+    options.insert(SensorOption::CustomLabel.to_string(), "Counter".into());
+    options.insert(SensorOption::Decimals.to_string(), 0.into());
+    if let Some(v) = ha_attr.get("minimum").filter(|v| v.is_i64()) {
+        options.insert(SensorOption::MinValue.to_string(), v.clone());
+    }
+    if let Some(v) = ha_attr.get("maximum").filter(|v| v.is_i64()) {
+        options.insert(SensorOption::MaxValue.to_string(), v.clone());
+    }
+    if let Some(v) = ha_attr.get("step").filter(|v| v.is_u64()) {
+        options.insert("step".into(), v.clone());
+    }
+
+    // convert attributes
+    let attributes = Some(map_counter_attributes(&entity_id, &state, Some(ha_attr))?);
+
+    Ok(AvailableIntgEntity {
+        entity_id,
+        device_id: None, // prepared for device_id handling
+        entity_type: EntityType::Sensor,
+        device_class: Some("custom".into()),
+        name,
+        features: None,
+        area: None,
+        options: Some(options),
+        attributes,
+    })
+}
@@ -16,10 +16,27 @@ pub const COVER_SUPPORT_OPEN: u32 = 1;
 pub const COVER_SUPPORT_CLOSE: u32 = 2;
 pub const COVER_SUPPORT_SET_POSITION: u32 = 4;
 pub const COVER_SUPPORT_STOP: u32 = 8;
-// pub const COVER_SUPPORT_OPEN_TILT: u32 = 16;
-// pub const COVER_SUPPORT_CLOSE_TILT: u32 = 32;
-// pub const COVER_SUPPORT_STOP_TILT: u32 = 64;
-// pub const COVER_SUPPORT_SET_TILT_POSITION: u32 = 128;
+pub const COVER_SUPPORT_OPEN_TILT: u32 = 16;
+pub const COVER_SUPPORT_CLOSE_TILT: u32 = 32;
+pub const COVER_SUPPORT_STOP_TILT: u32 = 64;
+pub const COVER_SUPPORT_SET_TILT_POSITION: u32 = 128;
+
+/// Map a HA cover device class to a Remote Two cover device class.
+///
+/// HA device classes without a matching Remote Two device class are mapped to the closest one,
+/// or to the default device class if there's no similar one.
+pub(crate) fn cover_device_class(device_class: &str) -> Option<&'static str> {
+    match device_class {
+        "blind" | "shutter" => Some("blind"),
+        "curtain" => Some("curtain"),
+        "garage" => Some("garage"),
+        "shade" | "awning" => Some("shade"),
+        "door" => Some("door"),
+        "gate" => Some("gate"),
+        "window" => Some("window"),
+        _ => None,
+    }
+}
 
 pub(crate) fn map_cover_attributes(
     _entity_id: &str,
@@ -73,13 +90,11 @@ pub(crate) fn convert_cover_entity(
 ) -> Result<AvailableIntgEntity, ServiceError> {
     let friendly_name = ha_attr.get("friendly_name").and_then(|v| v.as_str());
     let name = HashMap::from([("en".into(), friendly_name.unwrap_or(&entity_id).into())]);
-    let device_class = ha_attr.get("device_class").and_then(|v| v.as_str());
-    let device_class = match device_class {
-        Some("blind") | Some("curtain") | Some("garage") | Some("shade") => {
-            device_class.map(|v| v.into())
-        }
-        _ => None,
-    };
+    let device_class = ha_attr
+        .get("device_class")
+        .and_then(|v| v.as_str())
+        .and_then(cover_device_class)
+        .map(|v| v.into());
 
     // handle features
     let supported_features = ha_attr
@@ -100,6 +115,17 @@ pub(crate) fn convert_cover_entity(
     if supported_features & COVER_SUPPORT_SET_POSITION > 0 {
         cover_feats.push(CoverFeature::Position);
     }
+    let mut features: Vec<String> = cover_feats.into_iter().map(|v| v.to_string()).collect();
+    // tilt features are not yet available in the Integration-API library
+    if supported_features & (COVER_SUPPORT_OPEN_TILT | COVER_SUPPORT_CLOSE_TILT) > 0 {
+        features.push("tilt".into());
+    }
+    if supported_features & COVER_SUPPORT_STOP_TILT > 0 {
+        features.push("tilt_stop".into());
+    }
+    if supported_features & COVER_SUPPORT_SET_TILT_POSITION > 0 {
+        features.push("tilt_position".into());
+    }
 
     // convert attributes
     let attributes = Some(map_cover_attributes(&entity_id, &state, Some(ha_attr))?);
@@ -110,9 +136,58 @@ pub(crate) fn convert_cover_entity(
         entity_type: EntityType::Cover,
         device_class,
         name,
-        features: Some(cover_feats.into_iter().map(|v| v.to_string()).collect()),
+        features: Some(features),
         area: None,
         options: None,
         attributes,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use serde_json::json;
+
+    #[rstest]
+    #[case(3, vec!["open", "close"])]
+    #[case(15, vec!["open", "close", "stop", "position"])]
+    #[case(11, vec!["open", "close", "stop"])]
+    #[case(255, vec!["open", "close", "stop", "position", "tilt", "tilt_stop", "tilt_position"])]
+    #[case(48, vec!["tilt"])]
+    #[case(0, vec![])]
+    fn convert_cover_entity_features(#[case] supported_features: u32, #[case] expected: Vec<&str>) {
+        let mut attr = json!({ "supported_features": supported_features })
+            .as_object()
+            .cloned()
+            .unwrap();
+        let entity = convert_cover_entity("cover.test".into(), "open".into(), &mut attr).unwrap();
+
+        assert_eq!(
+            Some(expected.into_iter().map(String::from).collect::<Vec<_>>()),
+            entity.features
+        );
+    }
+
+    #[rstest]
+    #[case("blind", Some("blind"))]
+    #[case("shutter", Some("blind"))]
+    #[case("awning", Some("shade"))]
+    #[case("gate", Some("gate"))]
+    #[case("damper", None)]
+    fn cover_device_class_mapping(#[case] device_class: &str, #[case] expected: Option<&str>) {
+        assert_eq!(expected, cover_device_class(device_class));
+    }
+
+    #[test]
+    fn map_cover_attributes_with_tilt_position() {
+        let mut attr = json!({ "current_position": 30, "current_tilt_position": 75 })
+            .as_object()
+            .cloned()
+            .unwrap();
+        let attributes = map_cover_attributes("cover.test", "open", Some(&mut attr)).unwrap();
+
+        assert_eq!(Some(&json!(30)), attributes.get("position"));
+        assert_eq!(Some(&json!(75)), attributes.get("tilt_position"));
+    }
+}
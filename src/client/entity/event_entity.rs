@@ -0,0 +1,175 @@
+// Copyright (c) 2023 Unfolded Circle ApS, Markus Zehnder <markus.z@unfoldedcircle.com>
+// SPDX-License-Identifier: MPL-2.0
+
+//! Event entity specific logic.
+//!
+//! A HA event entity is stateless: the state is the timestamp of the last fired event and the
+//! `event_type` attribute identifies the event. It's exposed as sensor with the last event type as
+//! value.
+
+use crate::client::entity::device_class_to_label;
+use crate::client::model::EventData;
+use crate::errors::ServiceError;
+use crate::util::json;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use uc_api::intg::{AvailableIntgEntity, EntityChange};
+use uc_api::{EntityType, SensorOption};
+
+pub(crate) fn map_event_attributes(
+    _entity_id: &str,
+    state: &str,
+    ha_attr: Option<&mut Map<String, Value>>,
+) -> Result<Map<String, Value>, ServiceError> {
+    let mut attributes = serde_json::Map::with_capacity(2);
+
+    match state {
+        // unavailable or never fired
+        "unavailable" | "unknown" => {
+            attributes.insert("value".into(), state.into());
+        }
+        timestamp => {
+            let event_type = ha_attr
+                .and_then(|attr| attr.remove("event_type"))
+                .unwrap_or_else(|| "unknown".into());
+            attributes.insert("value".into(), event_type);
+            attributes.insert("timestamp".into(), timestamp.into());
+        }
+    }
+
+    Ok(attributes)
+}
+
+pub(crate) fn event_event_to_entity_change(
+    mut data: EventData,
+) -> Result<EntityChange, ServiceError> {
+    let attributes = map_event_attributes(
+        &data.entity_id,
+        &data.new_state.state,
+        data.new_state.attributes.as_mut(),
+    )?;
+
+    Ok(EntityChange {
+        device_id: None,
+        entity_type: EntityType::Sensor,
+        entity_id: data.entity_id,
+        attributes,
+    })
+}
+
+pub(crate) fn convert_event_entity(
+    entity_id: String,
+    state: String,
+    ha_attr: &mut Map<String, Value>,
+) -> Result<AvailableIntgEntity, ServiceError> {
+    let friendly_name = ha_attr.get("friendly_name").and_then(|v| v.as_str());
+    let name = HashMap::from([("en".into(), friendly_name.unwrap_or(&entity_id).into())]);
+
+    // handle options
+    let label = ha_attr
+        .get("device_class")
+        .and_then(|v| v.as_str())
+        .and_then(device_class_to_label)
+        .unwrap_or_else(|| "Event".into());
+    let mut options = serde_json::Map::new();
+    options.insert(SensorOption::CustomLabel.to_string(), label.into());
+    json::copy_entry(ha_attr, &mut options, "event_types");
+
+    // convert attributes
+    let attributes = Some(map_event_attributes(&entity_id, &state, Some(ha_attr))?);
+
+    Ok(AvailableIntgEntity {
+        entity_id,
+        device_id: None, // prepared for device_id handling
+        entity_type: EntityType::Sensor,
+        device_class: Some("custom".into()),
+        name,
+        features: None,
+        area: None,
+        options: Some(options),
+        attributes,
+    })
+}
+
+/// Create the notification data of a fired event.
+///
+/// Returns `None` if the new state isn't a fired event: either the entity became unavailable, or
+/// it was restored after a HA restart.
+pub(crate) fn event_notification_data(
+    old_state: Option<&str>,
+    new_state: &str,
+    ha_attr: Option<&Map<String, Value>>,
+) -> Option<Map<String, Value>> {
+    match (old_state, new_state) {
+        (_, "unavailable" | "unknown") | (None | Some("unavailable"), _) => return None,
+        (Some(old), new) if old == new => return None,
+        _ => {}
+    }
+
+    // all event attributes except the static entity attributes
+    let mut data = ha_attr.cloned().unwrap_or_default();
+    for key in ["event_types", "friendly_name", "icon", "supported_features"] {
+        data.remove(key);
+    }
+    data.insert("timestamp".into(), new_state.into());
+    Some(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use serde_json::json;
+
+    #[rstest]
+    #[case(Some("unknown"), "2023-08-01T10:00:00.000+00:00", true)]
+    #[case(
+        Some("2023-08-01T09:00:00.000+00:00"),
+        "2023-08-01T10:00:00.000+00:00",
+        true
+    )]
+    #[case(
+        Some("2023-08-01T10:00:00.000+00:00"),
+        "2023-08-01T10:00:00.000+00:00",
+        false
+    )]
+    #[case(Some("unavailable"), "2023-08-01T10:00:00.000+00:00", false)]
+    #[case(None, "2023-08-01T10:00:00.000+00:00", false)]
+    #[case(Some("2023-08-01T10:00:00.000+00:00"), "unavailable", false)]
+    fn notification_only_for_fired_events(
+        #[case] old_state: Option<&str>,
+        #[case] new_state: &str,
+        #[case] expected: bool,
+    ) {
+        let attr = json!({ "event_type": "ring", "event_types": ["ring"], "device_class": "doorbell", "friendly_name": "Front door" });
+        let data = event_notification_data(old_state, new_state, attr.as_object());
+
+        assert_eq!(expected, data.is_some());
+        if let Some(data) = data {
+            assert_eq!(
+                json!({ "event_type": "ring", "device_class": "doorbell", "timestamp": new_state }),
+                Value::Object(data)
+            );
+        }
+    }
+
+    #[test]
+    fn event_value_is_event_type() {
+        let mut attr = json!({ "event_type": "ring" })
+            .as_object()
+            .cloned()
+            .unwrap();
+        let attributes = map_event_attributes(
+            "event.doorbell",
+            "2023-08-01T10:00:00.000+00:00",
+            Some(&mut attr),
+        )
+        .unwrap();
+
+        assert_eq!(Some(&json!("ring")), attributes.get("value"));
+        assert_eq!(
+            Some(&json!("2023-08-01T10:00:00.000+00:00")),
+            attributes.get("timestamp")
+        );
+    }
+}
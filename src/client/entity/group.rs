@@ -0,0 +1,165 @@
+// Copyright (c) 2023 Unfolded Circle ApS, Markus Zehnder <markus.z@unfoldedcircle.com>
+// SPDX-License-Identifier: MPL-2.0
+
+//! Group entity specific logic.
+//!
+//! HA groups are exposed as the entity type of their members: old-style `group` entities, and
+//! light, switch, cover or media player groups with an `entity_id` member attribute.
+
+use serde_json::{Map, Value};
+use uc_api::EntityType;
+
+/// Get the member entity_ids of a group from the `entity_id` attribute.
+///
+/// Returns `None` if the entity is not a group.
+pub(crate) fn group_members(ha_attr: &Map<String, Value>) -> Option<Vec<&str>> {
+    let members: Vec<&str> = ha_attr
+        .get("entity_id")
+        .and_then(|v| v.as_array())?
+        .iter()
+        .filter_map(|v| v.as_str())
+        .collect();
+    if members.is_empty() {
+        None
+    } else {
+        Some(members)
+    }
+}
+
+/// Get the entity type of an old-style `group` entity from its members.
+///
+/// Groups with members of different domains are exposed as switch, as long as the group has an
+/// on / off state. Other groups, e.g. of device trackers or locks, are not supported.
+pub(crate) fn group_entity_type(state: &str, ha_attr: &Map<String, Value>) -> Option<EntityType> {
+    let members = group_members(ha_attr)?;
+    let mut domains = members
+        .iter()
+        .filter_map(|m| m.split_once('.').map(|v| v.0));
+    let domain = domains.next()?;
+
+    if domains.all(|d| d == domain) {
+        match domain {
+            "light" => return Some(EntityType::Light),
+            "switch" | "input_boolean" => return Some(EntityType::Switch),
+            "cover" => return Some(EntityType::Cover),
+            "media_player" => return Some(EntityType::MediaPlayer),
+            _ => {}
+        }
+    }
+
+    match state {
+        "on" | "off" | "unavailable" | "unknown" => Some(EntityType::Switch),
+        _ => None,
+    }
+}
+
+/// Get the HA service domain to control an old-style `group` entity.
+///
+/// HA expands group entities in service call targets to the group members. Switch groups might
+/// contain members of different domains and use the generic `homeassistant` domain.
+pub(crate) fn group_service_domain(entity_type: &EntityType) -> &'static str {
+    match entity_type {
+        EntityType::Light => "light",
+        EntityType::Cover => "cover",
+        EntityType::MediaPlayer => "media_player",
+        _ => "homeassistant",
+    }
+}
+
+/// Only keep the features supported by all group members.
+pub(crate) fn intersect_features(features: &mut Vec<String>, member_features: &[String]) {
+    features.retain(|f| member_features.contains(f));
+}
+
+/// Get the features supported by all group members.
+///
+/// # Arguments
+///
+/// * `features`: features of the converted group entity.
+/// * `member_features`: features of each group member.
+/// * `from_members`: the group entity was converted from its own attributes without features, as
+///   old-style `group` entities. The features are taken from the first member instead.
+pub(crate) fn group_features(
+    features: Option<Vec<String>>,
+    member_features: &[Vec<String>],
+    from_members: bool,
+) -> Option<Vec<String>> {
+    let first = match member_features.first() {
+        None => return features,
+        Some(first) => first,
+    };
+    let mut features = match features {
+        Some(features) if !from_members => features,
+        _ => first.clone(),
+    };
+    for member_features in member_features {
+        intersect_features(&mut features, member_features);
+    }
+    Some(features)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::entity::convert_light_entity;
+    use rstest::rstest;
+    use serde_json::json;
+
+    #[rstest]
+    #[case("on", json!(["light.kitchen", "light.dining"]), Some(EntityType::Light))]
+    #[case("off", json!(["switch.fan", "input_boolean.guest"]), Some(EntityType::Switch))]
+    #[case("on", json!(["light.kitchen", "switch.fan"]), Some(EntityType::Switch))]
+    #[case("open", json!(["cover.left", "cover.right"]), Some(EntityType::Cover))]
+    #[case("playing", json!(["media_player.kitchen"]), Some(EntityType::MediaPlayer))]
+    #[case("home", json!(["person.anna", "device_tracker.phone"]), None)]
+    #[case("on", json!([]), None)]
+    fn group_type_from_members(
+        #[case] state: &str,
+        #[case] members: Value,
+        #[case] expected: Option<EntityType>,
+    ) {
+        let attr = json!({ "entity_id": members });
+        assert_eq!(
+            expected,
+            group_entity_type(state, attr.as_object().unwrap())
+        );
+    }
+
+    #[test]
+    fn intersect_features_keeps_common_features() {
+        let mut features = vec!["on_off".to_string(), "dim".into(), "color".into()];
+        intersect_features(&mut features, &["dim".into(), "on_off".into()]);
+
+        assert_eq!(vec!["on_off".to_string(), "dim".into()], features);
+    }
+
+    #[test]
+    fn old_style_light_group_keeps_member_features() {
+        let mut group_attr = json!({ "entity_id": ["light.kitchen", "light.dining"] })
+            .as_object()
+            .cloned()
+            .unwrap();
+        let group = convert_light_entity("group.lights".into(), "on".into(), &mut group_attr)
+            .expect("conversion failed");
+        let member_features: Vec<Vec<String>> = ["light.kitchen", "light.dining"]
+            .into_iter()
+            .map(|entity_id| {
+                let mut attr = json!({ "supported_color_modes": ["brightness"] })
+                    .as_object()
+                    .cloned()
+                    .unwrap();
+                convert_light_entity(entity_id.into(), "on".into(), &mut attr)
+                    .expect("conversion failed")
+                    .features
+                    .unwrap_or_default()
+            })
+            .collect();
+
+        let features = group_features(group.features, &member_features, true).unwrap_or_default();
+
+        assert!(
+            features.contains(&"dim".to_string()),
+            "Missing dim feature: {features:?}"
+        );
+    }
+}
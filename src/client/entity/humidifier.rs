@@ -0,0 +1,122 @@
+// Copyright (c) 2023 Unfolded Circle ApS, Markus Zehnder <markus.z@unfoldedcircle.com>
+// SPDX-License-Identifier: MPL-2.0
+
+//! Humidifier entity specific logic.
+//!
+//! A humidifier or dehumidifier is exposed as climate entity with the target humidity instead of a
+//! target temperature.
+
+use crate::client::event::convert_ha_onoff_state;
+use crate::client::model::EventData;
+use crate::errors::ServiceError;
+use crate::util::json;
+use crate::util::json::number_value;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use uc_api::intg::{AvailableIntgEntity, EntityChange};
+use uc_api::{ClimateFeature, EntityType};
+
+// https://developers.home-assistant.io/docs/core/entity/humidifier#supported-features
+pub const HUMIDIFIER_SUPPORT_MODES: u32 = 1;
+
+pub(crate) fn map_humidifier_attributes(
+    _entity_id: &str,
+    state: &str,
+    ha_attr: Option<&mut Map<String, Value>>,
+) -> Result<Map<String, Value>, ServiceError> {
+    let mut attributes = serde_json::Map::with_capacity(5);
+
+    // the humidifier controls the humidity on its own when turned on
+    let state = match state {
+        "on" => "AUTO".into(),
+        _ => convert_ha_onoff_state(state)?,
+    };
+    attributes.insert("state".into(), state);
+
+    if let Some(ha_attr) = ha_attr {
+        json::move_entry(ha_attr, &mut attributes, "current_humidity");
+        json::move_value(ha_attr, &mut attributes, "humidity", "target_humidity");
+        json::move_entry(ha_attr, &mut attributes, "mode");
+        json::move_entry(ha_attr, &mut attributes, "action");
+    }
+
+    Ok(attributes)
+}
+
+pub(crate) fn humidifier_event_to_entity_change(
+    mut data: EventData,
+) -> Result<EntityChange, ServiceError> {
+    let attributes = map_humidifier_attributes(
+        &data.entity_id,
+        &data.new_state.state,
+        data.new_state.attributes.as_mut(),
+    )?;
+
+    Ok(EntityChange {
+        device_id: None,
+        entity_type: EntityType::Climate,
+        entity_id: data.entity_id,
+        attributes,
+    })
+}
+
+pub(crate) fn convert_humidifier_entity(
+    entity_id: String,
+    state: String,
+    ha_attr: &mut Map<String, Value>,
+) -> Result<AvailableIntgEntity, ServiceError> {
+    let friendly_name = ha_attr.get("friendly_name").and_then(|v| v.as_str());
+    let name = HashMap::from([("en".into(), friendly_name.unwrap_or(&entity_id).into())]);
+    let device_class = match ha_attr.get("device_class").and_then(|v| v.as_str()) {
+        Some(v @ "humidifier") | Some(v @ "dehumidifier") => Some(v.into()),
+        _ => None,
+    };
+
+    // handle features
+    let supported_features = ha_attr
+        .get("supported_features")
+        .and_then(|v| v.as_u64())
+        .unwrap_or_default() as u32;
+    let mut features = vec![ClimateFeature::OnOff.to_string(), "target_humidity".into()];
+    if ha_attr.contains_key("current_humidity") {
+        features.push("current_humidity".into());
+    }
+    if supported_features & HUMIDIFIER_SUPPORT_MODES > 0 {
+        features.push("mode".into());
+    }
+
+    // handle options
+    let mut options = serde_json::Map::new();
+    if let Some(v) = number_value(ha_attr, "min_humidity") {
+        options.insert("min_humidity".into(), v);
+    }
+    if let Some(v) = number_value(ha_attr, "max_humidity") {
+        options.insert("max_humidity".into(), v);
+    }
+    if let Some(v) = ha_attr.get("available_modes").filter(|v| v.is_array()) {
+        options.insert("modes".into(), v.clone());
+    }
+
+    // convert attributes
+    let attributes = Some(map_humidifier_attributes(
+        &entity_id,
+        &state,
+        Some(ha_attr),
+    )?);
+
+    Ok(AvailableIntgEntity {
+        entity_id,
+        device_id: None, // prepared for device_id handling
+        entity_type: EntityType::Climate,
+        device_class,
+        name,
+        features: Some(features),
+        area: None,
+        options: if options.is_empty() {
+            None
+        } else {
+            Some(options)
+        },
+        attributes,
+    })
+}
@@ -0,0 +1,94 @@
+// Copyright (c) 2023 Unfolded Circle ApS, Markus Zehnder <markus.z@unfoldedcircle.com>
+// SPDX-License-Identifier: MPL-2.0
+
+//! Input_datetime entity specific logic.
+//!
+//! The value is the HA state: a date `2023-08-01`, a time `07:30:00`, or both
+//! `2023-08-01 07:30:00`, depending on the `has_date` and `has_time` attributes.
+
+use crate::client::model::EventData;
+use crate::errors::ServiceError;
+use crate::util::json;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use uc_api::intg::{AvailableIntgEntity, EntityChange};
+use uc_api::{EntityType, SensorOption};
+
+pub(crate) fn map_input_datetime_attributes(
+    _entity_id: &str,
+    state: &str,
+    ha_attr: Option<&mut Map<String, Value>>,
+) -> Result<Map<String, Value>, ServiceError> {
+    let mut attributes = serde_json::Map::with_capacity(2);
+    attributes.insert("value".into(), state.into());
+
+    if let Some(ha_attr) = ha_attr {
+        json::move_entry(ha_attr, &mut attributes, "timestamp");
+    }
+
+    Ok(attributes)
+}
+
+pub(crate) fn input_datetime_event_to_entity_change(
+    mut data: EventData,
+) -> Result<EntityChange, ServiceError> {
+    let attributes = map_input_datetime_attributes(
+        &data.entity_id,
+        &data.new_state.state,
+        data.new_state.attributes.as_mut(),
+    )?;
+
+    Ok(EntityChange {
+        device_id: None,
+        entity_type: EntityType::Sensor,
+        entity_id: data.entity_id,
+        attributes,
+    })
+}
+
+pub(crate) fn convert_input_datetime_entity(
+    entity_id: String,
+    state: String,
+    ha_attr: &mut Map<String, Value>,
+) -> Result<AvailableIntgEntity, ServiceError> {
+    let friendly_name = ha_attr.get("friendly_name").and_then(|v| v.as_str());
+    let name = HashMap::from([("en".into(), friendly_name.unwrap_or(&entity_id).into())]);
+
+    // handle options
+    let has_date = ha_attr
+        .get("has_date")
+        .and_then(|v| v.as_bool())
+        .unwrap_or_default();
+    let has_time = ha_attr
+        .get("has_time")
+        .and_then(|v| v.as_bool())
+        .unwrap_or_default();
+    let label = match (has_date, has_time) {
+        (true, true) => "Date & time",
+        (true, false) => "Date",
+        _ => "Time",
+    };
+    let mut options = serde_json::Map::new();
+    options.insert(SensorOption::CustomLabel.to_string(), label.into());
+    options.insert("has_date".into(), has_date.into());
+    options.insert("has_time".into(), has_time.into());
+
+    // convert attributes
+    let attributes = Some(map_input_datetime_attributes(
+        &entity_id,
+        &state,
+        Some(ha_attr),
+    )?);
+
+    Ok(AvailableIntgEntity {
+        entity_id,
+        device_id: None, // prepared for device_id handling
+        entity_type: EntityType::Sensor,
+        device_class: Some("custom".into()),
+        name,
+        features: None,
+        area: None,
+        options: Some(options),
+        attributes,
+    })
+}
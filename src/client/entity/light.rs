@@ -6,12 +6,45 @@
 use crate::client::event::convert_ha_onoff_state;
 use crate::client::model::EventData;
 use crate::errors::ServiceError;
-use log::{error, warn};
+use crate::util::{color, json};
+use log::warn;
 use serde_json::{Map, Value};
 use std::collections::HashMap;
 use uc_api::intg::AvailableIntgEntity;
 use uc_api::{intg::EntityChange, EntityType, LightFeature};
 
+// https://developers.home-assistant.io/docs/core/entity/light#supported-features
+pub const LIGHT_SUPPORT_EFFECT: u32 = 4;
+pub const LIGHT_SUPPORT_FLASH: u32 = 8;
+pub const LIGHT_SUPPORT_TRANSITION: u32 = 32;
+
+/// Check if the light supports the given HA light entity feature.
+pub(crate) fn light_supports(ha_attr: &Map<String, Value>, feature: u32) -> bool {
+    let supported_features = ha_attr
+        .get("supported_features")
+        .and_then(|v| v.as_u64())
+        .unwrap_or_default() as u32;
+    supported_features & feature > 0
+}
+
+/// Get the brightness factor of the color in the color modes `rgb`, `rgbw` and `rgbww`.
+///
+/// In these color modes there is brightness information both in the light's brightness property
+/// and in the color. The remote only uses the brightness property, which is multiplied with the
+/// brightest color channel. See https://developers.home-assistant.io/docs/core/entity/light
+pub(crate) fn light_brightness_factor(ha_attr: &Map<String, Value>) -> f64 {
+    let channels: Option<Vec<u8>> = match ha_attr.get("color_mode").and_then(|v| v.as_str()) {
+        Some("rgb") => color_channels::<3>(ha_attr, "rgb_color").map(Vec::from),
+        Some("rgbw") => color_channels::<4>(ha_attr, "rgbw_color").map(Vec::from),
+        Some("rgbww") => color_channels::<5>(ha_attr, "rgbww_color").map(Vec::from),
+        _ => None,
+    };
+    channels
+        .and_then(|c| c.into_iter().max())
+        .map(|max| max as f64 / 255.0)
+        .unwrap_or(1.0)
+}
+
 pub(crate) fn map_light_attributes(
     entity_id: &str,
     state: &str,
@@ -23,34 +56,31 @@ pub(crate) fn map_light_attributes(
     attributes.insert("state".into(), state);
 
     if let Some(ha_attr) = ha_attr {
-        // FIXME brightness adjustment for RGB## modes #7. https://developers.home-assistant.io/docs/core/entity/light
-        // Note that in color modes COLOR_MODE_RGB, COLOR_MODE_RGBW and COLOR_MODE_RGBWW there is
-        // brightness information both in the light's brightness property and in the color. As an
-        // example, if the light's brightness is 128 and the light's color is (192, 64, 32), the
-        // overall brightness of the light is: 128/255 * max(192, 64, 32)/255 = 38%.
-        ha_attr
-            .remove_entry("brightness")
-            .and_then(|e| match e.1.is_u64() {
-                true => Some(e),
-                false => None,
-            })
-            .map(|e| attributes.insert(e.0, e.1));
+        let brightness = ha_attr.remove("brightness").and_then(|v| v.as_u64());
+        let brightness_factor = light_brightness_factor(ha_attr);
 
         match ha_attr.get("color_mode").and_then(|v| v.as_str()) {
             Some("color_temp") => {
-                if let Some(color_temp) = ha_attr.get("color_temp").and_then(|v| v.as_u64()) {
-                    let min_mireds = ha_attr
-                        .get("min_mireds")
-                        .and_then(|v| v.as_u64())
-                        .unwrap_or_default() as u16;
-                    let max_mireds = ha_attr
-                        .get("max_mireds")
-                        .and_then(|v| v.as_u64())
-                        .unwrap_or_default() as u16;
-
-                    let color_temp_pct =
-                        color_temp_mired_to_percent(color_temp, min_mireds, max_mireds)?;
+                // newer HA versions provide Kelvin attributes, mireds are deprecated
+                let color_temp_pct = match (
+                    ha_attr.get("color_temp_kelvin").and_then(|v| v.as_u64()),
+                    light_color_temp_kelvin_range(ha_attr),
+                ) {
+                    (Some(kelvin), Some((min_kelvin, max_kelvin))) => Some(
+                        color_temp_kelvin_to_percent(kelvin, min_kelvin, max_kelvin)?,
+                    ),
+                    _ => match ha_attr.get("color_temp").and_then(|v| v.as_u64()) {
+                        Some(color_temp) => {
+                            let (min_mireds, max_mireds) = light_mired_range(ha_attr);
+                            Some(color_temp_mired_to_percent(
+                                color_temp, min_mireds, max_mireds,
+                            )?)
+                        }
+                        None => None,
+                    },
+                };
 
+                if let Some(color_temp_pct) = color_temp_pct {
                     attributes.insert(
                         "color_temperature".into(),
                         Value::Number(color_temp_pct.into()),
@@ -58,37 +88,61 @@ pub(crate) fn map_light_attributes(
                 }
             }
             Some("hs") => {
-                if let Some(hs) = ha_attr.get("hs_color").and_then(|v| v.as_array()) {
-                    if hs.len() != 2 {
-                        return Err(ServiceError::BadRequest(
-                            "Invalid hs_color value. Expected hue & saturation".into(),
-                        ));
-                    }
+                if let Some([hue, saturation]) = color_values::<2>(ha_attr, "hs_color") {
                     // hs values are returned as floats: hue: 0..360, saturation: 0..100
-                    let hue = hs.get(0).unwrap().as_f64().unwrap_or_default() as u16;
-                    let saturation =
-                        (hs.get(1).unwrap().as_f64().unwrap_or_default() as f32 * 2.55_f32) as u16;
-                    if hue > 360 || saturation > 100 {
+                    if !(0.0..=360.0).contains(&hue) || !(0.0..=100.0).contains(&saturation) {
                         return Err(ServiceError::BadRequest(format!(
                             "Invalid hs_color values ({}, {})",
                             hue, saturation
                         )));
                     }
-                    attributes.insert("hue".into(), Value::Number(hue.into()));
-                    attributes.insert(
-                        "saturation".into(),
-                        Value::Number((saturation * 255 / 100).into()),
-                    );
+                    insert_hue_saturation(&mut attributes, hue, saturation);
+                }
+            }
+            Some("xy") => {
+                if let Some([x, y]) = color_values::<2>(ha_attr, "xy_color") {
+                    let (hue, saturation) = color::xy_to_hs(x, y);
+                    insert_hue_saturation(&mut attributes, hue, saturation);
+                }
+            }
+            Some("rgb") => {
+                if let Some([r, g, b]) = color_channels::<3>(ha_attr, "rgb_color") {
+                    let (hue, saturation, _) = color::rgb_to_hsv(r, g, b);
+                    insert_hue_saturation(&mut attributes, hue, saturation);
                 }
             }
-            None => {}
-            v => {
-                error!(
-                    "TODO {} implement color mode conversion for color_mode: {}. Probably #7",
-                    entity_id,
-                    v.unwrap()
-                );
+            Some("rgbw") => {
+                if let Some([r, g, b, w]) = color_channels::<4>(ha_attr, "rgbw_color") {
+                    let (r2, g2, b2) = color::rgbw_to_rgb(r, g, b, w);
+                    let (hue, saturation, _) = color::rgb_to_hsv(r2, g2, b2);
+                    insert_hue_saturation(&mut attributes, hue, saturation);
+                }
+            }
+            Some("rgbww") => {
+                if let Some([r, g, b, cw, ww]) = color_channels::<5>(ha_attr, "rgbww_color") {
+                    let (min_kelvin, max_kelvin) = light_kelvin_range(ha_attr);
+                    let (r2, g2, b2) =
+                        color::rgbww_to_rgb((r, g, b, cw, ww), min_kelvin, max_kelvin);
+                    let (hue, saturation, _) = color::rgb_to_hsv(r2, g2, b2);
+                    insert_hue_saturation(&mut attributes, hue, saturation);
+                }
             }
+            // no color information
+            None | Some("onoff") | Some("brightness") | Some("white") | Some("unknown") => {}
+            Some(v) => {
+                warn!("{entity_id}: unsupported color_mode {v}");
+            }
+        }
+
+        if light_supports(ha_attr, LIGHT_SUPPORT_EFFECT) {
+            json::move_entry(ha_attr, &mut attributes, "effect");
+        }
+
+        if let Some(brightness) = brightness {
+            attributes.insert(
+                "brightness".into(),
+                ((brightness as f64 * brightness_factor).round() as u64).into(),
+            );
         }
     }
 
@@ -112,6 +166,78 @@ pub(crate) fn light_event_to_entity_change(
     })
 }
 
+/// Insert the HA hue (0..360) and saturation (0..100) values as remote hue (0..360) and
+/// saturation (0..255) attributes.
+fn insert_hue_saturation(attributes: &mut Map<String, Value>, hue: f64, saturation: f64) {
+    attributes.insert("hue".into(), (hue.round() as u16).into());
+    attributes.insert(
+        "saturation".into(),
+        ((saturation * 2.55).round() as u16).into(),
+    );
+}
+
+/// Get a color attribute with a fixed number of float values.
+fn color_values<const N: usize>(ha_attr: &Map<String, Value>, key: &str) -> Option<[f64; N]> {
+    let values = ha_attr.get(key)?.as_array()?;
+    if values.len() != N {
+        warn!("Invalid {key} value, expected {N} values: {values:?}");
+        return None;
+    }
+    let mut result = [0.0; N];
+    for (i, value) in values.iter().enumerate() {
+        result[i] = value.as_f64()?;
+    }
+    Some(result)
+}
+
+/// Get a color attribute with a fixed number of 0..255 color channel values.
+fn color_channels<const N: usize>(ha_attr: &Map<String, Value>, key: &str) -> Option<[u8; N]> {
+    color_values::<N>(ha_attr, key).map(|values| values.map(|v| v.clamp(0.0, 255.0) as u8))
+}
+
+/// Get the supported color temperature range in Kelvin of a light, if provided by HA.
+pub(crate) fn light_color_temp_kelvin_range(ha_attr: &Map<String, Value>) -> Option<(u16, u16)> {
+    let kelvin = |key: &str| ha_attr.get(key).and_then(|v| v.as_u64()).map(|v| v as u16);
+    Some((
+        kelvin("min_color_temp_kelvin")?,
+        kelvin("max_color_temp_kelvin")?,
+    ))
+}
+
+/// Get the supported color temperature range in Kelvin of a light, or the HA default range.
+pub(crate) fn light_kelvin_range(ha_attr: &Map<String, Value>) -> (u16, u16) {
+    light_color_temp_kelvin_range(ha_attr)
+        .unwrap_or((color::DEFAULT_MIN_KELVIN, color::DEFAULT_MAX_KELVIN))
+}
+
+/// Get the supported color temperature range in mireds of a light, or the HA default range.
+pub(crate) fn light_mired_range(ha_attr: &Map<String, Value>) -> (u16, u16) {
+    let mireds = |key: &str| ha_attr.get(key).and_then(|v| v.as_u64()).map(|v| v as u16);
+    (
+        mireds("min_mireds").unwrap_or(color::DEFAULT_MIN_MIREDS),
+        mireds("max_mireds").unwrap_or(color::DEFAULT_MAX_MIREDS),
+    )
+}
+
+/// Convert a color temperature in Kelvin to the remote percentage value.
+///
+/// Like with mireds, 0% is the coldest and 100% the warmest color temperature.
+fn color_temp_kelvin_to_percent(
+    value: u64,
+    min_kelvin: u16,
+    max_kelvin: u16,
+) -> Result<u16, ServiceError> {
+    if max_kelvin <= min_kelvin {
+        return Err(ServiceError::BadRequest(format!(
+            "Invalid min_color_temp_kelvin or max_color_temp_kelvin value! min={}, max={}",
+            min_kelvin, max_kelvin
+        )));
+    }
+    let value = (value as u16).clamp(min_kelvin, max_kelvin);
+
+    Ok(((max_kelvin - value) as u32 * 100 / (max_kelvin - min_kelvin) as u32) as u16)
+}
+
 fn color_temp_mired_to_percent(
     mut value: u64,
     min_mireds: u16,
@@ -138,18 +264,11 @@ fn color_temp_mired_to_percent(
         value = max_mireds as u64;
     }
 
-    Ok(((value as u16) - min_mireds) * 100 / (max_mireds - min_mireds))
+    Ok((((value as u16) - min_mireds) as u32 * 100 / (max_mireds - min_mireds) as u32) as u16)
 }
 
-pub(crate) fn convert_light_entity(
-    entity_id: String,
-    state: String,
-    ha_attr: &mut Map<String, Value>,
-) -> Result<AvailableIntgEntity, ServiceError> {
-    let friendly_name = ha_attr.get("friendly_name").and_then(|v| v.as_str());
-    let name = HashMap::from([("en".into(), friendly_name.unwrap_or(&entity_id).into())]);
-
-    // handle features
+/// Get the supported light features from the supported color modes of a light.
+pub(crate) fn light_features(ha_attr: &Map<String, Value>) -> Vec<LightFeature> {
     let mut light_feats = Vec::with_capacity(2);
     // OnOff is default
     light_feats.push(LightFeature::Toggle);
@@ -186,6 +305,33 @@ pub(crate) fn convert_light_entity(
         }
     }
 
+    light_feats
+}
+
+pub(crate) fn convert_light_entity(
+    entity_id: String,
+    state: String,
+    ha_attr: &mut Map<String, Value>,
+) -> Result<AvailableIntgEntity, ServiceError> {
+    let friendly_name = ha_attr.get("friendly_name").and_then(|v| v.as_str());
+    let name = HashMap::from([("en".into(), friendly_name.unwrap_or(&entity_id).into())]);
+
+    // handle features
+    let light_feats = light_features(ha_attr);
+
+    // handle options
+    let mut options = serde_json::Map::new();
+    if light_supports(ha_attr, LIGHT_SUPPORT_EFFECT) {
+        json::copy_entry(ha_attr, &mut options, "effect_list");
+    }
+    options.insert(
+        "flash".into(),
+        light_supports(ha_attr, LIGHT_SUPPORT_FLASH).into(),
+    );
+    options.insert(
+        "transition".into(),
+        light_supports(ha_attr, LIGHT_SUPPORT_TRANSITION).into(),
+    );
     // TODO color entity options: color_temperature_steps - do we get that from HASS? #8
 
     // convert attributes
@@ -199,16 +345,103 @@ pub(crate) fn convert_light_entity(
         name,
         features: Some(light_feats.into_iter().map(|v| v.to_string()).collect()),
         area: None,
-        options: None,
+        options: Some(options),
         attributes,
     })
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::client::entity::light::color_temp_mired_to_percent;
+    use crate::client::entity::light::{
+        color_temp_kelvin_to_percent, color_temp_mired_to_percent, map_light_attributes,
+    };
     use crate::errors::ServiceError;
     use rstest::rstest;
+    use serde_json::{json, Value};
+
+    #[rstest]
+    #[case(json!({ "color_mode": "hs", "brightness": 128, "hs_color": [30.0, 100.0] }), 30, 255, 128)]
+    #[case(json!({ "color_mode": "rgb", "brightness": 255, "rgb_color": [255, 0, 0] }), 0, 255, 255)]
+    #[case(json!({ "color_mode": "rgb", "brightness": 128, "rgb_color": [192, 64, 32] }), 12, 212, 96)]
+    #[case(json!({ "color_mode": "rgbw", "brightness": 255, "rgbw_color": [0, 0, 255, 0] }), 240, 255, 255)]
+    #[case(json!({ "color_mode": "rgbw", "brightness": 200, "rgbw_color": [128, 0, 0, 128] }), 0, 127, 100)]
+    #[case(json!({ "color_mode": "rgbww", "brightness": 255, "rgbww_color": [0, 255, 0, 0, 0] }), 120, 255, 255)]
+    #[case(json!({ "color_mode": "rgbww", "brightness": 255, "rgbww_color": [0, 0, 0, 0, 255], "min_color_temp_kelvin": 2000, "max_color_temp_kelvin": 6535 }), 31, 241, 255)]
+    #[case(json!({ "color_mode": "xy", "brightness": 100, "xy_color": [0.701, 0.299] }), 0, 255, 100)]
+    fn map_light_attributes_converts_color_modes(
+        #[case] attr: Value,
+        #[case] hue: u64,
+        #[case] saturation: u64,
+        #[case] brightness: u64,
+    ) {
+        let mut attr = attr.as_object().cloned().unwrap();
+        let attributes = map_light_attributes("light.test", "on", Some(&mut attr)).unwrap();
+
+        assert_eq!(Some(&json!(hue)), attributes.get("hue"), "hue");
+        assert_eq!(
+            Some(&json!(saturation)),
+            attributes.get("saturation"),
+            "saturation"
+        );
+        assert_eq!(
+            Some(&json!(brightness)),
+            attributes.get("brightness"),
+            "brightness"
+        );
+    }
+
+    #[rstest]
+    #[case(json!({ "color_mode": "color_temp", "color_temp": 325, "min_mireds": 150, "max_mireds": 500 }), 50)]
+    #[case(json!({ "color_mode": "color_temp", "color_temp": 370, "min_mireds": 250, "max_mireds": 454 }), 58)]
+    #[case(json!({ "color_mode": "color_temp", "color_temp_kelvin": 2700, "min_color_temp_kelvin": 2202, "max_color_temp_kelvin": 4000, "color_temp": 370, "min_mireds": 250, "max_mireds": 454 }), 72)]
+    fn map_light_attributes_uses_entity_color_temp_range(
+        #[case] attr: Value,
+        #[case] expected: u64,
+    ) {
+        let mut attr = attr.as_object().cloned().unwrap();
+        let attributes = map_light_attributes("light.test", "on", Some(&mut attr)).unwrap();
+
+        assert_eq!(Some(&json!(expected)), attributes.get("color_temperature"));
+    }
+
+    #[rstest]
+    #[case(6500, 0)]
+    #[case(7000, 0)]
+    #[case(4250, 50)]
+    #[case(2000, 100)]
+    #[case(1000, 100)]
+    fn color_temp_kelvin_to_percent_returns_scaled_values(
+        #[case] input: u64,
+        #[case] expected: u16,
+    ) {
+        assert_eq!(
+            Ok(expected),
+            color_temp_kelvin_to_percent(input, 2000, 6500)
+        );
+    }
+
+    #[rstest]
+    #[case(json!({ "supported_features": 4, "effect": "Rainbow" }), Some(json!("Rainbow")))]
+    #[case(json!({ "supported_features": 44, "effect": "off" }), Some(json!("off")))]
+    #[case(json!({ "supported_features": 32, "effect": "Rainbow" }), None)]
+    fn map_light_attributes_with_effect(#[case] attr: Value, #[case] expected: Option<Value>) {
+        let mut attr = attr.as_object().cloned().unwrap();
+        let attributes = map_light_attributes("light.test", "on", Some(&mut attr)).unwrap();
+
+        assert_eq!(expected.as_ref(), attributes.get("effect"));
+    }
+
+    #[test]
+    fn map_light_attributes_without_color_keeps_brightness() {
+        let mut attr = json!({ "color_mode": "brightness", "brightness": 42 })
+            .as_object()
+            .cloned()
+            .unwrap();
+        let attributes = map_light_attributes("light.test", "on", Some(&mut attr)).unwrap();
+
+        assert_eq!(Some(&json!(42)), attributes.get("brightness"));
+        assert_eq!(None, attributes.get("hue"));
+    }
 
     #[rstest]
     #[case(0, 0)]
@@ -254,4 +487,16 @@ mod tests {
 
         assert_eq!(Ok(expected), result);
     }
+
+    #[rstest]
+    #[case(0, 153)]
+    #[case(50, 577)]
+    #[case(99, 992)]
+    #[case(100, 1000)]
+    fn color_temp_mired_to_percent_with_wide_range_returns_scaled_values(
+        #[case] expected: u16,
+        #[case] input: u64,
+    ) {
+        assert_eq!(Ok(expected), color_temp_mired_to_percent(input, 153, 1000));
+    }
 }
@@ -3,13 +3,15 @@
 
 //! Media player entity specific logic.
 
+use crate::client::entity::entity_picture_url;
 use crate::client::event::convert_ha_onoff_state;
-use crate::client::model::EventData;
+use crate::client::model::{EventData, EventState};
 use crate::errors::ServiceError;
 use crate::util::json;
-use log::error;
 use serde_json::{Map, Value};
 use std::collections::HashMap;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
 use uc_api::intg::{AvailableIntgEntity, EntityChange};
 use uc_api::{EntityType, MediaPlayerDeviceClass, MediaPlayerFeature};
 use url::Url;
@@ -35,14 +37,21 @@ pub const SUPPORT_SELECT_SOUND_MODE: u32 = 65536;
 pub const SUPPORT_REPEAT_SET: u32 = 262144;
 // pub const SUPPORT_GROUPING: u32 = 524288;
 
+/// Allowed difference in seconds between a reported and the interpolated media position.
+const MEDIA_POSITION_TOLERANCE: f64 = 2.0;
+
 pub(crate) fn map_media_player_attributes(
     server: &Url,
     _entity_id: &str,
     state: &str,
     ha_attr: Option<&mut Map<String, Value>>,
+    now: OffsetDateTime,
 ) -> Result<Map<String, Value>, ServiceError> {
     let mut attributes = serde_json::Map::with_capacity(8);
 
+    let position = ha_attr
+        .as_deref()
+        .and_then(|attr| media_position_at(state, attr, now));
     let state = match state {
         "playing" | "paused" | "standby" | "buffering" => state.to_uppercase().into(),
         "idle" => "ON".into(),
@@ -55,7 +64,9 @@ pub(crate) fn map_media_player_attributes(
             attributes.insert("volume".into(), ((value * 100.0).round() as u64).into());
         }
         json::move_value(ha_attr, &mut attributes, "is_volume_muted", "muted");
-        json::move_entry(ha_attr, &mut attributes, "media_position");
+        if let Some(position) = position {
+            attributes.insert("media_position".into(), (position.round() as u64).into());
+        }
         json::move_entry(ha_attr, &mut attributes, "media_duration");
         json::move_entry(ha_attr, &mut attributes, "media_title");
         json::move_entry(ha_attr, &mut attributes, "media_artist");
@@ -70,28 +81,12 @@ pub(crate) fn map_media_player_attributes(
         json::move_entry(ha_attr, &mut attributes, "sound_mode");
         json::move_entry(ha_attr, &mut attributes, "sound_mode_list");
 
-        if let Some(value) = ha_attr.get("entity_picture").and_then(|v| v.as_str()) {
-            // let's hope it's only http, https or a local path :-)
-            if value.starts_with("http") {
-                attributes.insert("media_image_url".into(), value.into());
-            } else if value.starts_with('/') {
-                // `url.set_path(value)` doesn't work since the HA path contains query params as well
-                // or we'd have to decode `%3F` -> `?` (and maybe other chars as well).
-                // Let's try the simple (and dangerous) approach first which also worked in YIO v1
-                attributes.insert(
-                    "media_image_url".into(),
-                    format!(
-                        "{}://{}:{}{}",
-                        server.scheme(),
-                        server.host_str().unwrap_or_default(),
-                        server.port_or_known_default().unwrap_or_default(),
-                        value
-                    )
-                    .into(),
-                );
-            } else {
-                error!("Unexpected entity_picture format: {value}");
-            }
+        if let Some(url) = ha_attr
+            .get("entity_picture")
+            .and_then(|v| v.as_str())
+            .and_then(|v| entity_picture_url(server, v))
+        {
+            attributes.insert("media_image_url".into(), url.into());
         }
     }
 
@@ -107,6 +102,7 @@ pub(crate) fn media_player_event_to_entity_change(
         &data.entity_id,
         &data.new_state.state,
         data.new_state.attributes.as_mut(),
+        OffsetDateTime::now_utc(),
     )?;
 
     Ok(EntityChange {
@@ -144,14 +140,16 @@ pub(crate) fn convert_media_player_entity(
     if supported_features & SUPPORT_VOLUME_SET > 0 {
         media_feats.push(MediaPlayerFeature::Volume);
     }
-    if supported_features & SUPPORT_VOLUME_STEP > 0 {
+    // volume steps are emulated with volume_set if not supported
+    if supported_features & (SUPPORT_VOLUME_STEP | SUPPORT_VOLUME_SET) > 0 {
         media_feats.push(MediaPlayerFeature::VolumeUpDown);
     }
     if supported_features & SUPPORT_SELECT_SOURCE > 0 {
         media_feats.push(MediaPlayerFeature::SelectSource);
     }
     if supported_features & SUPPORT_VOLUME_MUTE > 0 {
-        // HASS media player doesn't support mute toggle!
+        // HASS media player doesn't support mute toggle: emulated with the current mute state
+        media_feats.push(MediaPlayerFeature::MuteToggle);
         media_feats.push(MediaPlayerFeature::Mute);
         media_feats.push(MediaPlayerFeature::Unmute);
     }
@@ -178,6 +176,9 @@ pub(crate) fn convert_media_player_entity(
     }
     if supported_features & SUPPORT_SEEK > 0 {
         media_feats.push(MediaPlayerFeature::Seek);
+        // emulated with a relative seek from the current position
+        media_feats.push(MediaPlayerFeature::FastForward);
+        media_feats.push(MediaPlayerFeature::Rewind);
         media_feats.push(MediaPlayerFeature::MediaDuration);
         media_feats.push(MediaPlayerFeature::MediaPosition);
     }
@@ -199,6 +200,7 @@ pub(crate) fn convert_media_player_entity(
         &entity_id,
         &state,
         Some(ha_attr),
+        OffsetDateTime::now_utc(),
     )?);
 
     Ok(AvailableIntgEntity {
@@ -213,3 +215,145 @@ pub(crate) fn convert_media_player_entity(
         attributes,
     })
 }
+
+/// Get the media position in seconds at the given time.
+///
+/// HA doesn't send position updates during playback: the position of a playing media player is
+/// interpolated from `media_position_updated_at` and limited to the media duration.
+pub(crate) fn media_position_at(
+    state: &str,
+    ha_attr: &Map<String, Value>,
+    now: OffsetDateTime,
+) -> Option<f64> {
+    let position = ha_attr.get("media_position").and_then(|v| v.as_f64())?;
+    let updated_at = ha_attr
+        .get("media_position_updated_at")
+        .and_then(|v| v.as_str())
+        .and_then(|v| OffsetDateTime::parse(v, &Rfc3339).ok());
+
+    let position = match (state, updated_at) {
+        ("playing", Some(updated_at)) => position + (now - updated_at).as_seconds_f64().max(0.0),
+        _ => position,
+    };
+
+    Some(
+        match ha_attr.get("media_duration").and_then(|v| v.as_f64()) {
+            Some(duration) if duration > 0.0 => position.min(duration),
+            _ => position,
+        },
+    )
+}
+
+/// Check if a media player state change only refreshes the media position.
+///
+/// HA periodically updates `media_position` and `media_position_updated_at` of a playing media
+/// player. Such a change doesn't need to be sent if the reported position matches the interpolated
+/// position of the previous state.
+pub(crate) fn is_media_position_refresh(
+    old_state: &EventState,
+    new_state: &EventState,
+    now: OffsetDateTime,
+) -> bool {
+    let (old_attr, new_attr) = match (&old_state.attributes, &new_state.attributes) {
+        (Some(old_attr), Some(new_attr)) => (old_attr, new_attr),
+        _ => return false,
+    };
+    if old_state.state != new_state.state {
+        return false;
+    }
+    let is_position = |key: &String| key == "media_position" || key == "media_position_updated_at";
+    let other_changes = old_attr.len() != new_attr.len()
+        || new_attr
+            .iter()
+            .filter(|(key, _)| !is_position(key))
+            .any(|(key, value)| old_attr.get(key) != Some(value));
+    if other_changes {
+        return false;
+    }
+
+    match (
+        media_position_at(&old_state.state, old_attr, now),
+        media_position_at(&new_state.state, new_attr, now),
+    ) {
+        (Some(old), Some(new)) => (old - new).abs() < MEDIA_POSITION_TOLERANCE,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use serde_json::json;
+
+    #[rstest]
+    #[case("playing", json!({ "media_position": 60, "media_position_updated_at": "2023-08-01T10:00:00+00:00" }), Some(90.0))]
+    #[case("playing", json!({ "media_position": 60, "media_duration": 80, "media_position_updated_at": "2023-08-01T10:00:00+00:00" }), Some(80.0))]
+    #[case("playing", json!({ "media_position": 60 }), Some(60.0))]
+    #[case("paused", json!({ "media_position": 60, "media_position_updated_at": "2023-08-01T10:00:00+00:00" }), Some(60.0))]
+    #[case("playing", json!({ "media_position_updated_at": "2023-08-01T10:00:00+00:00" }), None)]
+    fn media_position_is_interpolated(
+        #[case] state: &str,
+        #[case] attr: Value,
+        #[case] expected: Option<f64>,
+    ) {
+        let now = OffsetDateTime::parse("2023-08-01T10:00:30Z", &Rfc3339).unwrap();
+        let attr = attr.as_object().cloned().unwrap();
+
+        assert_eq!(expected, media_position_at(state, &attr, now));
+    }
+
+    #[test]
+    fn media_position_attribute_is_interpolated() {
+        let server = Url::parse("http://hassio.local:8123").unwrap();
+        let now = OffsetDateTime::parse("2023-08-01T10:00:30Z", &Rfc3339).unwrap();
+        let mut attr = json!({ "media_position": 60.4, "media_duration": 300, "media_position_updated_at": "2023-08-01T10:00:00+00:00" })
+            .as_object()
+            .cloned()
+            .unwrap();
+        let attributes = map_media_player_attributes(
+            &server,
+            "media_player.kitchen",
+            "playing",
+            Some(&mut attr),
+            now,
+        )
+        .unwrap();
+
+        assert_eq!(Some(&json!(90)), attributes.get("media_position"));
+    }
+
+    fn new_state(state: &str, attributes: Value) -> EventState {
+        EventState {
+            state: state.into(),
+            attributes: attributes.as_object().cloned(),
+        }
+    }
+
+    #[rstest]
+    // reported position matches the interpolated position
+    #[case("playing", json!({ "media_title": "Song", "media_position": 89, "media_position_updated_at": "2023-08-01T10:00:29+00:00" }), true)]
+    #[case("playing", json!({ "media_title": "Song", "media_position": 60, "media_position_updated_at": "2023-08-01T10:00:00+00:00" }), true)]
+    // seek
+    #[case("playing", json!({ "media_title": "Song", "media_position": 150, "media_position_updated_at": "2023-08-01T10:00:29+00:00" }), false)]
+    // other attribute change
+    #[case("playing", json!({ "media_title": "Next song", "media_position": 91, "media_position_updated_at": "2023-08-01T10:00:29+00:00" }), false)]
+    // state change
+    #[case("paused", json!({ "media_title": "Song", "media_position": 90, "media_position_updated_at": "2023-08-01T10:00:30+00:00" }), false)]
+    fn media_position_refresh_is_detected(
+        #[case] state: &str,
+        #[case] attr: Value,
+        #[case] expected: bool,
+    ) {
+        let now = OffsetDateTime::parse("2023-08-01T10:00:30Z", &Rfc3339).unwrap();
+        let old_state = new_state(
+            "playing",
+            json!({ "media_title": "Song", "media_position": 60, "media_position_updated_at": "2023-08-01T10:00:00+00:00" }),
+        );
+
+        assert_eq!(
+            expected,
+            is_media_position_refresh(&old_state, &new_state(state, attr), now)
+        );
+    }
+}
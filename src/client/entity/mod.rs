@@ -3,18 +3,114 @@
 
 //! Home Assistant entity helper functions.
 
+use log::error;
+use url::Url;
+
+mod attribute_sensor;
 mod button;
+mod camera;
 mod climate;
+mod counter;
 mod cover;
+mod event_entity;
+mod group;
+mod humidifier;
+mod input_datetime;
 mod light;
 mod media_player;
+mod number;
+mod presence;
+mod remote;
+mod select;
 mod sensor;
+mod siren;
 mod switch;
+mod temperature;
+mod template;
+mod text;
+mod timer;
+mod update;
+mod valve;
+mod water_heater;
+mod weather;
 
+pub(crate) use attribute_sensor::*;
 pub(crate) use button::*;
+pub(crate) use camera::*;
 pub(crate) use climate::*;
+pub(crate) use counter::*;
 pub(crate) use cover::*;
+pub(crate) use event_entity::*;
+pub(crate) use group::*;
+pub(crate) use humidifier::*;
+pub(crate) use input_datetime::*;
 pub(crate) use light::*;
 pub(crate) use media_player::*;
+pub(crate) use number::*;
+pub(crate) use presence::*;
+pub(crate) use remote::*;
+pub(crate) use select::*;
 pub(crate) use sensor::*;
+pub(crate) use siren::*;
 pub(crate) use switch::*;
+pub(crate) use temperature::*;
+pub(crate) use template::*;
+pub(crate) use text::*;
+pub(crate) use timer::*;
+pub(crate) use update::*;
+pub(crate) use valve::*;
+pub(crate) use water_heater::*;
+pub(crate) use weather::*;
+
+/// Convert a HA `entity_picture` attribute value to an absolute URL.
+///
+/// Relative HA paths are resolved with the given HA server address.
+pub(crate) fn entity_picture_url(server: &Url, value: &str) -> Option<String> {
+    // let's hope it's only http, https or a local path :-)
+    if value.starts_with("http") {
+        Some(value.into())
+    } else if value.starts_with('/') {
+        // `url.set_path(value)` doesn't work since the HA path contains query params as well
+        // or we'd have to decode `%3F` -> `?` (and maybe other chars as well).
+        // Let's try the simple (and dangerous) approach first which also worked in YIO v1
+        Some(format!(
+            "{}://{}:{}{}",
+            server.scheme(),
+            server.host_str().unwrap_or_default(),
+            server.port_or_known_default().unwrap_or_default(),
+            value
+        ))
+    } else {
+        error!("Unexpected entity_picture format: {value}");
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::entity_picture_url;
+    use rstest::rstest;
+    use url::Url;
+
+    #[rstest]
+    #[case("https://example.com/cover.jpg", Some("https://example.com/cover.jpg"))]
+    #[case(
+        "/api/media_player_proxy/media_player.kitchen?token=1234",
+        Some("http://hassio.local:8123/api/media_player_proxy/media_player.kitchen?token=1234")
+    )]
+    #[case(
+        "/api/image/serve/123/512x512",
+        Some("http://hassio.local:8123/api/image/serve/123/512x512")
+    )]
+    #[case("image.jpg", None)]
+    fn entity_picture_url_returns_absolute_url(
+        #[case] value: &str,
+        #[case] expected: Option<&str>,
+    ) {
+        let server = Url::parse("http://hassio.local:8123").unwrap();
+        assert_eq!(
+            expected.map(|v| v.to_string()),
+            entity_picture_url(&server, value)
+        );
+    }
+}
@@ -0,0 +1,246 @@
+// Copyright (c) 2023 Unfolded Circle ApS, Markus Zehnder <markus.z@unfoldedcircle.com>
+// SPDX-License-Identifier: MPL-2.0
+
+//! Number and input_number entity specific logic.
+//!
+//! There's no number entity in the Integration-API: a number is exposed as a sensor with the
+//! value range and step size as entity options.
+
+use crate::client::entity::device_class_to_label;
+use crate::client::model::EventData;
+use crate::errors::ServiceError;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use uc_api::intg::{AvailableIntgEntity, EntityChange};
+use uc_api::{EntityType, SensorOption};
+
+pub(crate) fn map_number_attributes(
+    _entity_id: &str,
+    state: &str,
+    ha_attr: Option<&mut Map<String, Value>>,
+) -> Result<Map<String, Value>, ServiceError> {
+    let mut attributes = serde_json::Map::with_capacity(2);
+
+    let value = match (state.parse::<f64>(), ha_attr.as_deref()) {
+        (Ok(value), Some(ha_attr)) => {
+            let range = NumberRange::from(ha_attr);
+            range.json_value(range.round(value))
+        }
+        (Ok(value), None) => value.into(),
+        // unavailable, unknown
+        (Err(_), _) => state.into(),
+    };
+    attributes.insert("value".into(), value);
+
+    if let Some(ha_attr) = ha_attr {
+        if let Some(uom) = ha_attr.remove("unit_of_measurement") {
+            attributes.insert("unit".into(), uom);
+        }
+    }
+
+    Ok(attributes)
+}
+
+pub(crate) fn number_event_to_entity_change(
+    mut data: EventData,
+) -> Result<EntityChange, ServiceError> {
+    let attributes = map_number_attributes(
+        &data.entity_id,
+        &data.new_state.state,
+        data.new_state.attributes.as_mut(),
+    )?;
+
+    Ok(EntityChange {
+        device_id: None,
+        entity_type: EntityType::Sensor,
+        entity_id: data.entity_id,
+        attributes,
+    })
+}
+
+pub(crate) fn convert_number_entity(
+    entity_id: String,
+    state: String,
+    ha_attr: &mut Map<String, Value>,
+) -> Result<AvailableIntgEntity, ServiceError> {
+    let friendly_name = ha_attr.get("friendly_name").and_then(|v| v.as_str());
+    let name = HashMap::from([("en".into(), friendly_name.unwrap_or(&entity_id).into())]);
+
+    // handle options
+    let range = NumberRange::from(&*ha_attr);
+    let mut options = serde_json::Map::new();
+    if let Some(label) = ha_attr
+        .get("device_class")
+        .and_then(|v| v.as_str())
+        .and_then(device_class_to_label)
+    {
+        options.insert(SensorOption::CustomLabel.to_string(), Value::String(label));
+    }
+    if let Some(v) = ha_attr.get("unit_of_measurement") {
+        options.insert(SensorOption::CustomUnit.to_string(), v.clone());
+    }
+    options.insert(
+        SensorOption::MinValue.to_string(),
+        range.json_value(range.min),
+    );
+    options.insert(
+        SensorOption::MaxValue.to_string(),
+        range.json_value(range.max),
+    );
+    options.insert(SensorOption::Decimals.to_string(), range.decimals.into());
+    options.insert("step".into(), range.json_value(range.step));
+    if let Some(mode) = ha_attr.get("mode").and_then(|v| v.as_str()) {
+        options.insert("mode".into(), mode.into());
+    }
+
+    // convert attributes
+    let attributes = Some(map_number_attributes(&entity_id, &state, Some(ha_attr))?);
+
+    Ok(AvailableIntgEntity {
+        entity_id,
+        device_id: None, // prepared for device_id handling
+        entity_type: EntityType::Sensor,
+        device_class: Some("custom".into()),
+        name,
+        features: None,
+        area: None,
+        options: Some(options),
+        attributes,
+    })
+}
+
+/// Value range of a HA number entity.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct NumberRange {
+    pub min: f64,
+    pub max: f64,
+    pub step: f64,
+    /// Number of decimal places defined by `min` and `step`.
+    pub decimals: u32,
+}
+
+impl From<&Map<String, Value>> for NumberRange {
+    /// Retrieve the value range from the HA entity attributes.
+    ///
+    /// Missing attributes are set to the HA defaults of the number entity: min=0, max=100, step=1.
+    fn from(ha_attr: &Map<String, Value>) -> Self {
+        let min = ha_attr.get("min").and_then(|v| v.as_f64()).unwrap_or(0.0);
+        let max = ha_attr.get("max").and_then(|v| v.as_f64()).unwrap_or(100.0);
+        let step = ha_attr
+            .get("step")
+            .and_then(|v| v.as_f64())
+            .filter(|v| *v > 0.0)
+            .unwrap_or(1.0);
+        NumberRange::new(min, max, step)
+    }
+}
+
+impl NumberRange {
+    pub fn new(min: f64, max: f64, step: f64) -> Self {
+        Self {
+            min,
+            max,
+            step,
+            decimals: decimal_places(min).max(decimal_places(step)),
+        }
+    }
+
+    /// Round a value to the nearest step within the value range.
+    ///
+    /// Values outside the range are clamped to the min or max value. The result is rounded to
+    /// the number of decimal places of the step size to remove floating point artifacts like
+    /// `0.30000000000000004`.
+    pub fn round(&self, value: f64) -> f64 {
+        let steps = ((value - self.min) / self.step).round();
+        let factor = 10_f64.powi(self.decimals as i32);
+        let value = ((self.min + steps * self.step) * factor).round() / factor;
+        value.clamp(self.min, self.max.max(self.min))
+    }
+
+    /// Convert a number to a json value: an integer number is used if there are no decimal places.
+    pub fn json_value(&self, value: f64) -> Value {
+        if self.decimals == 0 && value.fract() == 0.0 {
+            Value::from(value as i64)
+        } else {
+            Value::from(value)
+        }
+    }
+}
+
+/// Get the number of decimal places of a number in its shortest representation.
+fn decimal_places(value: f64) -> u32 {
+    value
+        .to_string()
+        .split_once('.')
+        .map(|(_, d)| d.len() as u32)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decimal_places, NumberRange};
+    use rstest::rstest;
+    use serde_json::json;
+
+    #[rstest]
+    #[case(1.0, 0)]
+    #[case(10.0, 0)]
+    #[case(0.5, 1)]
+    #[case(0.1, 1)]
+    #[case(0.25, 2)]
+    #[case(-0.001, 3)]
+    fn decimal_places_returns_number_of_decimals(#[case] value: f64, #[case] expected: u32) {
+        assert_eq!(expected, decimal_places(value));
+    }
+
+    #[rstest]
+    #[case(0.0, 100.0, 1.0, 42.4, 42.0)]
+    #[case(0.0, 100.0, 1.0, 42.5, 43.0)]
+    #[case(0.0, 1.0, 0.1, 0.3, 0.3)]
+    #[case(0.0, 1.0, 0.1, 0.1 + 0.2, 0.3)]
+    #[case(0.0, 1.0, 0.1, 0.46, 0.5)]
+    #[case(-10.0, 10.0, 0.5, -3.3, -3.5)]
+    #[case(-10.0, 10.0, 0.5, -3.2, -3.0)]
+    #[case(0.5, 10.0, 1.0, 3.0, 3.5)]
+    #[case(0.0, 15.0, 5.0, 12.0, 10.0)]
+    #[case(0.0, 15.0, 5.0, 13.0, 15.0)]
+    fn round_returns_nearest_step(
+        #[case] min: f64,
+        #[case] max: f64,
+        #[case] step: f64,
+        #[case] value: f64,
+        #[case] expected: f64,
+    ) {
+        let range = NumberRange::new(min, max, step);
+        assert_eq!(expected, range.round(value));
+    }
+
+    #[rstest]
+    #[case(-1.0, 0.0)]
+    #[case(100.4, 100.0)]
+    #[case(1000.0, 100.0)]
+    fn round_clamps_value_to_range(#[case] value: f64, #[case] expected: f64) {
+        let range = NumberRange::new(0.0, 100.0, 1.0);
+        assert_eq!(expected, range.round(value));
+    }
+
+    #[test]
+    fn range_from_attributes_uses_defaults() {
+        let attr = json!({ "friendly_name": "Bass level" });
+        let range = NumberRange::from(attr.as_object().unwrap());
+        assert_eq!(NumberRange::new(0.0, 100.0, 1.0), range);
+    }
+
+    #[rstest]
+    #[case(1.0, 5.0, json!(5))]
+    #[case(0.5, 5.0, json!(5.0))]
+    #[case(0.5, 5.5, json!(5.5))]
+    fn json_value_returns_integer_without_decimals(
+        #[case] step: f64,
+        #[case] value: f64,
+        #[case] expected: serde_json::Value,
+    ) {
+        let range = NumberRange::new(0.0, 10.0, step);
+        assert_eq!(expected, range.json_value(value));
+    }
+}
@@ -0,0 +1,103 @@
+// Copyright (c) 2023 Unfolded Circle ApS, Markus Zehnder <markus.z@unfoldedcircle.com>
+// SPDX-License-Identifier: MPL-2.0
+
+//! Person, device_tracker and zone entity specific logic.
+//!
+//! Presence entities are exposed as sensors:
+//! - person & device_tracker: the value is the zone name, e.g. `home`, `not_home` or a custom zone.
+//! - zone: the value is the number of persons in the zone.
+
+use crate::client::entity::entity_picture_url;
+use crate::client::model::EventData;
+use crate::errors::ServiceError;
+use crate::util::json;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use uc_api::intg::{AvailableIntgEntity, EntityChange};
+use uc_api::{EntityType, SensorOption};
+use url::Url;
+
+pub(crate) fn map_presence_attributes(
+    server: &Url,
+    _entity_id: &str,
+    state: &str,
+    ha_attr: Option<&mut Map<String, Value>>,
+) -> Result<Map<String, Value>, ServiceError> {
+    let mut attributes = serde_json::Map::with_capacity(3);
+    attributes.insert("value".into(), state.into());
+
+    if let Some(ha_attr) = ha_attr {
+        if let Some(url) = ha_attr
+            .get("entity_picture")
+            .and_then(|v| v.as_str())
+            .and_then(|v| entity_picture_url(server, v))
+        {
+            attributes.insert("image_url".into(), url.into());
+        }
+        // mobile app device trackers use `battery_level`, some other integrations `battery`
+        if !json::move_entry(ha_attr, &mut attributes, "battery_level") {
+            json::move_value(ha_attr, &mut attributes, "battery", "battery_level");
+        }
+        json::move_entry(ha_attr, &mut attributes, "battery_charging");
+    }
+
+    Ok(attributes)
+}
+
+pub(crate) fn presence_event_to_entity_change(
+    server: &Url,
+    mut data: EventData,
+) -> Result<EntityChange, ServiceError> {
+    let attributes = map_presence_attributes(
+        server,
+        &data.entity_id,
+        &data.new_state.state,
+        data.new_state.attributes.as_mut(),
+    )?;
+
+    Ok(EntityChange {
+        device_id: None,
+        entity_type: EntityType::Sensor,
+        entity_id: data.entity_id,
+        attributes,
+    })
+}
+
+pub(crate) fn convert_presence_entity(
+    server: &Url,
+    entity_id: String,
+    state: String,
+    ha_attr: &mut Map<String, Value>,
+) -> Result<AvailableIntgEntity, ServiceError> {
+    let friendly_name = ha_attr.get("friendly_name").and_then(|v| v.as_str());
+    let name = HashMap::from([("en".into(), friendly_name.unwrap_or(&entity_id).into())]);
+
+    // handle options
+    let label = match entity_id.split_once('.') {
+        Some(("zone", _)) => "Persons",
+        Some(("person", _)) => "Presence",
+        _ => "Location",
+    };
+    let mut options = serde_json::Map::new();
+    options.insert(SensorOption::CustomLabel.to_string(), label.into());
+
+    // convert attributes
+    let attributes = Some(map_presence_attributes(
+        server,
+        &entity_id,
+        &state,
+        Some(ha_attr),
+    )?);
+
+    Ok(AvailableIntgEntity {
+        entity_id,
+        device_id: None, // prepared for device_id handling
+        entity_type: EntityType::Sensor,
+        device_class: Some("custom".into()),
+        name,
+        features: None,
+        area: None,
+        options: Some(options),
+        attributes,
+    })
+}
@@ -0,0 +1,101 @@
+// Copyright (c) 2023 Unfolded Circle ApS, Markus Zehnder <markus.z@unfoldedcircle.com>
+// SPDX-License-Identifier: MPL-2.0
+
+//! Remote entity specific logic.
+
+use crate::client::event::convert_ha_onoff_state;
+use crate::client::model::EventData;
+use crate::configuration::RemoteSettings;
+use crate::errors::ServiceError;
+use crate::util::json;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use uc_api::intg::{AvailableIntgEntity, EntityChange};
+use uc_api::EntityType;
+
+// https://developers.home-assistant.io/docs/core/entity/remote#supported-features
+// pub const REMOTE_SUPPORT_LEARN_COMMAND: u32 = 1;
+// pub const REMOTE_SUPPORT_DELETE_COMMAND: u32 = 2;
+pub const REMOTE_SUPPORT_ACTIVITY: u32 = 4;
+
+pub(crate) fn map_remote_attributes(
+    _entity_id: &str,
+    state: &str,
+    ha_attr: Option<&mut Map<String, Value>>,
+) -> Result<Map<String, Value>, ServiceError> {
+    let mut attributes = serde_json::Map::with_capacity(2);
+    let state = convert_ha_onoff_state(state)?;
+
+    attributes.insert("state".into(), state);
+
+    if let Some(ha_attr) = ha_attr {
+        json::move_value(ha_attr, &mut attributes, "current_activity", "activity");
+    }
+
+    Ok(attributes)
+}
+
+pub(crate) fn remote_event_to_entity_change(
+    mut data: EventData,
+) -> Result<EntityChange, ServiceError> {
+    let attributes = map_remote_attributes(
+        &data.entity_id,
+        &data.new_state.state,
+        data.new_state.attributes.as_mut(),
+    )?;
+
+    Ok(EntityChange {
+        device_id: None,
+        entity_type: EntityType::Remote,
+        entity_id: data.entity_id,
+        attributes,
+    })
+}
+
+pub(crate) fn convert_remote_entity(
+    entity_id: String,
+    state: String,
+    ha_attr: &mut Map<String, Value>,
+    settings: Option<&RemoteSettings>,
+) -> Result<AvailableIntgEntity, ServiceError> {
+    let friendly_name = ha_attr.get("friendly_name").and_then(|v| v.as_str());
+    let name = HashMap::from([("en".into(), friendly_name.unwrap_or(&entity_id).into())]);
+
+    // handle features: sending commands is always possible, the available commands depend on the
+    // HA integration and are either configured by the user or learned in HA.
+    let features = vec!["on_off".into(), "toggle".into(), "send_cmd".into()];
+
+    // handle options
+    let mut options = serde_json::Map::new();
+    if let Some(settings) = settings {
+        if !settings.commands.is_empty() {
+            options.insert("simple_commands".into(), settings.commands.clone().into());
+        }
+    }
+    let supported_features = ha_attr
+        .get("supported_features")
+        .and_then(|v| v.as_u64())
+        .unwrap_or_default() as u32;
+    if supported_features & REMOTE_SUPPORT_ACTIVITY > 0 {
+        json::copy_entry(ha_attr, &mut options, "activity_list");
+    }
+
+    // convert attributes
+    let attributes = Some(map_remote_attributes(&entity_id, &state, Some(ha_attr))?);
+
+    Ok(AvailableIntgEntity {
+        entity_id,
+        device_id: None, // prepared for device_id handling
+        entity_type: EntityType::Remote,
+        device_class: None,
+        name,
+        features: Some(features),
+        area: None,
+        options: if options.is_empty() {
+            None
+        } else {
+            Some(options)
+        },
+        attributes,
+    })
+}
@@ -108,7 +108,7 @@ pub(crate) fn convert_sensor_entity(
     })
 }
 
-fn device_class_to_label(class: &str) -> Option<String> {
+pub(crate) fn device_class_to_label(class: &str) -> Option<String> {
     let name = class.replace('_', " ");
     let mut c = name.chars();
     c.next()
@@ -0,0 +1,89 @@
+// Copyright (c) 2023 Unfolded Circle ApS, Markus Zehnder <markus.z@unfoldedcircle.com>
+// SPDX-License-Identifier: MPL-2.0
+
+//! Siren entity specific logic.
+//!
+//! A siren is exposed as switch entity. The optional tone, duration and volume level can be set
+//! with the `on` command.
+
+use crate::client::entity::map_switch_attributes;
+use crate::client::model::EventData;
+use crate::errors::ServiceError;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use uc_api::intg::{AvailableIntgEntity, EntityChange};
+use uc_api::EntityType;
+
+// https://developers.home-assistant.io/docs/core/entity/siren#supported-features
+// pub const SIREN_SUPPORT_TURN_ON: u32 = 1;
+// pub const SIREN_SUPPORT_TURN_OFF: u32 = 2;
+pub const SIREN_SUPPORT_TONES: u32 = 4;
+pub const SIREN_SUPPORT_VOLUME_SET: u32 = 8;
+pub const SIREN_SUPPORT_DURATION: u32 = 16;
+
+pub(crate) fn siren_event_to_entity_change(
+    mut data: EventData,
+) -> Result<EntityChange, ServiceError> {
+    let attributes = map_switch_attributes(
+        &data.entity_id,
+        &data.new_state.state,
+        data.new_state.attributes.as_mut(),
+    )?;
+
+    Ok(EntityChange {
+        device_id: None,
+        entity_type: EntityType::Switch,
+        entity_id: data.entity_id,
+        attributes,
+    })
+}
+
+pub(crate) fn convert_siren_entity(
+    entity_id: String,
+    state: String,
+    ha_attr: &mut Map<String, Value>,
+) -> Result<AvailableIntgEntity, ServiceError> {
+    let friendly_name = ha_attr.get("friendly_name").and_then(|v| v.as_str());
+    let name = HashMap::from([("en".into(), friendly_name.unwrap_or(&entity_id).into())]);
+
+    // handle features
+    let supported_features = ha_attr
+        .get("supported_features")
+        .and_then(|v| v.as_u64())
+        .unwrap_or_default() as u32;
+    let mut features = vec!["toggle".to_string()]; // OnOff is a default feature
+    let mut options = serde_json::Map::new();
+
+    if supported_features & SIREN_SUPPORT_TONES > 0 {
+        features.push("tone".into());
+        // either a list of tone names or a map of tone ids to names
+        if let Some(tones) = ha_attr.get("available_tones") {
+            options.insert("available_tones".into(), tones.clone());
+        }
+    }
+    if supported_features & SIREN_SUPPORT_VOLUME_SET > 0 {
+        features.push("volume".into());
+    }
+    if supported_features & SIREN_SUPPORT_DURATION > 0 {
+        features.push("duration".into());
+    }
+
+    // convert attributes
+    let attributes = Some(map_switch_attributes(&entity_id, &state, Some(ha_attr))?);
+
+    Ok(AvailableIntgEntity {
+        entity_id,
+        device_id: None, // prepared for device_id handling
+        entity_type: EntityType::Switch,
+        device_class: None,
+        name,
+        features: Some(features),
+        area: None,
+        options: if options.is_empty() {
+            None
+        } else {
+            Some(options)
+        },
+        attributes,
+    })
+}
@@ -0,0 +1,430 @@
+// Copyright (c) 2023 Unfolded Circle ApS, Markus Zehnder <markus.z@unfoldedcircle.com>
+// SPDX-License-Identifier: MPL-2.0
+
+//! Temperature unit conversion of climate entities, weather entities and temperature sensors.
+//!
+//! Climate and water heater temperatures are reported in the HA unit system, weather temperatures
+//! in the `temperature_unit` attribute and temperature sensors in the `unit_of_measurement` of the
+//! sensor. Converted values are rounded to one decimal, climate set points to the temperature step.
+//!
+//! Attribute sensors are not converted: the unit of an entity attribute is not known.
+
+use crate::configuration::TemperatureUnit;
+use serde_json::{Map, Value};
+
+/// Temperature attributes of climate and water heater entities.
+const CLIMATE_TEMPERATURES: [&str; 5] = [
+    "temperature",
+    "target_temp_high",
+    "target_temp_low",
+    "min_temp",
+    "max_temp",
+];
+
+/// Temperature attributes of weather entities and weather forecast entries.
+const WEATHER_TEMPERATURES: [&str; 4] = [
+    "temperature",
+    "apparent_temperature",
+    "dew_point",
+    "templow",
+];
+
+/// Conversion of temperature command values from the preferred unit to the HA unit system.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct TemperatureConversion {
+    /// Preferred temperature unit of the remote.
+    pub unit: TemperatureUnit,
+    /// Temperature unit of the HA unit system.
+    pub ha_unit: TemperatureUnit,
+}
+
+/// Get the temperature unit from a HA unit symbol, e.g. `°C`.
+pub(crate) fn temperature_unit_from_symbol(symbol: &str) -> Option<TemperatureUnit> {
+    match symbol {
+        "°C" | "C" => Some(TemperatureUnit::Celsius),
+        "°F" | "F" => Some(TemperatureUnit::Fahrenheit),
+        _ => None,
+    }
+}
+
+fn temperature_unit_symbol(unit: TemperatureUnit) -> &'static str {
+    match unit {
+        TemperatureUnit::Celsius => "°C",
+        TemperatureUnit::Fahrenheit => "°F",
+    }
+}
+
+/// Value of the `temperature_unit` climate entity option.
+fn temperature_unit_option(unit: TemperatureUnit) -> &'static str {
+    match unit {
+        TemperatureUnit::Celsius => "CELSIUS",
+        TemperatureUnit::Fahrenheit => "FAHRENHEIT",
+    }
+}
+
+pub(crate) fn convert_temperature(value: f64, from: TemperatureUnit, to: TemperatureUnit) -> f64 {
+    match (from, to) {
+        (TemperatureUnit::Celsius, TemperatureUnit::Fahrenheit) => value * 1.8 + 32.0,
+        (TemperatureUnit::Fahrenheit, TemperatureUnit::Celsius) => (value - 32.0) / 1.8,
+        _ => value,
+    }
+}
+
+/// Convert a temperature step size: the converted step is rounded to whole degrees Fahrenheit or
+/// half degrees Celsius.
+pub(crate) fn convert_temperature_step(
+    step: f64,
+    from: TemperatureUnit,
+    to: TemperatureUnit,
+) -> f64 {
+    match (from, to) {
+        (TemperatureUnit::Celsius, TemperatureUnit::Fahrenheit) => (step * 1.8).round().max(1.0),
+        (TemperatureUnit::Fahrenheit, TemperatureUnit::Celsius) => {
+            (step / 1.8 * 2.0).round().max(1.0) / 2.0
+        }
+        _ => step,
+    }
+}
+
+/// Round a value to a multiple of the given step size.
+pub(crate) fn round_to_step(value: f64, step: f64) -> f64 {
+    if step <= 0.0 {
+        return value;
+    }
+    // remove floating point artifacts like 21.900000000000002
+    ((value / step).round() * step * 100.0).round() / 100.0
+}
+
+fn round_tenths(value: f64) -> f64 {
+    (value * 10.0).round() / 10.0
+}
+
+/// Convert the given temperature attributes and round them to one decimal.
+fn convert_temperature_attributes(
+    attributes: &mut Map<String, Value>,
+    keys: &[&str],
+    from: TemperatureUnit,
+    to: TemperatureUnit,
+) {
+    for key in keys {
+        if let Some(value) = attributes.get(*key).and_then(|v| v.as_f64()) {
+            let value = round_tenths(convert_temperature(value, from, to));
+            attributes.insert(key.to_string(), value.into());
+        }
+    }
+}
+
+/// Convert the temperature attributes of a climate or water heater entity from the HA unit system
+/// to the preferred unit and set the `temperature_unit` attribute.
+///
+/// Set points and limits are rounded to the converted `target_temp_step`, if available.
+pub(crate) fn convert_climate_temperatures(
+    ha_attr: &mut Map<String, Value>,
+    ha_unit: TemperatureUnit,
+    unit: TemperatureUnit,
+) {
+    if ha_unit != unit {
+        convert_temperature_attributes(ha_attr, &["current_temperature"], ha_unit, unit);
+
+        let step = ha_attr
+            .get("target_temp_step")
+            .and_then(|v| v.as_f64())
+            .map(|step| convert_temperature_step(step, ha_unit, unit));
+        if let Some(step) = step {
+            ha_attr.insert("target_temp_step".into(), step.into());
+        }
+        for key in CLIMATE_TEMPERATURES {
+            if let Some(value) = ha_attr.get(key).and_then(|v| v.as_f64()) {
+                let value = convert_temperature(value, ha_unit, unit);
+                let value = match step {
+                    Some(step) => round_to_step(value, step),
+                    None => round_tenths(value),
+                };
+                ha_attr.insert(key.into(), value.into());
+            }
+        }
+    }
+    ha_attr.insert(
+        "temperature_unit".into(),
+        temperature_unit_option(unit).into(),
+    );
+}
+
+/// Get the temperature unit of a weather entity from the `temperature_unit` attribute.
+pub(crate) fn weather_temperature_unit(ha_attr: &Map<String, Value>) -> Option<TemperatureUnit> {
+    ha_attr
+        .get("temperature_unit")
+        .and_then(|v| v.as_str())
+        .and_then(temperature_unit_from_symbol)
+}
+
+/// Convert the temperature attributes of a weather entity to the given unit and update the
+/// `temperature_unit` attribute.
+pub(crate) fn convert_weather_temperatures(
+    ha_attr: &mut Map<String, Value>,
+    unit: TemperatureUnit,
+) {
+    let weather_unit = match weather_temperature_unit(ha_attr) {
+        Some(weather_unit) if weather_unit != unit => weather_unit,
+        _ => return,
+    };
+
+    convert_temperature_attributes(ha_attr, &WEATHER_TEMPERATURES, weather_unit, unit);
+    ha_attr.insert(
+        "temperature_unit".into(),
+        temperature_unit_symbol(unit).into(),
+    );
+}
+
+/// Convert the temperatures of weather forecast entries from the `get_forecasts` service.
+pub(crate) fn convert_forecast_temperatures(
+    forecast: &mut [Value],
+    from: TemperatureUnit,
+    to: TemperatureUnit,
+) {
+    if from == to {
+        return;
+    }
+    for entry in forecast.iter_mut().filter_map(|v| v.as_object_mut()) {
+        convert_temperature_attributes(entry, &WEATHER_TEMPERATURES, from, to);
+    }
+}
+
+/// Convert the state of a temperature sensor to the given unit.
+///
+/// Sensors without a temperature `unit_of_measurement` are not changed.
+pub(crate) fn convert_sensor_temperature(
+    state: &mut String,
+    ha_attr: &mut Map<String, Value>,
+    unit: TemperatureUnit,
+) {
+    let sensor_unit = match ha_attr
+        .get("unit_of_measurement")
+        .and_then(|v| v.as_str())
+        .and_then(temperature_unit_from_symbol)
+    {
+        Some(sensor_unit) if sensor_unit != unit => sensor_unit,
+        _ => return,
+    };
+
+    // non-numeric states like `unavailable` are kept
+    if let Ok(value) = state.parse::<f64>() {
+        *state = round_tenths(convert_temperature(value, sensor_unit, unit)).to_string();
+    }
+    ha_attr.insert(
+        "unit_of_measurement".into(),
+        temperature_unit_symbol(unit).into(),
+    );
+}
+
+/// Convert a temperature command value from the preferred unit to the HA unit system.
+///
+/// The converted value is rounded to the `target_temp_step` of the entity. Without a step, the
+/// default HA climate precision is used: tenths for Celsius and whole degrees for Fahrenheit.
+pub(crate) fn temperature_to_ha(
+    value: f64,
+    conversion: Option<TemperatureConversion>,
+    ha_attr: Option<&Map<String, Value>>,
+) -> f64 {
+    let conversion = match conversion {
+        Some(conversion) if conversion.unit != conversion.ha_unit => conversion,
+        _ => return value,
+    };
+
+    let step = ha_attr
+        .and_then(|attr| attr.get("target_temp_step"))
+        .and_then(|v| v.as_f64())
+        .unwrap_or(match conversion.ha_unit {
+            TemperatureUnit::Celsius => 0.1,
+            TemperatureUnit::Fahrenheit => 1.0,
+        });
+    round_to_step(
+        convert_temperature(value, conversion.unit, conversion.ha_unit),
+        step,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use serde_json::json;
+    use TemperatureUnit::{Celsius, Fahrenheit};
+
+    #[rstest]
+    #[case(0.0, Celsius, Fahrenheit, 32.0)]
+    #[case(21.5, Celsius, Fahrenheit, 70.7)]
+    #[case(-40.0, Fahrenheit, Celsius, -40.0)]
+    #[case(212.0, Fahrenheit, Celsius, 100.0)]
+    #[case(21.5, Celsius, Celsius, 21.5)]
+    fn convert_temperature_returns_converted_value(
+        #[case] value: f64,
+        #[case] from: TemperatureUnit,
+        #[case] to: TemperatureUnit,
+        #[case] expected: f64,
+    ) {
+        assert_eq!(expected, round_tenths(convert_temperature(value, from, to)));
+    }
+
+    #[rstest]
+    #[case(0.5, Celsius, Fahrenheit, 1.0)]
+    #[case(0.1, Celsius, Fahrenheit, 1.0)]
+    #[case(1.0, Celsius, Fahrenheit, 2.0)]
+    #[case(1.0, Fahrenheit, Celsius, 0.5)]
+    #[case(5.0, Fahrenheit, Celsius, 3.0)]
+    #[case(0.5, Celsius, Celsius, 0.5)]
+    fn convert_temperature_step_returns_rounded_step(
+        #[case] step: f64,
+        #[case] from: TemperatureUnit,
+        #[case] to: TemperatureUnit,
+        #[case] expected: f64,
+    ) {
+        assert_eq!(expected, convert_temperature_step(step, from, to));
+    }
+
+    #[rstest]
+    #[case(21.94, 0.1, 21.9)]
+    #[case(21.3, 0.5, 21.5)]
+    #[case(70.4, 1.0, 70.0)]
+    #[case(21.3, 0.0, 21.3)]
+    fn round_to_step_returns_multiple_of_step(
+        #[case] value: f64,
+        #[case] step: f64,
+        #[case] expected: f64,
+    ) {
+        assert_eq!(expected, round_to_step(value, step));
+    }
+
+    #[test]
+    fn climate_temperatures_are_converted() {
+        let mut attr = json!({
+            "current_temperature": 21.0,
+            "temperature": 22.5,
+            "target_temp_high": null,
+            "min_temp": 7,
+            "max_temp": 35,
+            "target_temp_step": 0.5
+        })
+        .as_object()
+        .cloned()
+        .unwrap();
+
+        convert_climate_temperatures(&mut attr, Celsius, Fahrenheit);
+
+        assert_eq!(Some(&json!(69.8)), attr.get("current_temperature"));
+        assert_eq!(Some(&json!(73.0)), attr.get("temperature"));
+        assert_eq!(Some(&Value::Null), attr.get("target_temp_high"));
+        assert_eq!(Some(&json!(45.0)), attr.get("min_temp"));
+        assert_eq!(Some(&json!(95.0)), attr.get("max_temp"));
+        assert_eq!(Some(&json!(1.0)), attr.get("target_temp_step"));
+        assert_eq!(Some(&json!("FAHRENHEIT")), attr.get("temperature_unit"));
+    }
+
+    #[test]
+    fn climate_temperatures_without_step_are_rounded_to_tenths() {
+        let mut attr = json!({ "temperature": 72.0, "min_temp": 45 })
+            .as_object()
+            .cloned()
+            .unwrap();
+
+        convert_climate_temperatures(&mut attr, Fahrenheit, Celsius);
+
+        assert_eq!(Some(&json!(22.2)), attr.get("temperature"));
+        assert_eq!(Some(&json!(7.2)), attr.get("min_temp"));
+        assert_eq!(None, attr.get("target_temp_step"));
+    }
+
+    #[test]
+    fn climate_temperatures_without_conversion_set_unit() {
+        let mut attr = json!({ "temperature": 22.5 }).as_object().cloned().unwrap();
+
+        convert_climate_temperatures(&mut attr, Celsius, Celsius);
+
+        assert_eq!(Some(&json!(22.5)), attr.get("temperature"));
+        assert_eq!(Some(&json!("CELSIUS")), attr.get("temperature_unit"));
+    }
+
+    #[rstest]
+    #[case("72.5", "°F", Celsius, "22.5", "°C")]
+    #[case("22.5", "°C", Fahrenheit, "72.5", "°F")]
+    #[case("unavailable", "°C", Fahrenheit, "unavailable", "°F")]
+    #[case("22.5", "°C", Celsius, "22.5", "°C")]
+    #[case("45", "%", Fahrenheit, "45", "%")]
+    fn sensor_temperature_is_converted(
+        #[case] state: &str,
+        #[case] sensor_unit: &str,
+        #[case] unit: TemperatureUnit,
+        #[case] expected_state: &str,
+        #[case] expected_unit: &str,
+    ) {
+        let mut state = state.to_string();
+        let mut attr = json!({ "unit_of_measurement": sensor_unit })
+            .as_object()
+            .cloned()
+            .unwrap();
+
+        convert_sensor_temperature(&mut state, &mut attr, unit);
+
+        assert_eq!(expected_state, state);
+        assert_eq!(Some(&json!(expected_unit)), attr.get("unit_of_measurement"));
+    }
+
+    #[test]
+    fn weather_temperatures_are_converted() {
+        let mut attr = json!({
+            "temperature": 18.5,
+            "apparent_temperature": 17.0,
+            "dew_point": 10.2,
+            "humidity": 62,
+            "temperature_unit": "°C"
+        })
+        .as_object()
+        .cloned()
+        .unwrap();
+
+        convert_weather_temperatures(&mut attr, Fahrenheit);
+
+        assert_eq!(Some(&json!(65.3)), attr.get("temperature"));
+        assert_eq!(Some(&json!(62.6)), attr.get("apparent_temperature"));
+        assert_eq!(Some(&json!(50.4)), attr.get("dew_point"));
+        assert_eq!(Some(&json!(62)), attr.get("humidity"));
+        assert_eq!(Some(&json!("°F")), attr.get("temperature_unit"));
+    }
+
+    #[test]
+    fn forecast_temperatures_are_converted() {
+        let mut forecast = vec![
+            json!({ "condition": "sunny", "temperature": 77.0, "templow": 59.0 }),
+            json!({ "condition": "rainy", "temperature": 68.0, "precipitation": 2.5 }),
+        ];
+
+        convert_forecast_temperatures(&mut forecast, Fahrenheit, Celsius);
+
+        assert_eq!(
+            vec![
+                json!({ "condition": "sunny", "temperature": 25.0, "templow": 15.0 }),
+                json!({ "condition": "rainy", "temperature": 20.0, "precipitation": 2.5 }),
+            ],
+            forecast
+        );
+    }
+
+    #[rstest]
+    #[case(72.0, Fahrenheit, Celsius, json!({ "target_temp_step": 0.5 }), 22.0)]
+    #[case(72.0, Fahrenheit, Celsius, json!({}), 22.2)]
+    #[case(21.5, Celsius, Fahrenheit, json!({}), 71.0)]
+    #[case(21.3, Celsius, Celsius, json!({ "target_temp_step": 0.5 }), 21.3)]
+    fn temperature_to_ha_returns_rounded_value(
+        #[case] value: f64,
+        #[case] unit: TemperatureUnit,
+        #[case] ha_unit: TemperatureUnit,
+        #[case] attr: Value,
+        #[case] expected: f64,
+    ) {
+        let conversion = TemperatureConversion { unit, ha_unit };
+        assert_eq!(
+            expected,
+            temperature_to_ha(value, Some(conversion), attr.as_object())
+        );
+    }
+}
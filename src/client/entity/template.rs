@@ -0,0 +1,147 @@
+// Copyright (c) 2023 Unfolded Circle ApS, Markus Zehnder <markus.z@unfoldedcircle.com>
+// SPDX-License-Identifier: MPL-2.0
+
+//! Template sensor entity specific logic.
+//!
+//! Template sensors are virtual sensors defined in the configuration file. The sensor value is
+//! rendered by HA with a `render_template` subscription.
+
+use crate::configuration::TemplateSensorSettings;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use uc_api::intg::{AvailableIntgEntity, EntityChange};
+use uc_api::{EntityType, SensorOption};
+
+/// Get the sensor entity identifier of a template sensor.
+pub(crate) fn template_sensor_id(settings: &TemplateSensorSettings) -> String {
+    format!("template.{}", settings.id)
+}
+
+/// Get the rendered template value from a `render_template` subscription event.
+///
+/// Returns the rendered result, `Ok(None)` for a template warning, or the error message if the
+/// template could not be rendered.
+pub(crate) fn template_event_value(event: &Value) -> Result<Option<Value>, String> {
+    if let Some(result) = event.get("result") {
+        return Ok(Some(result.clone()));
+    }
+
+    let error = event
+        .get("error")
+        .and_then(|v| v.as_str())
+        .unwrap_or("missing template result");
+    match event.get("level").and_then(|v| v.as_str()) {
+        Some("WARNING") => Ok(None),
+        _ => Err(error.into()),
+    }
+}
+
+/// Map a rendered template value to the sensor attributes.
+///
+/// An unavailable sensor is indicated with `None`.
+fn map_template_attributes(
+    settings: &TemplateSensorSettings,
+    value: Option<Value>,
+) -> Map<String, Value> {
+    let mut attributes = serde_json::Map::with_capacity(3);
+
+    match value {
+        Some(value) => {
+            attributes.insert("state".into(), "ON".into());
+            attributes.insert("value".into(), value);
+        }
+        None => {
+            attributes.insert("state".into(), "UNAVAILABLE".into());
+            attributes.insert("value".into(), "unavailable".into());
+        }
+    }
+    if let Some(unit) = settings.unit.as_ref() {
+        attributes.insert("unit".into(), unit.clone().into());
+    }
+
+    attributes
+}
+
+pub(crate) fn template_sensor_entity_change(
+    settings: &TemplateSensorSettings,
+    value: Option<Value>,
+) -> EntityChange {
+    EntityChange {
+        device_id: None,
+        entity_type: EntityType::Sensor,
+        entity_id: template_sensor_id(settings),
+        attributes: map_template_attributes(settings, value),
+    }
+}
+
+/// Create the template sensor entity with the last rendered value, if available.
+pub(crate) fn convert_template_sensor(
+    settings: &TemplateSensorSettings,
+    value: Option<Value>,
+) -> AvailableIntgEntity {
+    let mut options = serde_json::Map::new();
+    if let Some(label) = settings.label.as_ref() {
+        options.insert(SensorOption::CustomLabel.to_string(), label.clone().into());
+    }
+    if let Some(unit) = settings.unit.as_ref() {
+        options.insert(SensorOption::CustomUnit.to_string(), unit.clone().into());
+    }
+
+    AvailableIntgEntity {
+        entity_id: template_sensor_id(settings),
+        device_id: None, // prepared for device_id handling
+        entity_type: EntityType::Sensor,
+        device_class: Some("custom".into()),
+        name: HashMap::from([("en".into(), settings.name.clone())]),
+        features: None,
+        area: None,
+        options: if options.is_empty() {
+            None
+        } else {
+            Some(options)
+        },
+        attributes: Some(map_template_attributes(settings, value)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn template_event_returns_result() {
+        let event = json!({ "result": "3 lights on", "listeners": { "all": false } });
+        assert_eq!(Ok(Some(json!("3 lights on"))), template_event_value(&event));
+    }
+
+    #[test]
+    fn template_event_returns_error() {
+        let event = json!({ "error": "UndefinedError: 'foo' is undefined", "level": "ERROR" });
+        assert_eq!(
+            Err("UndefinedError: 'foo' is undefined".to_string()),
+            template_event_value(&event)
+        );
+    }
+
+    #[test]
+    fn template_event_ignores_warning() {
+        let event = json!({ "error": "Template variable warning", "level": "WARNING" });
+        assert_eq!(Ok(None), template_event_value(&event));
+    }
+
+    #[test]
+    fn unavailable_template_sensor_change() {
+        let settings = TemplateSensorSettings {
+            id: "lights_on".into(),
+            name: "Lights".into(),
+            template: "{{ 1 }}".into(),
+            label: None,
+            unit: None,
+        };
+        let change = template_sensor_entity_change(&settings, None);
+
+        assert_eq!("template.lights_on", change.entity_id);
+        assert_eq!(Some(&json!("UNAVAILABLE")), change.attributes.get("state"));
+    }
+}
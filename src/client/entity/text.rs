@@ -0,0 +1,93 @@
+// Copyright (c) 2023 Unfolded Circle ApS, Markus Zehnder <markus.z@unfoldedcircle.com>
+// SPDX-License-Identifier: MPL-2.0
+
+//! Text and input_text entity specific logic.
+//!
+//! There's no text entity in the Integration-API: a text is exposed as a sensor with the length
+//! restrictions and pattern as entity options.
+
+use crate::client::model::EventData;
+use crate::errors::ServiceError;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use uc_api::intg::{AvailableIntgEntity, EntityChange};
+use uc_api::{EntityType, SensorOption};
+
+pub(crate) fn map_text_attributes(
+    _entity_id: &str,
+    state: &str,
+    ha_attr: Option<&mut Map<String, Value>>,
+) -> Result<Map<String, Value>, ServiceError> {
+    let mut attributes = serde_json::Map::with_capacity(1);
+
+    // don't expose passwords
+    let password = ha_attr
+        .and_then(|attr| attr.get("mode"))
+        .and_then(|v| v.as_str())
+        == Some("password");
+    let value = match state {
+        "unavailable" | "unknown" => state.into(),
+        _ if password => "*".repeat(state.chars().count()),
+        _ => state.into(),
+    };
+    attributes.insert("value".into(), value.into());
+
+    Ok(attributes)
+}
+
+pub(crate) fn text_event_to_entity_change(
+    mut data: EventData,
+) -> Result<EntityChange, ServiceError> {
+    let attributes = map_text_attributes(
+        &data.entity_id,
+        &data.new_state.state,
+        data.new_state.attributes.as_mut(),
+    )?;
+
+    Ok(EntityChange {
+        device_id: None,
+        entity_type: EntityType::Sensor,
+        entity_id: data.entity_id,
+        attributes,
+    })
+}
+
+pub(crate) fn convert_text_entity(
+    entity_id: String,
+    state: String,
+    ha_attr: &mut Map<String, Value>,
+) -> Result<AvailableIntgEntity, ServiceError> {
+    let friendly_name = ha_attr.get("friendly_name").and_then(|v| v.as_str());
+    let name = HashMap::from([("en".into(), friendly_name.unwrap_or(&entity_id).into())]);
+
+    // handle options
+    let mut options = serde_json::Map::new();
+    options.insert(SensorOption::CustomLabel.to_string(), "Text".into());
+    if let Some(v) = ha_attr.get("min").filter(|v| v.is_u64()) {
+        options.insert("min_length".into(), v.clone());
+    }
+    if let Some(v) = ha_attr.get("max").filter(|v| v.is_u64()) {
+        options.insert("max_length".into(), v.clone());
+    }
+    if let Some(v) = ha_attr.get("pattern").filter(|v| v.is_string()) {
+        options.insert("pattern".into(), v.clone());
+    }
+    if let Some(v) = ha_attr.get("mode").filter(|v| v.is_string()) {
+        options.insert("mode".into(), v.clone());
+    }
+
+    // convert attributes
+    let attributes = Some(map_text_attributes(&entity_id, &state, Some(ha_attr))?);
+
+    Ok(AvailableIntgEntity {
+        entity_id,
+        device_id: None, // prepared for device_id handling
+        entity_type: EntityType::Sensor,
+        device_class: Some("custom".into()),
+        name,
+        features: None,
+        area: None,
+        options: Some(options),
+        attributes,
+    })
+}
@@ -0,0 +1,226 @@
+// Copyright (c) 2023 Unfolded Circle ApS, Markus Zehnder <markus.z@unfoldedcircle.com>
+// SPDX-License-Identifier: MPL-2.0
+
+//! Timer entity specific logic.
+//!
+//! A timer is exposed as sensor with the remaining time as value. HA only sends state changes
+//! when a timer is started, paused or finished: the countdown of an active timer is calculated
+//! from the `finishes_at` attribute.
+
+use crate::client::model::EventData;
+use crate::errors::ServiceError;
+use crate::util::json;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use time::format_description::well_known::Rfc3339;
+use time::{Duration, OffsetDateTime};
+use uc_api::intg::{AvailableIntgEntity, EntityChange};
+use uc_api::{EntityType, SensorOption};
+
+pub(crate) fn map_timer_attributes(
+    _entity_id: &str,
+    state: &str,
+    ha_attr: Option<&mut Map<String, Value>>,
+    now: OffsetDateTime,
+) -> Result<Map<String, Value>, ServiceError> {
+    let mut attributes = serde_json::Map::with_capacity(4);
+
+    let value = match (state, ha_attr.as_deref()) {
+        ("active", Some(ha_attr)) => timer_finishes_at(ha_attr)
+            .map(|finishes_at| format_duration(finishes_at - now))
+            .or_else(|| timer_duration_value(ha_attr, "remaining")),
+        ("paused", Some(ha_attr)) => timer_duration_value(ha_attr, "remaining"),
+        ("idle", Some(ha_attr)) => timer_duration_value(ha_attr, "duration"),
+        _ => None,
+    };
+    attributes.insert(
+        "value".into(),
+        value.map(Value::String).unwrap_or_else(|| state.into()),
+    );
+    attributes.insert("timer_state".into(), state.into());
+
+    if let Some(ha_attr) = ha_attr {
+        json::move_entry(ha_attr, &mut attributes, "duration");
+        json::move_entry(ha_attr, &mut attributes, "finishes_at");
+    }
+
+    Ok(attributes)
+}
+
+pub(crate) fn timer_event_to_entity_change(
+    mut data: EventData,
+) -> Result<EntityChange, ServiceError> {
+    let attributes = map_timer_attributes(
+        &data.entity_id,
+        &data.new_state.state,
+        data.new_state.attributes.as_mut(),
+        OffsetDateTime::now_utc(),
+    )?;
+
+    Ok(EntityChange {
+        device_id: None,
+        entity_type: EntityType::Sensor,
+        entity_id: data.entity_id,
+        attributes,
+    })
+}
+
+/// Create the countdown change event of an active timer.
+pub(crate) fn timer_countdown_entity_change(
+    entity_id: &str,
+    finishes_at: OffsetDateTime,
+    now: OffsetDateTime,
+) -> EntityChange {
+    let mut attributes = serde_json::Map::with_capacity(1);
+    attributes.insert("value".into(), format_duration(finishes_at - now).into());
+
+    EntityChange {
+        device_id: None,
+        entity_type: EntityType::Sensor,
+        entity_id: entity_id.into(),
+        attributes,
+    }
+}
+
+pub(crate) fn convert_timer_entity(
+    entity_id: String,
+    state: String,
+    ha_attr: &mut Map<String, Value>,
+) -> Result<AvailableIntgEntity, ServiceError> {
+    let friendly_name = ha_attr.get("friendly_name").and_then(|v| v.as_str());
+    let name = HashMap::from([("en".into(), friendly_name.unwrap_or(&entity_id).into())]);
+
+    // handle options
+    let mut options = serde_json::Map::new();
+    options.insert(SensorOption::CustomLabel.to_string(), "Timer".into());
+
+    // convert attributes
+    let attributes = Some(map_timer_attributes(
+        &entity_id,
+        &state,
+        Some(ha_attr),
+        OffsetDateTime::now_utc(),
+    )?);
+
+    Ok(AvailableIntgEntity {
+        entity_id,
+        device_id: None, // prepared for device_id handling
+        entity_type: EntityType::Sensor,
+        device_class: Some("custom".into()),
+        name,
+        features: None,
+        area: None,
+        options: Some(options),
+        attributes,
+    })
+}
+
+/// Get the end time of an active timer from the `finishes_at` attribute.
+pub(crate) fn timer_finishes_at(ha_attr: &Map<String, Value>) -> Option<OffsetDateTime> {
+    ha_attr
+        .get("finishes_at")
+        .and_then(|v| v.as_str())
+        .and_then(|v| OffsetDateTime::parse(v, &Rfc3339).ok())
+}
+
+fn timer_duration_value(ha_attr: &Map<String, Value>, key: &str) -> Option<String> {
+    ha_attr
+        .get(key)
+        .and_then(|v| v.as_str())
+        .and_then(parse_duration)
+        .map(format_duration)
+}
+
+/// Parse a HA timer duration: `H:MM:SS` with an optional `N day(s), ` prefix.
+pub(crate) fn parse_duration(value: &str) -> Option<Duration> {
+    let (days, time) = match value.split_once(", ") {
+        Some((days, time)) => (
+            days.trim_end_matches(" days")
+                .trim_end_matches(" day")
+                .parse::<i64>()
+                .ok()?,
+            time,
+        ),
+        None => (0, value),
+    };
+
+    let mut parts = time.split(':');
+    let hours = parts.next()?.parse::<i64>().ok()?;
+    let minutes = parts.next()?.parse::<i64>().ok()?;
+    // seconds are optional and may contain fractions
+    let seconds = parts
+        .next()
+        .map(|v| v.parse::<f64>().ok())
+        .unwrap_or(Some(0.0))?;
+    if parts.next().is_some() || minutes > 59 || seconds >= 60.0 {
+        return None;
+    }
+
+    Some(
+        Duration::days(days)
+            + Duration::hours(hours)
+            + Duration::minutes(minutes)
+            + Duration::seconds(seconds as i64),
+    )
+}
+
+/// Format a duration as `H:MM:SS`. Negative durations are formatted as zero.
+pub(crate) fn format_duration(duration: Duration) -> String {
+    let seconds = duration.whole_seconds().max(0);
+    format!(
+        "{}:{:02}:{:02}",
+        seconds / 3600,
+        (seconds % 3600) / 60,
+        seconds % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use serde_json::json;
+
+    #[rstest]
+    #[case("0:05:00", Some(Duration::minutes(5)))]
+    #[case("1:30:15", Some(Duration::seconds(5415)))]
+    #[case("00:01", Some(Duration::minutes(1)))]
+    #[case("0:00:10.500000", Some(Duration::seconds(10)))]
+    #[case("1 day, 2:00:00", Some(Duration::hours(26)))]
+    #[case("2 days, 0:00:00", Some(Duration::hours(48)))]
+    #[case("0:61:00", None)]
+    #[case("5 minutes", None)]
+    fn parse_duration_returns_duration(#[case] value: &str, #[case] expected: Option<Duration>) {
+        assert_eq!(expected, parse_duration(value));
+    }
+
+    #[rstest]
+    #[case(Duration::seconds(59), "0:00:59")]
+    #[case(Duration::seconds(5415), "1:30:15")]
+    #[case(Duration::hours(26), "26:00:00")]
+    #[case(Duration::seconds(-3), "0:00:00")]
+    fn format_duration_returns_hours_minutes_seconds(
+        #[case] duration: Duration,
+        #[case] expected: &str,
+    ) {
+        assert_eq!(expected, format_duration(duration));
+    }
+
+    #[rstest]
+    #[case("active", json!({ "duration": "0:05:00", "finishes_at": "2023-08-01T10:05:00+00:00" }), "0:04:30")]
+    #[case("paused", json!({ "duration": "0:05:00", "remaining": "0:02:10" }), "0:02:10")]
+    #[case("idle", json!({ "duration": "0:05:00", "remaining": "0:05:00" }), "0:05:00")]
+    #[case("unavailable", json!({}), "unavailable")]
+    fn timer_value_is_remaining_time(
+        #[case] state: &str,
+        #[case] attr: Value,
+        #[case] expected: &str,
+    ) {
+        let mut attr = attr.as_object().cloned().unwrap();
+        let now = OffsetDateTime::parse("2023-08-01T10:00:30Z", &Rfc3339).unwrap();
+        let attributes = map_timer_attributes("timer.tea", state, Some(&mut attr), now).unwrap();
+
+        assert_eq!(Some(&json!(expected)), attributes.get("value"));
+        assert_eq!(Some(&json!(state)), attributes.get("timer_state"));
+    }
+}
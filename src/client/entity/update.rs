@@ -0,0 +1,224 @@
+// Copyright (c) 2023 Unfolded Circle ApS, Markus Zehnder <markus.z@unfoldedcircle.com>
+// SPDX-License-Identifier: MPL-2.0
+
+//! Update entity specific logic.
+//!
+//! An update entity is exposed as sensor showing if an update is available. The update can
+//! optionally be installed with a separate button entity `<entity_id>.install`.
+
+use crate::client::model::EventData;
+use crate::errors::ServiceError;
+use crate::util::json;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use uc_api::intg::{AvailableIntgEntity, EntityChange};
+use uc_api::{EntityType, SensorOption};
+
+// https://developers.home-assistant.io/docs/core/entity/update#supported-features
+pub const UPDATE_SUPPORT_INSTALL: u32 = 1;
+// pub const UPDATE_SUPPORT_SPECIFIC_VERSION: u32 = 2;
+// pub const UPDATE_SUPPORT_PROGRESS: u32 = 4;
+pub const UPDATE_SUPPORT_BACKUP: u32 = 8;
+// pub const UPDATE_SUPPORT_RELEASE_NOTES: u32 = 16;
+
+/// Get the install progress: `in_progress` is either a boolean or the progress in percent in
+/// older HA versions, newer versions use `update_percentage`.
+fn update_progress(ha_attr: &Map<String, Value>) -> Option<Option<u64>> {
+    let percentage = ha_attr.get("update_percentage").and_then(|v| v.as_u64());
+    match ha_attr.get("in_progress") {
+        Some(Value::Bool(true)) => Some(percentage),
+        Some(Value::Number(v)) => Some(v.as_u64().or(percentage)),
+        _ => None,
+    }
+}
+
+pub(crate) fn map_update_attributes(
+    _entity_id: &str,
+    state: &str,
+    ha_attr: Option<&mut Map<String, Value>>,
+) -> Result<Map<String, Value>, ServiceError> {
+    let mut attributes = serde_json::Map::with_capacity(5);
+
+    let progress = ha_attr.as_deref().and_then(update_progress);
+    let latest_version = ha_attr
+        .as_deref()
+        .and_then(|attr| attr.get("latest_version"))
+        .and_then(|v| v.as_str())
+        .unwrap_or_default();
+    let value = match (state, progress) {
+        ("on", Some(Some(progress))) => format!("installing {progress}%"),
+        ("on", Some(None)) => "installing".into(),
+        ("on", None) => format!("update available {latest_version}")
+            .trim_end()
+            .into(),
+        ("off", _) => "up to date".into(),
+        // unavailable, unknown
+        _ => state.into(),
+    };
+    attributes.insert("value".into(), value.into());
+    if let Some(Some(progress)) = progress {
+        attributes.insert("progress".into(), progress.into());
+    }
+
+    if let Some(ha_attr) = ha_attr {
+        json::move_entry(ha_attr, &mut attributes, "installed_version");
+        json::move_entry(ha_attr, &mut attributes, "latest_version");
+        json::move_entry(ha_attr, &mut attributes, "release_url");
+    }
+
+    Ok(attributes)
+}
+
+pub(crate) fn update_event_to_entity_change(
+    mut data: EventData,
+) -> Result<EntityChange, ServiceError> {
+    let attributes = map_update_attributes(
+        &data.entity_id,
+        &data.new_state.state,
+        data.new_state.attributes.as_mut(),
+    )?;
+
+    Ok(EntityChange {
+        device_id: None,
+        entity_type: EntityType::Sensor,
+        entity_id: data.entity_id,
+        attributes,
+    })
+}
+
+pub(crate) fn convert_update_entity(
+    entity_id: String,
+    state: String,
+    ha_attr: &mut Map<String, Value>,
+) -> Result<AvailableIntgEntity, ServiceError> {
+    let friendly_name = ha_attr.get("friendly_name").and_then(|v| v.as_str());
+    let name = HashMap::from([("en".into(), friendly_name.unwrap_or(&entity_id).into())]);
+
+    // handle options
+    let mut options = serde_json::Map::new();
+    options.insert(SensorOption::CustomLabel.to_string(), "Update".into());
+    json::copy_entry(ha_attr, &mut options, "title");
+
+    // convert attributes
+    let attributes = Some(map_update_attributes(&entity_id, &state, Some(ha_attr))?);
+
+    Ok(AvailableIntgEntity {
+        entity_id,
+        device_id: None, // prepared for device_id handling
+        entity_type: EntityType::Sensor,
+        device_class: Some("custom".into()),
+        name,
+        features: None,
+        area: None,
+        options: Some(options),
+        attributes,
+    })
+}
+
+/// Get the install button entity identifier of an update entity.
+pub(crate) fn update_install_button_id(entity_id: &str) -> String {
+    format!("{entity_id}.install")
+}
+
+/// Check if the update entity supports installing updates.
+pub(crate) fn update_supports_install(ha_attr: &Map<String, Value>) -> bool {
+    let supported_features = ha_attr
+        .get("supported_features")
+        .and_then(|v| v.as_u64())
+        .unwrap_or_default() as u32;
+    supported_features & UPDATE_SUPPORT_INSTALL > 0
+}
+
+/// Check if the update entity supports a backup before installing an update.
+pub(crate) fn update_supports_backup(ha_attr: &Map<String, Value>) -> bool {
+    let supported_features = ha_attr
+        .get("supported_features")
+        .and_then(|v| v.as_u64())
+        .unwrap_or_default() as u32;
+    supported_features & UPDATE_SUPPORT_BACKUP > 0
+}
+
+/// The install button is only available if an update is available and not yet being installed.
+fn map_update_install_button_attributes(
+    state: &str,
+    ha_attr: Option<&Map<String, Value>>,
+) -> Map<String, Value> {
+    let installing = ha_attr.and_then(update_progress).is_some();
+    let state = match state {
+        "on" if !installing => "AVAILABLE",
+        _ => "UNAVAILABLE",
+    };
+
+    let mut attributes = serde_json::Map::with_capacity(1);
+    attributes.insert("state".into(), state.into());
+    attributes
+}
+
+pub(crate) fn update_install_button_entity_change(
+    entity_id: &str,
+    state: &str,
+    ha_attr: Option<&Map<String, Value>>,
+) -> EntityChange {
+    EntityChange {
+        device_id: None,
+        entity_type: EntityType::Button,
+        entity_id: update_install_button_id(entity_id),
+        attributes: map_update_install_button_attributes(state, ha_attr),
+    }
+}
+
+pub(crate) fn convert_update_install_button(
+    entity_id: &str,
+    state: &str,
+    ha_attr: &Map<String, Value>,
+) -> AvailableIntgEntity {
+    let friendly_name = ha_attr
+        .get("friendly_name")
+        .and_then(|v| v.as_str())
+        .unwrap_or(entity_id);
+    let name = HashMap::from([("en".into(), format!("Install {friendly_name}"))]);
+
+    AvailableIntgEntity {
+        entity_id: update_install_button_id(entity_id),
+        device_id: None, // prepared for device_id handling
+        entity_type: EntityType::Button,
+        device_class: None,
+        name,
+        features: None, // no optional features, default = "press"
+        area: None,
+        options: None,
+        attributes: Some(map_update_install_button_attributes(state, Some(ha_attr))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use serde_json::json;
+
+    #[rstest]
+    #[case("off", json!({ "installed_version": "2023.8.0", "latest_version": "2023.8.0" }), "up to date")]
+    #[case("on", json!({ "installed_version": "2023.8.0", "latest_version": "2023.8.1", "in_progress": false }), "update available 2023.8.1")]
+    #[case("on", json!({ "latest_version": "2023.8.1", "in_progress": true }), "installing")]
+    #[case("on", json!({ "latest_version": "2023.8.1", "in_progress": 45 }), "installing 45%")]
+    #[case("on", json!({ "latest_version": "2023.8.1", "in_progress": true, "update_percentage": 60 }), "installing 60%")]
+    #[case("unavailable", json!({}), "unavailable")]
+    fn update_value(#[case] state: &str, #[case] attr: Value, #[case] expected: &str) {
+        let mut attr = attr.as_object().cloned().unwrap();
+        let attributes = map_update_attributes("update.core", state, Some(&mut attr)).unwrap();
+
+        assert_eq!(Some(&json!(expected)), attributes.get("value"));
+    }
+
+    #[rstest]
+    #[case("on", json!({ "in_progress": false }), "AVAILABLE")]
+    #[case("on", json!({ "in_progress": 10 }), "UNAVAILABLE")]
+    #[case("off", json!({ "in_progress": false }), "UNAVAILABLE")]
+    fn install_button_state(#[case] state: &str, #[case] attr: Value, #[case] expected: &str) {
+        let change = update_install_button_entity_change("update.core", state, attr.as_object());
+
+        assert_eq!("update.core.install", change.entity_id);
+        assert_eq!(Some(&json!(expected)), change.attributes.get("state"));
+    }
+}
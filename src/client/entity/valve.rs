@@ -0,0 +1,91 @@
+// Copyright (c) 2023 Unfolded Circle ApS, Markus Zehnder <markus.z@unfoldedcircle.com>
+// SPDX-License-Identifier: MPL-2.0
+
+//! Valve entity specific logic.
+//!
+//! A valve is exposed as cover entity: the states and supported features are identical.
+
+use crate::client::entity::map_cover_attributes;
+use crate::client::model::EventData;
+use crate::errors::ServiceError;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use uc_api::intg::{AvailableIntgEntity, EntityChange};
+use uc_api::{CoverFeature, EntityType};
+
+// https://developers.home-assistant.io/docs/core/entity/valve#supported-features
+pub const VALVE_SUPPORT_OPEN: u32 = 1;
+pub const VALVE_SUPPORT_CLOSE: u32 = 2;
+pub const VALVE_SUPPORT_SET_POSITION: u32 = 4;
+pub const VALVE_SUPPORT_STOP: u32 = 8;
+
+pub(crate) fn valve_event_to_entity_change(
+    mut data: EventData,
+) -> Result<EntityChange, ServiceError> {
+    let attributes = map_cover_attributes(
+        &data.entity_id,
+        &data.new_state.state,
+        data.new_state.attributes.as_mut(),
+    )?;
+
+    Ok(EntityChange {
+        device_id: None,
+        entity_type: EntityType::Cover,
+        entity_id: data.entity_id,
+        attributes,
+    })
+}
+
+pub(crate) fn convert_valve_entity(
+    entity_id: String,
+    state: String,
+    ha_attr: &mut Map<String, Value>,
+) -> Result<AvailableIntgEntity, ServiceError> {
+    let friendly_name = ha_attr.get("friendly_name").and_then(|v| v.as_str());
+    let name = HashMap::from([("en".into(), friendly_name.unwrap_or(&entity_id).into())]);
+
+    // handle features
+    let supported_features = ha_attr
+        .get("supported_features")
+        .and_then(|v| v.as_u64())
+        .unwrap_or_default() as u32;
+    let mut cover_feats = Vec::with_capacity(2);
+
+    if supported_features & VALVE_SUPPORT_OPEN > 0 {
+        cover_feats.push(CoverFeature::Open);
+    }
+    if supported_features & VALVE_SUPPORT_CLOSE > 0 {
+        cover_feats.push(CoverFeature::Close);
+    }
+    if supported_features & VALVE_SUPPORT_STOP > 0 {
+        cover_feats.push(CoverFeature::Stop);
+    }
+    if supported_features & VALVE_SUPPORT_SET_POSITION > 0 {
+        cover_feats.push(CoverFeature::Position);
+    }
+
+    // the valve device classes `water` and `gas` don't exist for covers
+    let mut options = serde_json::Map::new();
+    if let Some(v) = ha_attr.get("device_class").and_then(|v| v.as_str()) {
+        options.insert("valve_class".into(), v.into());
+    }
+
+    // convert attributes
+    let attributes = Some(map_cover_attributes(&entity_id, &state, Some(ha_attr))?);
+
+    Ok(AvailableIntgEntity {
+        entity_id,
+        device_id: None, // prepared for device_id handling
+        entity_type: EntityType::Cover,
+        device_class: None,
+        name,
+        features: Some(cover_feats.into_iter().map(|v| v.to_string()).collect()),
+        area: None,
+        options: if options.is_empty() {
+            None
+        } else {
+            Some(options)
+        },
+        attributes,
+    })
+}
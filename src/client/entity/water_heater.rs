@@ -0,0 +1,170 @@
+// Copyright (c) 2023 Unfolded Circle ApS, Markus Zehnder <markus.z@unfoldedcircle.com>
+// SPDX-License-Identifier: MPL-2.0
+
+//! Water heater entity specific logic.
+//!
+//! A water heater is exposed as climate entity: all operation modes except `off` are mapped to the
+//! `HEAT` state.
+
+use crate::client::model::EventData;
+use crate::errors::ServiceError;
+use crate::util::json;
+use crate::util::json::{is_float_value, number_value};
+use log::warn;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use uc_api::intg::{AvailableIntgEntity, EntityChange};
+use uc_api::{ClimateFeature, ClimateOption, EntityType};
+
+// https://developers.home-assistant.io/docs/core/entity/water-heater#supported-features
+pub const WATER_HEATER_SUPPORT_TARGET_TEMPERATURE: u32 = 1;
+pub const WATER_HEATER_SUPPORT_OPERATION_MODE: u32 = 2;
+pub const WATER_HEATER_SUPPORT_AWAY_MODE: u32 = 4;
+pub const WATER_HEATER_SUPPORT_ON_OFF: u32 = 8;
+
+pub(crate) fn map_water_heater_attributes(
+    entity_id: &str,
+    state: &str,
+    ha_attr: Option<&mut Map<String, Value>>,
+) -> Result<Map<String, Value>, ServiceError> {
+    let mut attributes = serde_json::Map::with_capacity(6);
+
+    match state {
+        "unavailable" | "unknown" | "off" => {
+            attributes.insert("state".into(), state.to_uppercase().into());
+        }
+        // operation modes
+        "eco" | "electric" | "gas" | "heat_pump" | "high_demand" | "performance" | "on" => {
+            attributes.insert("state".into(), "HEAT".into());
+        }
+        state => warn!("{} Not supported water heater state: {}", entity_id, state),
+    };
+
+    if let Some(ha_attr) = ha_attr {
+        json::move_entry(ha_attr, &mut attributes, "current_temperature");
+        json::move_value(
+            ha_attr,
+            &mut attributes,
+            "temperature",
+            "target_temperature",
+        );
+        json::move_value(
+            ha_attr,
+            &mut attributes,
+            "target_temp_high",
+            "target_temperature_high",
+        );
+        json::move_value(
+            ha_attr,
+            &mut attributes,
+            "target_temp_low",
+            "target_temperature_low",
+        );
+        json::move_entry(ha_attr, &mut attributes, "operation_mode");
+        if let Some(value) = ha_attr.get("away_mode").and_then(|v| v.as_str()) {
+            attributes.insert("away_mode".into(), (value == "on").into());
+        }
+    }
+
+    Ok(attributes)
+}
+
+pub(crate) fn water_heater_event_to_entity_change(
+    mut data: EventData,
+) -> Result<EntityChange, ServiceError> {
+    let attributes = map_water_heater_attributes(
+        &data.entity_id,
+        &data.new_state.state,
+        data.new_state.attributes.as_mut(),
+    )?;
+
+    Ok(EntityChange {
+        device_id: None,
+        entity_type: EntityType::Climate,
+        entity_id: data.entity_id,
+        attributes,
+    })
+}
+
+pub(crate) fn convert_water_heater_entity(
+    entity_id: String,
+    state: String,
+    ha_attr: &mut Map<String, Value>,
+) -> Result<AvailableIntgEntity, ServiceError> {
+    let friendly_name = ha_attr.get("friendly_name").and_then(|v| v.as_str());
+    let name = HashMap::from([("en".into(), friendly_name.unwrap_or(&entity_id).into())]);
+
+    // handle features
+    let supported_features = ha_attr
+        .get("supported_features")
+        .and_then(|v| v.as_u64())
+        .unwrap_or_default() as u32;
+    let operation_list = ha_attr
+        .get("operation_list")
+        .and_then(|v| v.as_array())
+        .cloned()
+        .unwrap_or_default();
+    let mut climate_feats = Vec::with_capacity(4);
+
+    // older water heater integrations provide an `off` operation mode instead of the on / off feature
+    if supported_features & WATER_HEATER_SUPPORT_ON_OFF > 0
+        || operation_list.iter().any(|v| v.as_str() == Some("off"))
+    {
+        climate_feats.push(ClimateFeature::OnOff);
+    }
+    climate_feats.push(ClimateFeature::Heat);
+    if supported_features & WATER_HEATER_SUPPORT_TARGET_TEMPERATURE > 0 {
+        climate_feats.push(ClimateFeature::TargetTemperature);
+    }
+    if is_float_value(ha_attr, "current_temperature") {
+        climate_feats.push(ClimateFeature::CurrentTemperature);
+    }
+    let mut features: Vec<String> = climate_feats.into_iter().map(|v| v.to_string()).collect();
+    if supported_features & WATER_HEATER_SUPPORT_OPERATION_MODE > 0 {
+        features.push("operation_mode".into());
+    }
+    if supported_features & WATER_HEATER_SUPPORT_AWAY_MODE > 0 {
+        features.push("away_mode".into());
+    }
+
+    // handle options
+    let mut options = serde_json::Map::new();
+    if let Some(v) = number_value(ha_attr, "min_temp") {
+        options.insert(ClimateOption::MinTemperature.to_string(), v);
+    }
+    if let Some(v) = number_value(ha_attr, "max_temp") {
+        options.insert(ClimateOption::MaxTemperature.to_string(), v);
+    }
+    if let Some(v) = number_value(ha_attr, "target_temp_step") {
+        options.insert(ClimateOption::TargetTemperatureStep.to_string(), v);
+    }
+    if let Some(v) = ha_attr.get("temperature_unit") {
+        options.insert(ClimateOption::TemperatureUnit.to_string(), v.clone());
+    }
+    if !operation_list.is_empty() {
+        options.insert("operation_modes".into(), operation_list.into());
+    }
+
+    // convert attributes
+    let attributes = Some(map_water_heater_attributes(
+        &entity_id,
+        &state,
+        Some(ha_attr),
+    )?);
+
+    Ok(AvailableIntgEntity {
+        entity_id,
+        device_id: None, // prepared for device_id handling
+        entity_type: EntityType::Climate,
+        device_class: None,
+        name,
+        features: Some(features),
+        area: None,
+        options: if options.is_empty() {
+            None
+        } else {
+            Some(options)
+        },
+        attributes,
+    })
+}
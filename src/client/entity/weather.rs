@@ -0,0 +1,454 @@
+// Copyright (c) 2023 Unfolded Circle ApS, Markus Zehnder <markus.z@unfoldedcircle.com>
+// SPDX-License-Identifier: MPL-2.0
+
+//! Weather entity specific logic.
+//!
+//! There's no weather entity in the Integration-API: a HA weather entity is split into multiple
+//! sensor entities, one for the weather condition and one for each available weather attribute.
+//! The sensor entity identifiers are the HA weather entity_id with the attribute name as suffix,
+//! e.g. `weather.home.temperature`.
+
+use crate::client::entity::device_class_to_label;
+use crate::client::model::EventData;
+use crate::errors::ServiceError;
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+use uc_api::intg::{AvailableIntgEntity, EntityChange};
+use uc_api::{EntityType, SensorOption};
+
+/// Weather condition sensor name, using the HA weather entity state as value.
+pub const WEATHER_CONDITION: &str = "condition";
+/// Weather forecast sensor name. Only available if the forecast is enabled in the configuration.
+pub const WEATHER_FORECAST: &str = "forecast";
+
+/// Unit of measurement of a weather attribute.
+#[derive(Debug, Clone, Copy)]
+enum WeatherUnit {
+    /// Unit is defined in another HA weather attribute.
+    Attribute(&'static str),
+    Fixed(&'static str),
+    None,
+}
+
+/// Weather attribute exposed as sensor.
+struct WeatherSensor {
+    /// HA weather entity attribute name, also used as sensor name.
+    attribute: &'static str,
+    /// Integration-API sensor device class. A custom sensor is used if not set.
+    device_class: Option<&'static str>,
+    unit: WeatherUnit,
+}
+
+const WEATHER_SENSORS: [WeatherSensor; 12] = [
+    WeatherSensor {
+        attribute: "temperature",
+        device_class: Some("temperature"),
+        unit: WeatherUnit::Attribute("temperature_unit"),
+    },
+    WeatherSensor {
+        attribute: "apparent_temperature",
+        device_class: None,
+        unit: WeatherUnit::Attribute("temperature_unit"),
+    },
+    WeatherSensor {
+        attribute: "dew_point",
+        device_class: None,
+        unit: WeatherUnit::Attribute("temperature_unit"),
+    },
+    WeatherSensor {
+        attribute: "humidity",
+        device_class: Some("humidity"),
+        unit: WeatherUnit::Fixed("%"),
+    },
+    WeatherSensor {
+        attribute: "pressure",
+        device_class: None,
+        unit: WeatherUnit::Attribute("pressure_unit"),
+    },
+    WeatherSensor {
+        attribute: "wind_speed",
+        device_class: None,
+        unit: WeatherUnit::Attribute("wind_speed_unit"),
+    },
+    WeatherSensor {
+        attribute: "wind_gust_speed",
+        device_class: None,
+        unit: WeatherUnit::Attribute("wind_speed_unit"),
+    },
+    WeatherSensor {
+        attribute: "wind_bearing",
+        device_class: None,
+        unit: WeatherUnit::Fixed("°"),
+    },
+    WeatherSensor {
+        attribute: "visibility",
+        device_class: None,
+        unit: WeatherUnit::Attribute("visibility_unit"),
+    },
+    WeatherSensor {
+        attribute: "cloud_coverage",
+        device_class: None,
+        unit: WeatherUnit::Fixed("%"),
+    },
+    WeatherSensor {
+        attribute: "uv_index",
+        device_class: None,
+        unit: WeatherUnit::None,
+    },
+    WeatherSensor {
+        attribute: "ozone",
+        device_class: None,
+        unit: WeatherUnit::None,
+    },
+];
+
+impl WeatherSensor {
+    fn unit(&self, ha_attr: &Map<String, Value>) -> Option<Value> {
+        match self.unit {
+            WeatherUnit::Attribute(key) => ha_attr.get(key).cloned(),
+            WeatherUnit::Fixed(unit) => Some(unit.into()),
+            WeatherUnit::None => None,
+        }
+    }
+}
+
+/// Get the sensor entity identifier of a weather entity attribute.
+pub(crate) fn weather_sensor_id(entity_id: &str, name: &str) -> String {
+    format!("{entity_id}.{name}")
+}
+
+/// Convert a HA weather entity event into sensor entity changes.
+///
+/// Only the condition sensor and the given weather sensors are changed, since the available sensor
+/// entities depend on the weather attributes when the entities were retrieved.
+///
+/// # Arguments
+///
+/// * `data`: HA weather entity event data.
+/// * `sensors`: sensor entity identifiers created with [`convert_weather_entities`].
+///
+/// returns: Result<Vec<EntityChange>, ServiceError>
+pub(crate) fn weather_event_to_entity_changes(
+    data: EventData,
+    sensors: &HashSet<String>,
+) -> Result<Vec<EntityChange>, ServiceError> {
+    let state = data.new_state.state;
+    let mut changes = Vec::with_capacity(WEATHER_SENSORS.len() + 1);
+
+    changes.push(sensor_entity_change(
+        weather_sensor_id(&data.entity_id, WEATHER_CONDITION),
+        state.clone().into(),
+        None,
+    ));
+
+    let ha_attr = data.new_state.attributes.unwrap_or_default();
+    for sensor in &WEATHER_SENSORS {
+        let sensor_id = weather_sensor_id(&data.entity_id, sensor.attribute);
+        if !sensors.contains(&sensor_id) {
+            continue;
+        }
+        let value = match (state.as_str(), ha_attr.get(sensor.attribute)) {
+            // propagate the unavailable state to all weather sensors
+            ("unavailable" | "unknown", _) => state.clone().into(),
+            (_, Some(value)) => value.clone(),
+            (_, None) => continue,
+        };
+        changes.push(sensor_entity_change(
+            sensor_id,
+            value,
+            sensor.unit(&ha_attr),
+        ));
+    }
+
+    Ok(changes)
+}
+
+/// Create the forecast sensor change event from the `weather.get_forecasts` service response.
+///
+/// The sensor value is the forecasted condition of the first forecast entry, the `forecast`
+/// attribute contains all forecast entries as provided by HA.
+pub(crate) fn weather_forecast_entity_change(
+    entity_id: &str,
+    forecast: Vec<Value>,
+) -> EntityChange {
+    let condition = forecast
+        .first()
+        .and_then(|v| v.get("condition"))
+        .cloned()
+        .unwrap_or_else(|| "unknown".into());
+    let mut change = sensor_entity_change(
+        weather_sensor_id(entity_id, WEATHER_FORECAST),
+        condition,
+        None,
+    );
+    change
+        .attributes
+        .insert(WEATHER_FORECAST.into(), forecast.into());
+    change
+}
+
+fn sensor_entity_change(entity_id: String, value: Value, unit: Option<Value>) -> EntityChange {
+    let mut attributes = serde_json::Map::with_capacity(2);
+    attributes.insert("value".into(), value);
+    if let Some(unit) = unit {
+        attributes.insert("unit".into(), unit);
+    }
+
+    EntityChange {
+        device_id: None,
+        entity_type: EntityType::Sensor,
+        entity_id,
+        attributes,
+    }
+}
+
+/// Convert a HA weather entity into multiple sensor entities.
+///
+/// Only weather attributes provided by the HA entity are exposed as sensors.
+///
+/// # Arguments
+///
+/// * `entity_id`: HA weather entity identifier.
+/// * `state`: HA weather entity state, which is the current weather condition.
+/// * `ha_attr`: HA weather entity attributes.
+/// * `forecast`: Create the forecast sensor entity.
+///
+/// returns: Result<Vec<AvailableIntgEntity>, ServiceError>
+pub(crate) fn convert_weather_entities(
+    entity_id: String,
+    state: String,
+    ha_attr: &mut Map<String, Value>,
+    forecast: bool,
+) -> Result<Vec<AvailableIntgEntity>, ServiceError> {
+    let friendly_name = ha_attr
+        .get("friendly_name")
+        .and_then(|v| v.as_str())
+        .unwrap_or(&entity_id)
+        .to_string();
+    let mut entities = Vec::with_capacity(WEATHER_SENSORS.len() + 2);
+
+    entities.push(sensor_entity(
+        weather_sensor_id(&entity_id, WEATHER_CONDITION),
+        &friendly_name,
+        WEATHER_CONDITION,
+        None,
+        state.into(),
+        None,
+    ));
+
+    for sensor in &WEATHER_SENSORS {
+        if let Some(value) = ha_attr.get(sensor.attribute) {
+            entities.push(sensor_entity(
+                weather_sensor_id(&entity_id, sensor.attribute),
+                &friendly_name,
+                sensor.attribute,
+                sensor.device_class,
+                value.clone(),
+                sensor.unit(ha_attr),
+            ));
+        }
+    }
+
+    if forecast {
+        // the forecast value is retrieved separately with the `weather.get_forecasts` service
+        entities.push(sensor_entity(
+            weather_sensor_id(&entity_id, WEATHER_FORECAST),
+            &friendly_name,
+            WEATHER_FORECAST,
+            None,
+            "unknown".into(),
+            None,
+        ));
+    }
+
+    Ok(entities)
+}
+
+fn sensor_entity(
+    entity_id: String,
+    friendly_name: &str,
+    name: &str,
+    device_class: Option<&str>,
+    value: Value,
+    unit: Option<Value>,
+) -> AvailableIntgEntity {
+    let label = device_class_to_label(name).unwrap_or_default();
+    let mut options = serde_json::Map::new();
+    if device_class.is_none() {
+        options.insert(SensorOption::CustomLabel.to_string(), label.clone().into());
+        if let Some(unit) = unit.as_ref() {
+            options.insert(SensorOption::CustomUnit.to_string(), unit.clone());
+        }
+    }
+
+    let mut attributes = serde_json::Map::with_capacity(2);
+    attributes.insert("value".into(), value);
+    if let Some(unit) = unit {
+        attributes.insert("unit".into(), unit);
+    }
+
+    AvailableIntgEntity {
+        entity_id,
+        device_id: None, // prepared for device_id handling
+        entity_type: EntityType::Sensor,
+        device_class: Some(device_class.unwrap_or("custom").into()),
+        name: HashMap::from([("en".into(), format!("{friendly_name} {label}"))]),
+        features: None,
+        area: None,
+        options: if options.is_empty() {
+            None
+        } else {
+            Some(options)
+        },
+        attributes: Some(attributes),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::model::EventState;
+    use serde_json::json;
+
+    fn weather_attributes() -> Map<String, Value> {
+        json!({
+            "friendly_name": "Home",
+            "temperature": 18.5,
+            "temperature_unit": "°C",
+            "humidity": 62,
+            "pressure": 1012.3,
+            "pressure_unit": "hPa",
+            "wind_speed": 14.8,
+            "wind_speed_unit": "km/h",
+            "attribution": "Weather forecast from met.no"
+        })
+        .as_object()
+        .cloned()
+        .unwrap()
+    }
+
+    #[test]
+    fn convert_weather_entities_creates_sensor_for_each_attribute() {
+        let mut attr = weather_attributes();
+        let entities =
+            convert_weather_entities("weather.home".into(), "sunny".into(), &mut attr, true)
+                .expect("conversion failed");
+
+        let ids: Vec<&str> = entities.iter().map(|e| e.entity_id.as_str()).collect();
+        assert_eq!(
+            vec![
+                "weather.home.condition",
+                "weather.home.temperature",
+                "weather.home.humidity",
+                "weather.home.pressure",
+                "weather.home.wind_speed",
+                "weather.home.forecast"
+            ],
+            ids
+        );
+
+        let temperature = &entities[1];
+        assert_eq!(Some("temperature".into()), temperature.device_class);
+        assert_eq!(None, temperature.options);
+        assert_eq!(
+            Some(
+                json!({ "value": 18.5, "unit": "°C" })
+                    .as_object()
+                    .cloned()
+                    .unwrap()
+            ),
+            temperature.attributes
+        );
+
+        let wind_speed = &entities[4];
+        assert_eq!(Some("custom".into()), wind_speed.device_class);
+        assert_eq!(
+            Some(&json!("Wind speed")),
+            wind_speed
+                .options
+                .as_ref()
+                .and_then(|o| o.get(&SensorOption::CustomLabel.to_string()))
+        );
+        assert_eq!(
+            Some(&"Home Wind speed".to_string()),
+            wind_speed.name.get("en")
+        );
+    }
+
+    fn weather_sensors(attributes: &[&str]) -> HashSet<String> {
+        attributes
+            .iter()
+            .map(|attr| weather_sensor_id("weather.home", attr))
+            .collect()
+    }
+
+    #[test]
+    fn weather_event_returns_change_for_each_attribute() {
+        let data = EventData {
+            entity_id: "weather.home".into(),
+            new_state: EventState {
+                state: "rainy".into(),
+                attributes: Some(weather_attributes()),
+            },
+        };
+        let sensors = weather_sensors(&["temperature", "humidity", "pressure", "wind_speed"]);
+        let changes = weather_event_to_entity_changes(data, &sensors).expect("conversion failed");
+
+        assert_eq!(5, changes.len());
+        assert_eq!("weather.home.condition", changes[0].entity_id);
+        assert_eq!(Some(&json!("rainy")), changes[0].attributes.get("value"));
+        assert_eq!("weather.home.pressure", changes[3].entity_id);
+        assert_eq!(Some(&json!(1012.3)), changes[3].attributes.get("value"));
+        assert_eq!(Some(&json!("hPa")), changes[3].attributes.get("unit"));
+    }
+
+    #[test]
+    fn weather_event_ignores_attributes_without_sensor() {
+        let data = EventData {
+            entity_id: "weather.home".into(),
+            new_state: EventState {
+                state: "rainy".into(),
+                attributes: Some(weather_attributes()),
+            },
+        };
+        let sensors = weather_sensors(&["temperature", "uv_index"]);
+        let changes = weather_event_to_entity_changes(data, &sensors).expect("conversion failed");
+
+        let ids: Vec<&str> = changes.iter().map(|c| c.entity_id.as_str()).collect();
+        assert_eq!(
+            vec!["weather.home.condition", "weather.home.temperature"],
+            ids
+        );
+    }
+
+    #[test]
+    fn unavailable_weather_event_sets_all_sensors_unavailable() {
+        let data = EventData {
+            entity_id: "weather.home".into(),
+            new_state: EventState {
+                state: "unavailable".into(),
+                attributes: None,
+            },
+        };
+        let sensors = weather_sensors(&["temperature", "humidity", "pressure"]);
+        let changes = weather_event_to_entity_changes(data, &sensors).expect("conversion failed");
+
+        assert_eq!(4, changes.len());
+        assert!(changes
+            .iter()
+            .all(|c| c.attributes.get("value") == Some(&json!("unavailable"))));
+    }
+
+    #[test]
+    fn forecast_entity_change_uses_first_condition_as_value() {
+        let forecast = vec![
+            json!({ "datetime": "2023-08-01T10:00:00+00:00", "condition": "cloudy", "temperature": 21.0 }),
+            json!({ "datetime": "2023-08-02T10:00:00+00:00", "condition": "sunny", "temperature": 25.0 }),
+        ];
+        let change = weather_forecast_entity_change("weather.home", forecast.clone());
+
+        assert_eq!("weather.home.forecast", change.entity_id);
+        assert_eq!(Some(&json!("cloudy")), change.attributes.get("value"));
+        assert_eq!(Some(&json!(forecast)), change.attributes.get("forecast"));
+    }
+}
@@ -7,11 +7,14 @@
 //! information.
 
 use crate::client::entity::*;
-use crate::client::messages::EntityEvent;
+use crate::client::messages::{EntityEvent, EntityNotification};
 use crate::client::model::Event;
 use crate::client::HomeAssistantClient;
 use crate::errors::ServiceError;
+use actix::Context;
 use log::debug;
+use time::OffsetDateTime;
+use uc_api::EntityType;
 
 impl HomeAssistantClient {
     /// Whenever an `event` message is received from HA, this method is called to handle it.  
@@ -24,9 +27,14 @@ impl HomeAssistantClient {
     /// # Arguments
     ///
     /// * `event`: Transformed `.event` json object containing only the required data.
+    /// * `ctx`: Actor execution context
     ///
     /// returns: Result<(), ServiceError>
-    pub(crate) fn handle_event(&mut self, event: Event) -> Result<(), ServiceError> {
+    pub(crate) fn handle_event(
+        &mut self,
+        mut event: Event,
+        ctx: &mut Context<HomeAssistantClient>,
+    ) -> Result<(), ServiceError> {
         let entity_type = match event.data.entity_id.split_once('.') {
             None => return Err(ServiceError::BadRequest("Invalid entity_id format".into())),
             Some((l, _)) => l,
@@ -39,24 +47,166 @@ impl HomeAssistantClient {
             )));
         }
 
-        let entity_change = match entity_type {
+        // additional sensors from entity attributes, independent of the entity type
+        for settings in self
+            .entity_settings
+            .attribute_sensors
+            .iter()
+            .filter(|s| s.entity_id == event.data.entity_id)
+        {
+            self.controller_actor.try_send(EntityEvent {
+                client_id: self.id.clone(),
+                entity_change: attribute_sensor_entity_change(
+                    settings,
+                    &event.data.new_state.state,
+                    event.data.new_state.attributes.as_ref(),
+                ),
+            })?;
+        }
+
+        // camera snapshot refresh triggered by a motion entity
+        self.handle_camera_motion(&event.data.entity_id, &event.data.new_state.state);
+
+        // a weather entity is split into multiple sensor entities
+        if entity_type == "weather" {
+            return self.handle_weather_event(event.data, ctx);
+        }
+        if entity_type == "camera" {
+            return self.handle_camera_event(event.data);
+        }
+
+        // keep the original state for commands depending on the current state
+        let new_state = event.data.new_state.clone();
+        self.convert_temperatures(
+            entity_type,
+            &mut event.data.new_state.state,
+            event.data.new_state.attributes.as_mut(),
+        );
+
+        let mut entity_change = match entity_type {
             "light" => light_event_to_entity_change(event.data),
+            "group" => match event
+                .data
+                .new_state
+                .attributes
+                .as_ref()
+                .and_then(|attr| group_entity_type(&event.data.new_state.state, attr))
+            {
+                Some(EntityType::Light) => light_event_to_entity_change(event.data),
+                Some(EntityType::Cover) => cover_event_to_entity_change(event.data),
+                Some(EntityType::MediaPlayer) => {
+                    media_player_event_to_entity_change(&self.server, event.data)
+                }
+                Some(_) => switch_event_to_entity_change(event.data),
+                None => {
+                    debug!("[{}] Unsupported group: {}", self.id, event.data.entity_id);
+                    return Ok(());
+                }
+            },
             "switch" | "input_boolean" => switch_event_to_entity_change(event.data),
             "button" | "input_button" => {
                 // the button entity is stateless and the remote doesn't need to be notified when the button was pressed externally
                 return Ok(());
             }
+            "siren" => siren_event_to_entity_change(event.data),
             "cover" => cover_event_to_entity_change(event.data),
+            "valve" => valve_event_to_entity_change(event.data),
             "sensor" => sensor_event_to_entity_change(event.data),
             "binary_sensor" => binary_sensor_event_to_entity_change(event.data),
+            "select" | "input_select" => select_event_to_entity_change(event.data),
+            "number" | "input_number" => number_event_to_entity_change(event.data),
+            "person" | "device_tracker" | "zone" => {
+                presence_event_to_entity_change(&self.server, event.data)
+            }
+            "timer" => {
+                self.update_active_timer(
+                    &event.data.entity_id,
+                    &event.data.new_state.state,
+                    event.data.new_state.attributes.as_ref(),
+                );
+                timer_event_to_entity_change(event.data)
+            }
+            "counter" => counter_event_to_entity_change(event.data),
+            "input_datetime" => input_datetime_event_to_entity_change(event.data),
+            "text" | "input_text" => text_event_to_entity_change(event.data),
+            "event" => {
+                if self
+                    .entity_settings
+                    .events
+                    .get(&event.data.entity_id)
+                    .map(|s| s.notification)
+                    .unwrap_or_default()
+                {
+                    if let Some(data) = event_notification_data(
+                        self.entity_states
+                            .get(&event.data.entity_id)
+                            .map(|s| s.state.as_str()),
+                        &event.data.new_state.state,
+                        event.data.new_state.attributes.as_ref(),
+                    ) {
+                        self.controller_actor.try_send(EntityNotification {
+                            client_id: self.id.clone(),
+                            entity_id: event.data.entity_id.clone(),
+                            data,
+                        })?;
+                    }
+                }
+                event_event_to_entity_change(event.data)
+            }
+            "update" => {
+                if self
+                    .entity_settings
+                    .updates
+                    .get(&event.data.entity_id)
+                    .map(|s| s.install_button)
+                    .unwrap_or_default()
+                {
+                    self.controller_actor.try_send(EntityEvent {
+                        client_id: self.id.clone(),
+                        entity_change: update_install_button_entity_change(
+                            &event.data.entity_id,
+                            &event.data.new_state.state,
+                            event.data.new_state.attributes.as_ref(),
+                        ),
+                    })?;
+                }
+                update_event_to_entity_change(event.data)
+            }
             "climate" => climate_event_to_entity_change(event.data),
-            "media_player" => media_player_event_to_entity_change(&self.server, event.data),
+            "water_heater" => water_heater_event_to_entity_change(event.data),
+            "humidifier" => humidifier_event_to_entity_change(event.data),
+            "media_player" => {
+                // don't send position updates matching the interpolated position of the remote
+                if self
+                    .entity_states
+                    .get(&event.data.entity_id)
+                    .map(|old_state| {
+                        is_media_position_refresh(old_state, &new_state, OffsetDateTime::now_utc())
+                    })
+                    .unwrap_or_default()
+                {
+                    self.entity_states.insert(event.data.entity_id, new_state);
+                    return Ok(());
+                }
+                media_player_event_to_entity_change(&self.server, event.data)
+            }
+            "remote" => remote_event_to_entity_change(event.data),
             &_ => {
                 debug!("[{}] Unsupported entity: {}", self.id, entity_type);
                 return Ok(()); // it's not really an error, so it's ok ;-)
             }
         }?;
 
+        // an attached camera snapshot replaces the media image of the entity
+        if let Some(url) = self.attached_camera_snapshot(&entity_change.entity_id) {
+            entity_change
+                .attributes
+                .insert("media_image_url".into(), url.into());
+        }
+
+        self.entity_states
+            .insert(entity_change.entity_id.clone(), new_state);
+
         self.controller_actor.try_send(EntityEvent {
             client_id: self.id.clone(),
             entity_change,
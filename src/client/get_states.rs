@@ -5,13 +5,16 @@
 
 use std::str::FromStr;
 
-use actix::Handler;
+use actix::{Context, Handler};
 use log::{debug, error, warn};
 use serde_json::{json, Value};
+use time::OffsetDateTime;
 use uc_api::EntityType;
 
 use crate::client::entity::*;
 use crate::client::messages::{AvailableEntities, GetStates};
+use crate::client::model::EventState;
+use crate::client::service::{navigation_features, paired_remote_id};
 use crate::client::HomeAssistantClient;
 use crate::errors::ServiceError;
 
@@ -36,8 +39,14 @@ impl HomeAssistantClient {
     pub(crate) fn handle_get_states_result(
         &mut self,
         entities: Vec<Value>,
+        ctx: &mut Context<HomeAssistantClient>,
     ) -> Result<(), ServiceError> {
         let mut available = Vec::with_capacity(32);
+        let mut forecasts = Vec::new();
+        let mut groups = Vec::new();
+        self.entity_states.clear();
+        self.active_timers.clear();
+        self.weather_sensors.clear();
 
         for mut entity in entities {
             let entity_id = entity
@@ -46,7 +55,25 @@ impl HomeAssistantClient {
                 .unwrap_or_default();
             let entity_id = entity_id.to_string();
             let error_id = entity_id.to_string();
-            let entity_type = match entity_id.split_once('.') {
+
+            // additional sensors from entity attributes, independent of the entity type
+            for settings in self
+                .entity_settings
+                .attribute_sensors
+                .iter()
+                .filter(|s| s.entity_id == entity_id)
+            {
+                available.push(convert_attribute_sensor(
+                    settings,
+                    entity
+                        .get("state")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or_default(),
+                    entity.get("attributes").and_then(|v| v.as_object()),
+                ));
+            }
+
+            let (domain, entity_type) = match entity_id.split_once('.') {
                 None => {
                     error!(
                         "[{}] Invalid entity_id format, missing dot to extract domain: {entity_id}",
@@ -55,12 +82,28 @@ impl HomeAssistantClient {
                     continue; // best effort
                 }
                 // map different entity type names
-                Some((domain, _)) => match domain {
-                    "input_boolean" => "switch",
-                    "binary_sensor" => "sensor",
-                    "input_button" => "button",
-                    v => v,
-                },
+                Some((domain, _)) => (
+                    domain,
+                    match domain {
+                        "input_boolean" | "siren" => "switch",
+                        "valve" => "cover",
+                        "binary_sensor" => "sensor",
+                        // HA entities without a matching Integration-API entity
+                        "select" | "input_select" | "number" | "input_number" | "weather" => {
+                            "sensor"
+                        }
+                        "person" | "device_tracker" | "zone" => "sensor",
+                        "timer" | "counter" | "input_datetime" => "sensor",
+                        "text" | "input_text" => "sensor",
+                        "update" | "event" => "sensor",
+                        "input_button" => "button",
+                        "water_heater" | "humidifier" => "climate",
+                        "camera" => "media_player",
+                        // converted with the entity type of the group members
+                        "group" => "switch",
+                        v => v,
+                    },
+                ),
             };
 
             let entity_type = match EntityType::from_str(entity_type) {
@@ -71,7 +114,7 @@ impl HomeAssistantClient {
                 Ok(v) => v,
             };
 
-            let state = entity
+            let mut state = entity
                 .get("state")
                 .and_then(|v| v.as_str())
                 .map(|v| v.to_string())
@@ -87,18 +130,116 @@ impl HomeAssistantClient {
                 Some(o) => o,
             };
 
+            self.entity_states.insert(
+                entity_id.clone(),
+                EventState {
+                    state: state.clone(),
+                    attributes: Some(attr.clone()),
+                },
+            );
+            self.convert_temperatures(domain, &mut state, Some(&mut *attr));
+
+            // a weather entity is split into multiple sensor entities
+            if domain == "weather" {
+                let forecast = self
+                    .entity_settings
+                    .weather
+                    .get(&entity_id)
+                    .and_then(|s| s.forecast);
+                match convert_weather_entities(entity_id.clone(), state, attr, forecast.is_some()) {
+                    Ok(entities) => {
+                        self.weather_sensors.insert(
+                            entity_id.clone(),
+                            entities.iter().map(|e| e.entity_id.clone()).collect(),
+                        );
+                        available.extend(entities)
+                    }
+                    Err(e) => warn!(
+                        "[{}] Could not convert HASS entity {error_id}: {e:?}",
+                        self.id
+                    ),
+                }
+                if let Some(forecast) = forecast {
+                    forecasts.push((entity_id, forecast));
+                }
+                continue;
+            }
+
+            // groups are converted once all member states are known
+            if domain == "group" {
+                groups.push(entity_id);
+                continue;
+            }
+            if matches!(domain, "light" | "switch" | "cover" | "media_player")
+                && group_members(attr).is_some()
+            {
+                groups.push(entity_id.clone());
+            }
+
+            // optional install button of an update entity
+            if domain == "update"
+                && update_supports_install(attr)
+                && self
+                    .entity_settings
+                    .updates
+                    .get(&entity_id)
+                    .map(|s| s.install_button)
+                    .unwrap_or_default()
+            {
+                available.push(convert_update_install_button(&entity_id, &state, attr));
+            }
+
             let avail_entity = match entity_type {
                 EntityType::Button => convert_button_entity(entity_id, state, attr),
-                EntityType::Switch => convert_switch_entity(entity_id, state, attr),
-                EntityType::Climate => convert_climate_entity(entity_id, state, attr),
-                EntityType::Cover => convert_cover_entity(entity_id, state, attr),
+                EntityType::Switch => match domain {
+                    "siren" => convert_siren_entity(entity_id, state, attr),
+                    _ => convert_switch_entity(entity_id, state, attr),
+                },
+                EntityType::Climate => match domain {
+                    "water_heater" => convert_water_heater_entity(entity_id, state, attr),
+                    "humidifier" => convert_humidifier_entity(entity_id, state, attr),
+                    _ => convert_climate_entity(entity_id, state, attr),
+                },
+                EntityType::Cover => match domain {
+                    "valve" => convert_valve_entity(entity_id, state, attr),
+                    _ => convert_cover_entity(entity_id, state, attr),
+                },
                 EntityType::Light => convert_light_entity(entity_id, state, attr),
-                EntityType::MediaPlayer => {
-                    convert_media_player_entity(&self.server, entity_id, state, attr)
+                EntityType::MediaPlayer => match domain {
+                    // an attached camera is only exposed as media image of another entity
+                    "camera" if self.is_attached_camera(&entity_id) => continue,
+                    "camera" => convert_camera_entity(
+                        &self.server,
+                        entity_id,
+                        state,
+                        attr,
+                        OffsetDateTime::now_utc().unix_timestamp(),
+                    ),
+                    _ => convert_media_player_entity(&self.server, entity_id, state, attr),
+                },
+                EntityType::Sensor => match domain {
+                    "select" | "input_select" => convert_select_entity(entity_id, state, attr),
+                    "number" | "input_number" => convert_number_entity(entity_id, state, attr),
+                    "person" | "device_tracker" | "zone" => {
+                        convert_presence_entity(&self.server, entity_id, state, attr)
+                    }
+                    "timer" => {
+                        self.update_active_timer(&entity_id, &state, Some(attr));
+                        convert_timer_entity(entity_id, state, attr)
+                    }
+                    "counter" => convert_counter_entity(entity_id, state, attr),
+                    "input_datetime" => convert_input_datetime_entity(entity_id, state, attr),
+                    "text" | "input_text" => convert_text_entity(entity_id, state, attr),
+                    "update" => convert_update_entity(entity_id, state, attr),
+                    "event" => convert_event_entity(entity_id, state, attr),
+                    _ => convert_sensor_entity(entity_id, state, attr),
+                },
+                EntityType::Remote => {
+                    let settings = self.entity_settings.remotes.get(&entity_id);
+                    convert_remote_entity(entity_id, state, attr, settings)
                 }
-                EntityType::Sensor => convert_sensor_entity(entity_id, state, attr),
                 // internal core entities for the moment
-                EntityType::Activity | EntityType::Macro | EntityType::Remote => {
+                EntityType::Activity | EntityType::Macro => {
                     warn!("[{}] skipping internal entity {entity_type}", self.id);
                     continue;
                 }
@@ -113,11 +254,57 @@ impl HomeAssistantClient {
             }
         }
 
+        self.convert_group_entities(groups, &mut available);
+
+        // attached camera snapshots: all camera states are known at this point
+        for entity in available.iter_mut() {
+            if let Some(url) = self.attached_camera_snapshot(&entity.entity_id) {
+                entity
+                    .attributes
+                    .get_or_insert_with(Default::default)
+                    .insert("media_image_url".into(), url.into());
+            }
+        }
+
+        // navigation commands are sent to the associated remote entity: all remote states are
+        // known at this point
+        for entity in available
+            .iter_mut()
+            .filter(|e| e.entity_type == EntityType::MediaPlayer)
+        {
+            let settings = match self.entity_settings.media_players.get(&entity.entity_id) {
+                None => continue,
+                Some(settings) => settings,
+            };
+            let paired_remote = paired_remote_id(&entity.entity_id)
+                .filter(|remote| self.entity_states.contains_key(remote));
+            if let Some(features) = entity.features.as_mut() {
+                features.extend(
+                    navigation_features(settings, paired_remote.as_deref())
+                        .into_iter()
+                        .map(|v| v.to_string()),
+                );
+            }
+        }
+
+        // virtual template sensors with the last rendered value
+        for (index, settings) in self.entity_settings.template_sensors.iter().enumerate() {
+            available.push(convert_template_sensor(
+                settings,
+                self.template_values.get(&index).cloned(),
+            ));
+        }
+
         self.controller_actor.try_send(AvailableEntities {
             client_id: self.id.clone(),
             entities: available,
         })?;
 
+        // the forecast sensor values are sent in separate entity change events
+        for (entity_id, forecast) in forecasts {
+            self.request_weather_forecast(entity_id, forecast, ctx)?;
+        }
+
         Ok(())
     }
 }
@@ -0,0 +1,112 @@
+// Copyright (c) 2023 Unfolded Circle ApS, Markus Zehnder <markus.z@unfoldedcircle.com>
+// SPDX-License-Identifier: MPL-2.0
+
+//! Group entity conversion.
+//!
+//! The supported features of a group depend on the members and can only be determined once all
+//! entity states are known.
+
+use log::warn;
+use uc_api::intg::AvailableIntgEntity;
+use uc_api::EntityType;
+
+use crate::client::entity::*;
+use crate::client::HomeAssistantClient;
+use crate::errors::ServiceError;
+
+impl HomeAssistantClient {
+    /// Convert the collected group entities with the features supported by all group members.
+    ///
+    /// Old-style `group` entities are converted with the entity type of their members, other
+    /// groups have already been converted with their own entity type.
+    pub(crate) fn convert_group_entities(
+        &self,
+        groups: Vec<String>,
+        available: &mut Vec<AvailableIntgEntity>,
+    ) {
+        for group_id in groups {
+            let state = match self.entity_states.get(&group_id) {
+                Some(state) => state,
+                None => continue,
+            };
+            let ha_attr = match state.attributes.as_ref() {
+                Some(ha_attr) => ha_attr,
+                None => continue,
+            };
+
+            let (index, from_members) = match available.iter().position(|e| e.entity_id == group_id)
+            {
+                Some(index) => (index, false),
+                None => {
+                    let entity_type = match group_entity_type(&state.state, ha_attr) {
+                        Some(entity_type) => entity_type,
+                        None => {
+                            warn!("[{}] Unsupported group entity: {group_id}", self.id);
+                            continue;
+                        }
+                    };
+                    match self.convert_entity_as(
+                        &entity_type,
+                        group_id.clone(),
+                        state.state.clone(),
+                        &mut ha_attr.clone(),
+                    ) {
+                        Ok(entity) => available.push(entity),
+                        Err(e) => {
+                            warn!("[{}] Could not convert group {group_id}: {e:?}", self.id);
+                            continue;
+                        }
+                    }
+                    (available.len() - 1, true)
+                }
+            };
+
+            let members = group_members(ha_attr).unwrap_or_default();
+            let group = &available[index];
+            let member_features: Vec<Vec<String>> = members
+                .iter()
+                .filter_map(|member| self.member_features(&group.entity_type, member))
+                .collect();
+
+            let group = &mut available[index];
+            group.features = group_features(group.features.take(), &member_features, from_members);
+            if self.entity_settings.group_members {
+                group
+                    .options
+                    .get_or_insert_with(Default::default)
+                    .insert("members".into(), members.into());
+            }
+        }
+    }
+
+    /// Get the features of a group member with the given entity type.
+    fn member_features(&self, entity_type: &EntityType, member_id: &str) -> Option<Vec<String>> {
+        let state = self.entity_states.get(member_id)?;
+        let mut ha_attr = state.attributes.clone()?;
+        self.convert_entity_as(
+            entity_type,
+            member_id.into(),
+            state.state.clone(),
+            &mut ha_attr,
+        )
+        .ok()?
+        .features
+    }
+
+    fn convert_entity_as(
+        &self,
+        entity_type: &EntityType,
+        entity_id: String,
+        state: String,
+        ha_attr: &mut serde_json::Map<String, serde_json::Value>,
+    ) -> Result<AvailableIntgEntity, ServiceError> {
+        match entity_type {
+            EntityType::Light => convert_light_entity(entity_id, state, ha_attr),
+            EntityType::Cover => convert_cover_entity(entity_id, state, ha_attr),
+            EntityType::MediaPlayer => {
+                convert_media_player_entity(&self.server, entity_id, state, ha_attr)
+            }
+            _ => convert_switch_entity(entity_id, state, ha_attr),
+        }
+    }
+}
@@ -54,6 +54,16 @@ pub struct EntityEvent {
     pub entity_change: EntityChange,
 }
 
+/// HA entity notification, e.g. a fired event of an event entity.
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct EntityNotification {
+    pub client_id: String,
+    pub entity_id: String,
+    /// Notification data
+    pub data: serde_json::Map<String, serde_json::Value>,
+}
+
 /// HA client request: disconnect and close the session.
 // Used internally by the client and from Controller
 #[derive(Message)]
@@ -3,6 +3,7 @@
 
 //! Home Assistant client WebSocket API implementation with Actix actors.
 
+use std::collections::{HashMap, HashSet};
 use std::env;
 use std::time::Instant;
 
@@ -19,20 +20,28 @@ use serde_json::{json, Value};
 use url::Url;
 
 use crate::client::messages::{ConnectionEvent, ConnectionState};
-use crate::client::model::Event;
-use crate::configuration::{HeartbeatSettings, ENV_HASS_MSG_TRACING};
+use crate::client::model::{Event, EventState};
+use crate::configuration::{
+    EntitySettings, HeartbeatSettings, TemperatureUnit, ENV_HASS_MSG_TRACING,
+};
 use crate::errors::ServiceError;
 use crate::Controller;
 
 mod actor;
+mod camera;
 mod close_handler;
+mod config;
 mod entity;
 mod event;
 mod get_states;
+mod group;
 pub mod messages;
 mod model;
 mod service;
 mod streamhandler;
+mod template;
+mod timer;
+mod weather;
 
 pub struct HomeAssistantClient {
     /// Unique HA client id
@@ -47,6 +56,10 @@ pub struct HomeAssistantClient {
     subscribe_events_id: Option<u32>,
     /// request id of the last `subscribe_events` request. This id will be used the result message.
     entity_states_id: Option<u32>,
+    /// request id of the last `get_config` request. This id will be used the result message.
+    config_id: Option<u32>,
+    /// Temperature unit of the HA unit system, retrieved with `get_config`.
+    temperature_unit: Option<TemperatureUnit>,
     sink: SinkWrite<ws::Message, SplitSink<Framed<BoxedSocket, ws::Codec>, ws::Message>>,
     controller_actor: Addr<Controller>,
     /// Last heart beat timestamp.
@@ -57,6 +70,22 @@ pub struct HomeAssistantClient {
     /// Enable outgoing websocket message tracing: log every message, except messages with key
     /// `access_token`.
     msg_tracing_out: bool,
+    /// Optional entity specific settings from the configuration file.
+    entity_settings: EntitySettings,
+    /// Last known state and original HA attributes of the supported entities.
+    ///
+    /// Required for commands depending on the current entity state.
+    entity_states: HashMap<String, EventState>,
+    /// Pending `weather.get_forecasts` requests. Key: request id, value: weather entity_id
+    forecast_requests: HashMap<u32, String>,
+    /// Sensor entities of the weather entities. Key: weather entity_id, value: sensor entity_ids
+    weather_sensors: HashMap<String, HashSet<String>>,
+    /// Active `render_template` subscriptions. Key: request id, value: template sensor index
+    template_subscriptions: HashMap<u32, usize>,
+    /// Last rendered template sensor values. Key: template sensor index
+    template_values: HashMap<usize, Value>,
+    /// Active timer entities for the countdown. Key: entity_id, value: timer end
+    active_timers: HashMap<String, time::OffsetDateTime>,
 }
 
 impl HomeAssistantClient {
@@ -67,6 +96,7 @@ impl HomeAssistantClient {
         sink: SplitSink<Framed<BoxedSocket, ws::Codec>, ws::Message>,
         stream: SplitStream<Framed<BoxedSocket, ws::Codec>>,
         heartbeat: HeartbeatSettings,
+        entity_settings: EntitySettings,
     ) -> Addr<Self> {
         HomeAssistantClient::create(|ctx| {
             ctx.add_stream(stream);
@@ -89,12 +119,21 @@ impl HomeAssistantClient {
                 subscribed_events: false,
                 subscribe_events_id: None,
                 entity_states_id: None,
+                config_id: None,
+                temperature_unit: None,
                 sink: SinkWrite::new(sink, ctx),
                 controller_actor,
                 last_hb: Instant::now(),
                 heartbeat,
                 msg_tracing_in: msg_tracing == "all" || msg_tracing == "in",
                 msg_tracing_out: msg_tracing == "all" || msg_tracing == "out",
+                entity_settings,
+                entity_states: Default::default(),
+                forecast_requests: Default::default(),
+                weather_sensors: Default::default(),
+                template_subscriptions: Default::default(),
+                template_values: Default::default(),
+                active_timers: Default::default(),
             }
         })
     }
@@ -153,6 +192,13 @@ impl HomeAssistantClient {
             .unwrap_or_default()
         {
             "event" => {
+                if let Some(index) = self.template_subscriptions.get(&id).copied() {
+                    self.handle_template_event(
+                        index,
+                        object_msg.remove("event").unwrap_or(Value::Null),
+                    );
+                    return;
+                }
                 // TODO should we only check Event.event_type == "state_changed"? The id check worked well though in YIO v1
                 if Some(id) != self.subscribe_events_id {
                     debug!(
@@ -165,7 +211,7 @@ impl HomeAssistantClient {
                     object_msg.remove("event").unwrap_or(Value::Null),
                 );
                 if let Ok(event) = event {
-                    if let Err(e) = self.handle_event(event) {
+                    if let Err(e) = self.handle_event(event, ctx) {
                         error!(
                             "[{}] Error handling HA state_changed event: {:?}",
                             self.id, e
@@ -182,6 +228,7 @@ impl HomeAssistantClient {
                     self.subscribed_events = success;
                     if self.subscribed_events {
                         debug!("[{}] Subscribed to state changes", self.id);
+                        self.subscribe_templates(ctx);
                         self.controller_actor.do_send(ConnectionEvent {
                             client_id: self.id.clone(),
                             state: ConnectionState::Connected,
@@ -200,10 +247,16 @@ impl HomeAssistantClient {
                     {
                         // this looks ugly! Is there a better way to get ownership of the array?
                         let entities: Vec<Value> = entities.iter_mut().map(|v| v.take()).collect();
-                        if let Err(e) = self.handle_get_states_result(entities) {
+                        if let Err(e) = self.handle_get_states_result(entities, ctx) {
                             error!("[{}] Error handling HA get_states result: {:?}", self.id, e);
                         }
                     }
+                } else if Some(id) == self.config_id {
+                    self.handle_config_result(success, object_msg);
+                } else if let Some(entity_id) = self.forecast_requests.remove(&id) {
+                    self.handle_weather_forecast_result(&entity_id, success, object_msg);
+                } else if let Some(index) = self.template_subscriptions.get(&id).copied() {
+                    self.handle_template_result(index, success, object_msg);
                 }
             }
             "auth_required" => {
@@ -225,6 +278,11 @@ impl HomeAssistantClient {
             "auth_ok" => {
                 info!("[{}] Authentication OK", self.id);
 
+                // the unit system is required before the entity states are requested
+                if let Err(e) = self.request_config(ctx) {
+                    error!("[{}] Error sending get_config to HA: {:?}", self.id, e);
+                }
+
                 if !self.subscribed_events {
                     self.subscribe_events_id = Some(self.new_msg_id());
                     if let Err(e) = self.send_json(
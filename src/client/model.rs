@@ -15,6 +15,9 @@ pub(crate) struct CallServiceMsg {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub service_data: Option<serde_json::Value>,
     pub target: Target,
+    /// Request the service response data. Only supported by services returning data.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub return_response: Option<bool>,
 }
 
 #[derive(Debug, Serialize)]
@@ -34,7 +37,7 @@ pub(crate) struct EventData {
     pub new_state: EventState,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 pub(crate) struct EventState {
     pub state: String,
     pub attributes: Option<serde_json::Map<String, serde_json::Value>>,
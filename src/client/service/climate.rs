@@ -3,14 +3,41 @@
 
 //! Climate entity specific HA service call logic.
 
+use crate::client::entity::{temperature_to_ha, TemperatureConversion};
+use crate::client::model::EventState;
 use crate::client::service::{cmd_from_str, get_required_params};
 use crate::errors::ServiceError;
-use crate::util::json::copy_entry;
 use serde_json::{json, Map, Value};
+use std::str::FromStr;
+use strum_macros::{EnumString, EnumVariantNames};
 use uc_api::intg::EntityCommand;
 use uc_api::ClimateCommand;
 
-pub(crate) fn handle_climate(msg: &EntityCommand) -> Result<(String, Option<Value>), ServiceError> {
+/// Additional climate commands, not yet available in the Integration-API library.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumString, EnumVariantNames)]
+#[strum(serialize_all = "snake_case")]
+pub enum ClimateExtraCommand {
+    FanMode,
+    PresetMode,
+    SwingMode,
+    TargetHumidity,
+    TargetTemperatureRange,
+}
+
+/// Temperature values are converted from the preferred temperature unit to the HA unit system,
+/// if a `conversion` is given.
+pub(crate) fn handle_climate(
+    msg: &EntityCommand,
+    state: Option<&EventState>,
+    conversion: Option<TemperatureConversion>,
+) -> Result<(String, Option<Value>), ServiceError> {
+    let ha_attr = state.and_then(|s| s.attributes.as_ref());
+    let to_ha = |temp: f64| temperature_to_ha(temp, conversion, ha_attr);
+
+    if let Ok(cmd) = ClimateExtraCommand::from_str(&msg.cmd_id) {
+        return handle_climate_extra(cmd, msg, to_ha);
+    }
+
     let cmd: ClimateCommand = cmd_from_str(&msg.cmd_id)?;
 
     let result = match cmd {
@@ -38,18 +65,20 @@ pub(crate) fn handle_climate(msg: &EntityCommand) -> Result<(String, Option<Valu
                 }
             }
 
-            // TODO can we send a temperature param in set_hvac_mode? #12
-            // If not: remove example from entity docs...
-            copy_entry(params, &mut data, "temperature");
-
-            ("set_hvac_mode".into(), Some(data.into()))
+            // set_hvac_mode doesn't accept a temperature, but set_temperature accepts a hvac_mode
+            if let Some(temp) = params.get("temperature").and_then(|v| v.as_f64()) {
+                data.insert("temperature".into(), to_ha(temp).into());
+                ("set_temperature".into(), Some(data.into()))
+            } else {
+                ("set_hvac_mode".into(), Some(data.into()))
+            }
         }
         ClimateCommand::TargetTemperature => {
             let params = get_required_params(msg)?;
             if let Some(temp) = params.get("temperature").and_then(|v| v.as_f64()) {
                 (
                     "set_temperature".into(),
-                    Some(json!({ "temperature": temp })),
+                    Some(json!({ "temperature": to_ha(temp) })),
                 )
             } else {
                 return Err(ServiceError::BadRequest(
@@ -62,9 +91,85 @@ pub(crate) fn handle_climate(msg: &EntityCommand) -> Result<(String, Option<Valu
     Ok(result)
 }
 
+fn handle_climate_extra(
+    cmd: ClimateExtraCommand,
+    msg: &EntityCommand,
+    to_ha: impl Fn(f64) -> f64,
+) -> Result<(String, Option<Value>), ServiceError> {
+    let params = get_required_params(msg)?;
+
+    let result = match cmd {
+        ClimateExtraCommand::FanMode => {
+            let mode = required_mode(params, "fan_mode")?;
+            ("set_fan_mode".into(), Some(json!({ "fan_mode": mode })))
+        }
+        ClimateExtraCommand::PresetMode => {
+            let mode = required_mode(params, "preset_mode")?;
+            (
+                "set_preset_mode".into(),
+                Some(json!({ "preset_mode": mode })),
+            )
+        }
+        ClimateExtraCommand::SwingMode => {
+            let mode = required_mode(params, "swing_mode")?;
+            ("set_swing_mode".into(), Some(json!({ "swing_mode": mode })))
+        }
+        ClimateExtraCommand::TargetHumidity => {
+            let humidity = params
+                .get("humidity")
+                .and_then(|v| v.as_f64())
+                .filter(|v| (0.0..=100.0).contains(v))
+                .ok_or_else(|| {
+                    ServiceError::BadRequest(
+                        "Invalid or missing params.humidity attribute. Valid: 0..100".into(),
+                    )
+                })?;
+            (
+                "set_humidity".into(),
+                Some(json!({ "humidity": humidity.round() as u32 })),
+            )
+        }
+        ClimateExtraCommand::TargetTemperatureRange => {
+            let low = params
+                .get("target_temperature_low")
+                .and_then(|v| v.as_f64());
+            let high = params
+                .get("target_temperature_high")
+                .and_then(|v| v.as_f64());
+            match (low, high) {
+                (Some(low), Some(high)) if low <= high => (
+                    "set_temperature".into(),
+                    Some(json!({ "target_temp_low": to_ha(low), "target_temp_high": to_ha(high) })),
+                ),
+                _ => {
+                    return Err(ServiceError::BadRequest(
+                        "Invalid or missing params.target_temperature_low and params.target_temperature_high attributes".into(),
+                    ))
+                }
+            }
+        }
+    };
+
+    Ok(result)
+}
+
+/// Get a non-empty mode parameter. Modes are passed as-is, since they are integration specific.
+fn required_mode<'a>(params: &'a Map<String, Value>, key: &str) -> Result<&'a str, ServiceError> {
+    match params.get(key).and_then(|v| v.as_str()) {
+        Some(mode) if !mode.is_empty() => Ok(mode),
+        _ => Err(ServiceError::BadRequest(format!(
+            "Invalid or missing params.{key} attribute"
+        ))),
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use crate::client::entity::TemperatureConversion;
+    use crate::client::model::EventState;
     use crate::client::service::climate::handle_climate;
+    use crate::configuration::TemperatureUnit;
+    use crate::errors::ServiceError;
     use rstest::rstest;
     use serde_json::{json, Value};
     use uc_api::intg::EntityCommand;
@@ -133,9 +238,112 @@ mod tests {
         assert_eq!(Some(&json!(22.5)), data.get("temperature"));
     }
 
+    #[test]
+    fn hvac_mode_with_temperature_uses_set_temperature() {
+        let msg_data = json!({
+            "cmd_id": "hvac_mode",
+            "entity_id": "climate.living_room",
+            "entity_type": "climate",
+            "params": {
+                "hvac_mode": "HEAT",
+                "temperature": 21.5
+            }
+        });
+        let (cmd, data) = map_msg_data(msg_data);
+        assert_eq!("set_temperature", cmd);
+        assert_eq!(
+            Some(json!({ "hvac_mode": "heat", "temperature": 21.5 })),
+            data
+        );
+    }
+
+    #[rstest]
+    #[case("fan_mode", json!({ "fan_mode": "Auto low" }), "set_fan_mode", json!({ "fan_mode": "Auto low" }))]
+    #[case("preset_mode", json!({ "preset_mode": "eco" }), "set_preset_mode", json!({ "preset_mode": "eco" }))]
+    #[case("swing_mode", json!({ "swing_mode": "vertical" }), "set_swing_mode", json!({ "swing_mode": "vertical" }))]
+    #[case("target_humidity", json!({ "humidity": 45.6 }), "set_humidity", json!({ "humidity": 46 }))]
+    #[case(
+        "target_temperature_range",
+        json!({ "target_temperature_low": 19.5, "target_temperature_high": 24 }),
+        "set_temperature",
+        json!({ "target_temp_low": 19.5, "target_temp_high": 24.0 })
+    )]
+    fn extra_cmd_returns_service_with_data(
+        #[case] cmd_id: &str,
+        #[case] params: Value,
+        #[case] service: &str,
+        #[case] expected: Value,
+    ) {
+        let msg_data = json!({
+            "cmd_id": cmd_id,
+            "entity_id": "climate.living_room",
+            "entity_type": "climate",
+            "params": params
+        });
+        let (cmd, data) = map_msg_data(msg_data);
+        assert_eq!(service, cmd);
+        assert_eq!(Some(expected), data);
+    }
+
+    #[rstest]
+    #[case("hvac_mode", json!({ "hvac_mode": "DRY" }))]
+    #[case("fan_mode", json!({ "fan_mode": "" }))]
+    #[case("preset_mode", json!({}))]
+    #[case("swing_mode", Value::Null)]
+    #[case("target_humidity", json!({ "humidity": 101 }))]
+    #[case("target_temperature_range", json!({ "target_temperature_low": 19 }))]
+    #[case("target_temperature_range", json!({ "target_temperature_low": 25, "target_temperature_high": 20 }))]
+    fn invalid_request_returns_bad_request(#[case] cmd_id: &str, #[case] params: Value) {
+        let msg_data = json!({
+            "cmd_id": cmd_id,
+            "entity_id": "climate.living_room",
+            "entity_type": "climate",
+            "params": params
+        });
+        let cmd: EntityCommand = serde_json::from_value(msg_data).expect("invalid test data");
+        let result = handle_climate(&cmd, None, None);
+
+        assert!(
+            matches!(result, Err(ServiceError::BadRequest(_))),
+            "Invalid value must return BadRequest, but got: {:?}",
+            result
+        );
+    }
+
+    #[test]
+    fn set_temperature_is_converted_to_ha_unit() {
+        let msg_data = json!({
+            "cmd_id": "target_temperature",
+            "entity_id": "climate.living_room",
+            "entity_type": "climate",
+            "params": {
+              "temperature": 72
+            }
+        });
+        let cmd: EntityCommand = serde_json::from_value(msg_data).expect("invalid test data");
+        let state = EventState {
+            state: "heat".into(),
+            attributes: json!({ "target_temp_step": 0.5 }).as_object().cloned(),
+        };
+        let conversion = TemperatureConversion {
+            unit: TemperatureUnit::Fahrenheit,
+            ha_unit: TemperatureUnit::Celsius,
+        };
+
+        let result = handle_climate(&cmd, Some(&state), Some(conversion));
+
+        assert_eq!(
+            Ok((
+                "set_temperature".to_string(),
+                Some(json!({ "temperature": 22.0 }))
+            )),
+            result
+        );
+    }
+
     fn map_msg_data(msg_data: Value) -> (String, Option<Value>) {
         let cmd: EntityCommand = serde_json::from_value(msg_data).expect("invalid test data");
-        let result = handle_climate(&cmd);
+        let result = handle_climate(&cmd, None, None);
         assert!(
             result.is_ok(),
             "Expected successful cmd mapping but got: {:?}",
@@ -0,0 +1,99 @@
+// Copyright (c) 2023 Unfolded Circle ApS, Markus Zehnder <markus.z@unfoldedcircle.com>
+// SPDX-License-Identifier: MPL-2.0
+
+//! Counter entity specific HA service call logic.
+
+use crate::client::service::{cmd_from_str, get_required_params};
+use crate::errors::ServiceError;
+use serde_json::{json, Value};
+use strum_macros::{EnumString, EnumVariantNames};
+use uc_api::intg::EntityCommand;
+
+/// Counter entity commands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumString, EnumVariantNames)]
+#[strum(serialize_all = "snake_case")]
+pub enum CounterCommand {
+    Increment,
+    Decrement,
+    Reset,
+    SetValue,
+}
+
+pub(crate) fn handle_counter(msg: &EntityCommand) -> Result<(String, Option<Value>), ServiceError> {
+    let cmd: CounterCommand = cmd_from_str(&msg.cmd_id)?;
+
+    let result = match cmd {
+        CounterCommand::Increment => ("increment".into(), None),
+        CounterCommand::Decrement => ("decrement".into(), None),
+        CounterCommand::Reset => ("reset".into(), None),
+        CounterCommand::SetValue => {
+            let params = get_required_params(msg)?;
+            if let Some(value) = params.get("value").and_then(|v| v.as_i64()) {
+                ("set_value".into(), Some(json!({ "value": value })))
+            } else {
+                return Err(ServiceError::BadRequest(
+                    "Invalid or missing params.value attribute: integer required".into(),
+                ));
+            }
+        }
+    };
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::client::service::counter::handle_counter;
+    use crate::errors::ServiceError;
+    use rstest::rstest;
+    use serde_json::{json, Value};
+    use uc_api::intg::EntityCommand;
+    use uc_api::EntityType;
+
+    fn new_entity_command(cmd_id: impl Into<String>, params: Value) -> EntityCommand {
+        EntityCommand {
+            device_id: None,
+            entity_type: EntityType::Sensor,
+            entity_id: "counter.coffee".into(),
+            cmd_id: cmd_id.into(),
+            params: params.as_object().cloned(),
+        }
+    }
+
+    #[rstest]
+    #[case("increment")]
+    #[case("decrement")]
+    #[case("reset")]
+    fn simple_cmd_returns_service_without_data(#[case] cmd_id: &str) {
+        let cmd = new_entity_command(cmd_id, Value::Null);
+        let result = handle_counter(&cmd);
+
+        assert_eq!(Ok((cmd_id.to_string(), None)), result);
+    }
+
+    #[test]
+    fn set_value_returns_value() {
+        let cmd = new_entity_command("set_value", json!({ "value": 7 }));
+        let result = handle_counter(&cmd);
+
+        assert_eq!(
+            Ok(("set_value".to_string(), Some(json!({ "value": 7 })))),
+            result
+        );
+    }
+
+    #[rstest]
+    #[case(Value::Null)]
+    #[case(json!({ "value": 1.5 }))]
+    #[case(json!({ "value": "7" }))]
+    fn set_value_with_invalid_params_returns_bad_request(#[case] params: Value) {
+        let cmd = new_entity_command("set_value", params);
+        let result = handle_counter(&cmd);
+
+        assert!(
+            matches!(result, Err(ServiceError::BadRequest(_))),
+            "Invalid value must return BadRequest, but got: {:?}",
+            result
+        );
+    }
+}
@@ -3,13 +3,29 @@
 
 //! Cover entity specific HA service call logic.
 
-use crate::client::service::cmd_from_str;
+use crate::client::service::{cmd_from_str, get_required_params};
 use crate::errors::ServiceError;
-use serde_json::{Map, Value};
+use serde_json::{json, Map, Value};
+use std::str::FromStr;
+use strum_macros::{EnumString, EnumVariantNames};
 use uc_api::intg::EntityCommand;
 use uc_api::CoverCommand;
 
+/// Cover tilt commands, not yet available in the Integration-API library.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumString, EnumVariantNames)]
+#[strum(serialize_all = "snake_case")]
+pub enum CoverTiltCommand {
+    Tilt,
+    TiltUp,
+    TiltDown,
+    TiltStop,
+}
+
 pub(crate) fn handle_cover(msg: &EntityCommand) -> Result<(String, Option<Value>), ServiceError> {
+    if let Ok(cmd) = CoverTiltCommand::from_str(&msg.cmd_id) {
+        return handle_cover_tilt(cmd, msg);
+    }
+
     let cmd: CoverCommand = cmd_from_str(&msg.cmd_id)?;
 
     let result = match cmd {
@@ -24,8 +40,99 @@ pub(crate) fn handle_cover(msg: &EntityCommand) -> Result<(String, Option<Value>
                 }
             }
             ("set_cover_position".into(), Some(data.into()))
-        } // TODO implement tilt command #6
+        }
+    };
+
+    Ok(result)
+}
+
+fn handle_cover_tilt(
+    cmd: CoverTiltCommand,
+    msg: &EntityCommand,
+) -> Result<(String, Option<Value>), ServiceError> {
+    let result = match cmd {
+        CoverTiltCommand::Tilt => {
+            let params = get_required_params(msg)?;
+            match params.get("tilt_position").and_then(|v| v.as_u64()) {
+                Some(pos @ 0..=100) => (
+                    "set_cover_tilt_position".into(),
+                    Some(json!({ "tilt_position": pos })),
+                ),
+                _ => {
+                    return Err(ServiceError::BadRequest(
+                        "Invalid or missing params.tilt_position attribute. Valid: 0..100".into(),
+                    ))
+                }
+            }
+        }
+        CoverTiltCommand::TiltUp => ("open_cover_tilt".into(), None),
+        CoverTiltCommand::TiltDown => ("close_cover_tilt".into(), None),
+        CoverTiltCommand::TiltStop => ("stop_cover_tilt".into(), None),
     };
 
     Ok(result)
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::client::service::cover::handle_cover;
+    use crate::errors::ServiceError;
+    use rstest::rstest;
+    use serde_json::{json, Value};
+    use uc_api::intg::EntityCommand;
+    use uc_api::EntityType;
+
+    fn new_entity_command(cmd_id: impl Into<String>, params: Value) -> EntityCommand {
+        EntityCommand {
+            device_id: None,
+            entity_type: EntityType::Cover,
+            entity_id: "cover.living_room".into(),
+            cmd_id: cmd_id.into(),
+            params: params.as_object().cloned(),
+        }
+    }
+
+    #[rstest]
+    #[case("open", "open_cover")]
+    #[case("close", "close_cover")]
+    #[case("stop", "stop_cover")]
+    #[case("tilt_up", "open_cover_tilt")]
+    #[case("tilt_down", "close_cover_tilt")]
+    #[case("tilt_stop", "stop_cover_tilt")]
+    fn simple_cmd_returns_service_without_data(#[case] cmd_id: &str, #[case] service: &str) {
+        let cmd = new_entity_command(cmd_id, Value::Null);
+        let result = handle_cover(&cmd);
+
+        assert_eq!(Ok((service.to_string(), None)), result);
+    }
+
+    #[rstest]
+    #[case("position", json!({ "position": 40 }), "set_cover_position", json!({ "position": 40 }))]
+    #[case("tilt", json!({ "tilt_position": 60 }), "set_cover_tilt_position", json!({ "tilt_position": 60 }))]
+    fn position_cmd_returns_service_data(
+        #[case] cmd_id: &str,
+        #[case] params: Value,
+        #[case] service: &str,
+        #[case] expected: Value,
+    ) {
+        let cmd = new_entity_command(cmd_id, params);
+        let result = handle_cover(&cmd);
+
+        assert_eq!(Ok((service.to_string(), Some(expected))), result);
+    }
+
+    #[rstest]
+    #[case(Value::Null)]
+    #[case(json!({ "tilt_position": 101 }))]
+    #[case(json!({ "position": 50 }))]
+    fn tilt_with_invalid_position_returns_bad_request(#[case] params: Value) {
+        let cmd = new_entity_command("tilt", params);
+        let result = handle_cover(&cmd);
+
+        assert!(
+            matches!(result, Err(ServiceError::BadRequest(_))),
+            "Invalid tilt position must return BadRequest, but got: {:?}",
+            result
+        );
+    }
+}
@@ -0,0 +1,159 @@
+// Copyright (c) 2023 Unfolded Circle ApS, Markus Zehnder <markus.z@unfoldedcircle.com>
+// SPDX-License-Identifier: MPL-2.0
+
+//! Humidifier entity specific HA service call logic.
+
+use crate::client::service::{cmd_from_str, get_required_params};
+use crate::errors::ServiceError;
+use serde_json::{json, Value};
+use std::str::FromStr;
+use strum_macros::{EnumString, EnumVariantNames};
+use uc_api::intg::EntityCommand;
+use uc_api::ClimateCommand;
+
+/// Humidifier specific commands in addition to the climate entity commands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumString, EnumVariantNames)]
+#[strum(serialize_all = "snake_case")]
+pub enum HumidifierCommand {
+    TargetHumidity,
+    Mode,
+}
+
+pub(crate) fn handle_humidifier(
+    msg: &EntityCommand,
+) -> Result<(String, Option<Value>), ServiceError> {
+    if let Ok(cmd) = HumidifierCommand::from_str(&msg.cmd_id) {
+        let params = get_required_params(msg)?;
+        let result = match cmd {
+            HumidifierCommand::TargetHumidity => {
+                let humidity = params
+                    .get("humidity")
+                    .and_then(|v| v.as_f64())
+                    .filter(|v| (0.0..=100.0).contains(v))
+                    .ok_or_else(|| {
+                        ServiceError::BadRequest(
+                            "Invalid or missing params.humidity attribute. Valid: 0..100".into(),
+                        )
+                    })?;
+                (
+                    "set_humidity".into(),
+                    Some(json!({ "humidity": humidity.round() as u32 })),
+                )
+            }
+            HumidifierCommand::Mode => {
+                let mode = match params.get("mode").and_then(|v| v.as_str()) {
+                    Some(mode) if !mode.is_empty() => mode,
+                    _ => {
+                        return Err(ServiceError::BadRequest(
+                            "Invalid or missing params.mode attribute".into(),
+                        ))
+                    }
+                };
+                ("set_mode".into(), Some(json!({ "mode": mode })))
+            }
+        };
+        return Ok(result);
+    }
+
+    let cmd: ClimateCommand = cmd_from_str(&msg.cmd_id)?;
+
+    let result = match cmd {
+        ClimateCommand::On => ("turn_on".into(), None),
+        ClimateCommand::Off => ("turn_off".into(), None),
+        ClimateCommand::HvacMode => {
+            let params = get_required_params(msg)?;
+            match params.get("hvac_mode").and_then(|v| v.as_str()) {
+                Some("OFF") => ("turn_off".into(), None),
+                Some("AUTO") => ("turn_on".into(), None),
+                mode => {
+                    return Err(ServiceError::BadRequest(format!(
+                        "Invalid or missing params.hvac_mode attribute: {}. Valid: OFF, AUTO",
+                        mode.unwrap_or_default()
+                    )));
+                }
+            }
+        }
+        ClimateCommand::TargetTemperature => {
+            return Err(ServiceError::BadRequest(
+                "Humidifier doesn't support a target temperature. Use target_humidity instead"
+                    .into(),
+            ));
+        }
+    };
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::client::service::humidifier::handle_humidifier;
+    use crate::errors::ServiceError;
+    use rstest::rstest;
+    use serde_json::{json, Value};
+    use uc_api::intg::EntityCommand;
+    use uc_api::EntityType;
+
+    fn new_entity_command(cmd_id: impl Into<String>, params: Value) -> EntityCommand {
+        EntityCommand {
+            device_id: None,
+            entity_type: EntityType::Climate,
+            entity_id: "humidifier.bedroom".into(),
+            cmd_id: cmd_id.into(),
+            params: params.as_object().cloned(),
+        }
+    }
+
+    #[rstest]
+    #[case("on", Value::Null, "turn_on")]
+    #[case("off", Value::Null, "turn_off")]
+    #[case("hvac_mode", json!({ "hvac_mode": "OFF" }), "turn_off")]
+    #[case("hvac_mode", json!({ "hvac_mode": "AUTO" }), "turn_on")]
+    fn on_off_cmd_returns_service_without_data(
+        #[case] cmd_id: &str,
+        #[case] params: Value,
+        #[case] service: &str,
+    ) {
+        let cmd = new_entity_command(cmd_id, params);
+        let result = handle_humidifier(&cmd);
+
+        assert_eq!(Ok((service.to_string(), None)), result);
+    }
+
+    #[rstest]
+    #[case(json!({ "humidity": 45 }), json!({ "humidity": 45 }))]
+    #[case(json!({ "humidity": 45.6 }), json!({ "humidity": 46 }))]
+    fn target_humidity_returns_set_humidity(#[case] params: Value, #[case] expected: Value) {
+        let cmd = new_entity_command("target_humidity", params);
+        let result = handle_humidifier(&cmd);
+
+        assert_eq!(Ok(("set_humidity".to_string(), Some(expected))), result);
+    }
+
+    #[test]
+    fn mode_returns_set_mode() {
+        let cmd = new_entity_command("mode", json!({ "mode": "sleep" }));
+        let result = handle_humidifier(&cmd);
+
+        assert_eq!(
+            Ok(("set_mode".to_string(), Some(json!({ "mode": "sleep" })))),
+            result
+        );
+    }
+
+    #[rstest]
+    #[case("hvac_mode", json!({ "hvac_mode": "HEAT" }))]
+    #[case("target_temperature", json!({ "temperature": 21 }))]
+    #[case("target_humidity", json!({ "humidity": 101 }))]
+    #[case("target_humidity", Value::Null)]
+    #[case("mode", json!({ "mode": "" }))]
+    fn invalid_request_returns_bad_request(#[case] cmd_id: &str, #[case] params: Value) {
+        let cmd = new_entity_command(cmd_id, params);
+        let result = handle_humidifier(&cmd);
+
+        assert!(
+            matches!(result, Err(ServiceError::BadRequest(_))),
+            "Invalid value must return BadRequest, but got: {:?}",
+            result
+        );
+    }
+}
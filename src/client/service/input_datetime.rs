@@ -0,0 +1,97 @@
+// Copyright (c) 2023 Unfolded Circle ApS, Markus Zehnder <markus.z@unfoldedcircle.com>
+// SPDX-License-Identifier: MPL-2.0
+
+//! Input_datetime entity specific HA service call logic.
+
+use crate::client::service::{cmd_from_str, get_required_params};
+use crate::errors::ServiceError;
+use serde_json::{Map, Value};
+use strum_macros::{EnumString, EnumVariantNames};
+use uc_api::intg::EntityCommand;
+
+/// Input_datetime entity commands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumString, EnumVariantNames)]
+#[strum(serialize_all = "snake_case")]
+pub enum InputDatetimeCommand {
+    SetDatetime,
+}
+
+pub(crate) fn handle_input_datetime(
+    msg: &EntityCommand,
+) -> Result<(String, Option<Value>), ServiceError> {
+    let cmd: InputDatetimeCommand = cmd_from_str(&msg.cmd_id)?;
+
+    let result = match cmd {
+        InputDatetimeCommand::SetDatetime => {
+            let params = get_required_params(msg)?;
+            let mut data = Map::new();
+            // `datetime` and `timestamp` can't be combined with `date` or `time`
+            if let Some(v) = params.get("datetime").filter(|v| v.is_string()) {
+                data.insert("datetime".into(), v.clone());
+            } else if let Some(v) = params.get("timestamp").filter(|v| v.is_number()) {
+                data.insert("timestamp".into(), v.clone());
+            } else {
+                for key in ["date", "time"] {
+                    if let Some(v) = params.get(key).filter(|v| v.is_string()) {
+                        data.insert(key.into(), v.clone());
+                    }
+                }
+            }
+            if data.is_empty() {
+                return Err(ServiceError::BadRequest(
+                    "Missing params attribute: date, time, datetime or timestamp required".into(),
+                ));
+            }
+            ("set_datetime".into(), Some(data.into()))
+        }
+    };
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::client::service::input_datetime::handle_input_datetime;
+    use crate::errors::ServiceError;
+    use rstest::rstest;
+    use serde_json::{json, Value};
+    use uc_api::intg::EntityCommand;
+    use uc_api::EntityType;
+
+    fn new_entity_command(params: Value) -> EntityCommand {
+        EntityCommand {
+            device_id: None,
+            entity_type: EntityType::Sensor,
+            entity_id: "input_datetime.wake_up".into(),
+            cmd_id: "set_datetime".into(),
+            params: params.as_object().cloned(),
+        }
+    }
+
+    #[rstest]
+    #[case(json!({ "time": "07:30:00" }), json!({ "time": "07:30:00" }))]
+    #[case(json!({ "date": "2023-08-01", "time": "07:30:00" }), json!({ "date": "2023-08-01", "time": "07:30:00" }))]
+    #[case(json!({ "datetime": "2023-08-01 07:30:00", "time": "08:00:00" }), json!({ "datetime": "2023-08-01 07:30:00" }))]
+    #[case(json!({ "timestamp": 1690875000 }), json!({ "timestamp": 1690875000 }))]
+    fn set_datetime_returns_service_data(#[case] params: Value, #[case] expected: Value) {
+        let cmd = new_entity_command(params);
+        let result = handle_input_datetime(&cmd);
+
+        assert_eq!(Ok(("set_datetime".to_string(), Some(expected))), result);
+    }
+
+    #[rstest]
+    #[case(Value::Null)]
+    #[case(json!({}))]
+    #[case(json!({ "time": 730 }))]
+    fn set_datetime_with_invalid_params_returns_bad_request(#[case] params: Value) {
+        let cmd = new_entity_command(params);
+        let result = handle_input_datetime(&cmd);
+
+        assert!(
+            matches!(result, Err(ServiceError::BadRequest(_))),
+            "Invalid value must return BadRequest, but got: {:?}",
+            result
+        );
+    }
+}
@@ -3,49 +3,396 @@
 
 //! Light entity specific HA service call logic.
 
+use crate::client::entity::{
+    light_brightness_factor, light_color_temp_kelvin_range, light_features, light_kelvin_range,
+    light_mired_range, light_supports, LIGHT_SUPPORT_EFFECT, LIGHT_SUPPORT_FLASH,
+    LIGHT_SUPPORT_TRANSITION,
+};
+use crate::client::model::EventState;
 use crate::client::service::cmd_from_str;
+use crate::configuration::LightSettings;
 use crate::errors::ServiceError;
+use crate::util::color;
 use serde_json::{json, Map, Value};
+use std::str::FromStr;
+use strum_macros::{EnumString, EnumVariantNames};
 use uc_api::intg::EntityCommand;
-use uc_api::LightCommand;
+use uc_api::{LightCommand, LightFeature};
+
+/// Default step size in percent of the light step commands.
+const DEFAULT_STEP_PCT: u64 = 10;
+
+/// Light step commands for relative brightness and color temperature changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumString, EnumVariantNames)]
+#[strum(serialize_all = "snake_case")]
+pub enum LightStepCommand {
+    BrightnessUp,
+    BrightnessDown,
+    ColorTempWarmer,
+    ColorTempCooler,
+}
+
+/// Light effect commands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumString, EnumVariantNames)]
+#[strum(serialize_all = "snake_case")]
+pub enum LightEffectCommand {
+    Effect,
+    Flash,
+}
+
+/// Map a light command to a HA `turn_on`, `turn_off` or `toggle` service call.
+///
+/// A color is sent in the color mode supported by the light. An optional `transition` parameter
+/// in seconds is passed through to HA, otherwise the configured default transition is used.
+///
+/// # Arguments
+///
+/// * `msg`: Entity command of a light entity.
+/// * `state`: Last known entity state including the supported color modes.
+/// * `settings`: Optional light settings from the configuration file.
+///
+/// returns: Result<(String, Option<Value>), ServiceError>
+pub(crate) fn handle_light(
+    msg: &EntityCommand,
+    state: Option<&EventState>,
+    settings: Option<&LightSettings>,
+) -> Result<(String, Option<Value>), ServiceError> {
+    if let Ok(cmd) = LightStepCommand::from_str(&msg.cmd_id) {
+        return handle_light_step(cmd, msg, state, settings);
+    }
+    if let Ok(cmd) = LightEffectCommand::from_str(&msg.cmd_id) {
+        return handle_light_effect(cmd, msg, state);
+    }
 
-pub(crate) fn handle_light(msg: &EntityCommand) -> Result<(String, Option<Value>), ServiceError> {
     let cmd: LightCommand = cmd_from_str(&msg.cmd_id)?;
 
-    let result = match cmd {
+    let mut data = Map::new();
+    let service = match cmd {
         LightCommand::On => {
-            let mut data = Map::new();
             if let Some(params) = msg.params.as_ref() {
                 if let Some(brightness @ 0..=255) =
                     params.get("brightness").and_then(|v| v.as_u64())
                 {
+                    // a new color replaces the brightness information of the current color
+                    let changes_color =
+                        params.contains_key("hue") || params.contains_key("color_temperature");
+                    let brightness = match state.and_then(|s| s.attributes.as_ref()) {
+                        Some(ha_attr) if !changes_color => brightness_to_ha(brightness, ha_attr),
+                        _ => brightness,
+                    };
                     data.insert("brightness".into(), Value::Number(brightness.into()));
                 }
                 if let Some(color_temp_pct) =
                     params.get("color_temperature").and_then(|v| v.as_u64())
                 {
-                    // TODO keep an inventory of mired range per light #9
-                    let min_mireds = 150;
-                    let max_mireds = 500;
-                    let color_temp =
-                        color_temp_percent_to_mired(color_temp_pct, min_mireds, max_mireds)?;
-                    data.insert("color_temp".into(), Value::Number(color_temp.into()));
+                    let ha_attr = state.and_then(|s| s.attributes.as_ref());
+                    let (key, color_temp) = light_color_temp(color_temp_pct, ha_attr)?;
+                    data.insert(key.into(), Value::Number(color_temp.into()));
                 }
                 if let Some(hue @ 0..=360) = params.get("hue").and_then(|v| v.as_u64()) {
                     if let Some(saturation @ 0..=255) =
                         params.get("saturation").and_then(|v| v.as_u64())
                     {
-                        data.insert("hs_color".into(), json!([hue, saturation * 100 / 255]));
+                        let ha_attr = state.and_then(|s| s.attributes.as_ref());
+                        let (key, color) =
+                            light_color(hue as f64, saturation as f64 * 100.0 / 255.0, ha_attr);
+                        data.insert(key.into(), color);
                     }
                 }
             }
-            ("turn_on".into(), Some(Value::Object(data)))
+            "turn_on"
         }
-        LightCommand::Off => ("turn_off".into(), None),
-        LightCommand::Toggle => ("Toggle".into(), None),
+        LightCommand::Off => "turn_off",
+        LightCommand::Toggle => "toggle",
+    };
+    insert_transition(msg, state, settings, &mut data);
+
+    let data = match (cmd, data.is_empty()) {
+        (LightCommand::On, _) | (_, false) => Some(Value::Object(data)),
+        _ => None,
+    };
+    Ok((service.into(), data))
+}
+
+/// Convert the remote brightness to the HA brightness property.
+///
+/// Inverse of the brightness correction in the color modes `rgb`, `rgbw` and `rgbww`, where the
+/// brightness of the color is included in the remote brightness.
+fn brightness_to_ha(brightness: u64, ha_attr: &Map<String, Value>) -> u64 {
+    let factor = light_brightness_factor(ha_attr);
+    if factor <= 0.0 {
+        return brightness;
+    }
+    ((brightness as f64 / factor).round() as u64).min(255)
+}
+
+/// Map a light step command to a HA `turn_on` service call with a relative brightness or an
+/// absolute color temperature calculated from the current state.
+///
+/// The optional `step` parameter defines the step size in percent, default: 10%.
+fn handle_light_step(
+    cmd: LightStepCommand,
+    msg: &EntityCommand,
+    state: Option<&EventState>,
+    settings: Option<&LightSettings>,
+) -> Result<(String, Option<Value>), ServiceError> {
+    let step = match msg.params.as_ref().and_then(|p| p.get("step")) {
+        None => DEFAULT_STEP_PCT,
+        Some(step) => match step.as_u64() {
+            Some(step @ 1..=100) => step,
+            _ => {
+                return Err(ServiceError::BadRequest(format!(
+                    "Invalid step value {step}: Valid: 1..100"
+                )))
+            }
+        },
     };
+    let ha_attr = state.and_then(|s| s.attributes.as_ref());
+    let features = ha_attr.map(light_features).unwrap_or_default();
+
+    let mut data = Map::new();
+    match cmd {
+        LightStepCommand::BrightnessUp | LightStepCommand::BrightnessDown => {
+            if !features.iter().any(|f| matches!(f, LightFeature::Dim)) {
+                return Err(ServiceError::BadRequest(
+                    "Light doesn't support brightness".into(),
+                ));
+            }
+            let step = match cmd {
+                LightStepCommand::BrightnessDown => -(step as i64),
+                _ => step as i64,
+            };
+            data.insert("brightness_step_pct".into(), step.into());
+        }
+        LightStepCommand::ColorTempWarmer | LightStepCommand::ColorTempCooler => {
+            let ha_attr = match ha_attr {
+                Some(ha_attr)
+                    if features
+                        .iter()
+                        .any(|f| matches!(f, LightFeature::ColorTemperature)) =>
+                {
+                    ha_attr
+                }
+                _ => {
+                    return Err(ServiceError::BadRequest(
+                        "Light doesn't support color temperature".into(),
+                    ))
+                }
+            };
+            let warmer = cmd == LightStepCommand::ColorTempWarmer;
+            let (key, color_temp) = light_color_temp_step(step, warmer, ha_attr);
+            data.insert(key.into(), color_temp.into());
+        }
+    }
+    insert_transition(msg, state, settings, &mut data);
+
+    Ok(("turn_on".into(), Some(Value::Object(data))))
+}
+
+/// Map a light effect command to a HA `turn_on` service call with an effect or flash.
+///
+/// - `effect`: required `effect` parameter, validated against the effect list of the light.
+/// - `flash`: optional `flash` parameter: `short` (default) or `long`.
+fn handle_light_effect(
+    cmd: LightEffectCommand,
+    msg: &EntityCommand,
+    state: Option<&EventState>,
+) -> Result<(String, Option<Value>), ServiceError> {
+    let ha_attr = state.and_then(|s| s.attributes.as_ref());
+    let params = msg.params.as_ref();
+
+    let mut data = Map::new();
+    match cmd {
+        LightEffectCommand::Effect => {
+            let effect = match params
+                .and_then(|p| p.get("effect"))
+                .and_then(|v| v.as_str())
+            {
+                Some(effect) if !effect.is_empty() => effect,
+                _ => return Err(ServiceError::BadRequest("Missing parameter: effect".into())),
+            };
+            if let Some(ha_attr) = ha_attr {
+                let effect_list = ha_attr.get("effect_list").and_then(|v| v.as_array());
+                if !light_supports(ha_attr, LIGHT_SUPPORT_EFFECT)
+                    || !effect_list
+                        .map(|list| list.iter().any(|v| v.as_str() == Some(effect)))
+                        .unwrap_or_default()
+                {
+                    return Err(ServiceError::BadRequest(format!(
+                        "Effect not supported by light: {effect}"
+                    )));
+                }
+            }
+            data.insert("effect".into(), effect.into());
+        }
+        LightEffectCommand::Flash => {
+            if ha_attr
+                .map(|attr| !light_supports(attr, LIGHT_SUPPORT_FLASH))
+                .unwrap_or_default()
+            {
+                return Err(ServiceError::BadRequest(
+                    "Light doesn't support flash".into(),
+                ));
+            }
+            let flash = match params.and_then(|p| p.get("flash")) {
+                None => "short",
+                Some(flash) => match flash.as_str() {
+                    Some(flash @ ("short" | "long")) => flash,
+                    _ => {
+                        return Err(ServiceError::BadRequest(format!(
+                            "Invalid flash value {flash}: Valid: short, long"
+                        )))
+                    }
+                },
+            };
+            data.insert("flash".into(), flash.into());
+        }
+    }
 
-    Ok(result)
+    Ok(("turn_on".into(), Some(Value::Object(data))))
+}
+
+/// Pass through the optional `transition` parameter in seconds.
+///
+/// If the parameter is missing, the configured default transition is used if the light supports
+/// transitions.
+fn insert_transition(
+    msg: &EntityCommand,
+    state: Option<&EventState>,
+    settings: Option<&LightSettings>,
+    data: &mut Map<String, Value>,
+) {
+    let transition = msg
+        .params
+        .as_ref()
+        .and_then(|p| p.get("transition"))
+        .and_then(|v| v.as_f64())
+        .or_else(|| {
+            settings.and_then(|s| s.transition).filter(|_| {
+                state
+                    .and_then(|s| s.attributes.as_ref())
+                    .map(|attr| light_supports(attr, LIGHT_SUPPORT_TRANSITION))
+                    .unwrap_or(true)
+            })
+        });
+    if let Some(transition) = transition.filter(|v| *v >= 0.0) {
+        data.insert("transition".into(), transition.into());
+    }
+}
+
+/// Calculate the new color temperature of a step command from the current color temperature.
+///
+/// The middle of the color temperature range is used if the current color temperature is not
+/// known, e.g. if the light is off or in a color mode.
+fn light_color_temp_step(
+    step_pct: u64,
+    warmer: bool,
+    ha_attr: &Map<String, Value>,
+) -> (&'static str, u16) {
+    let current = |key: &str| ha_attr.get(key).and_then(|v| v.as_u64()).map(|v| v as u32);
+
+    if let Some((min_kelvin, max_kelvin)) = light_color_temp_kelvin_range(ha_attr) {
+        let (min, max) = (min_kelvin as u32, max_kelvin as u32);
+        let value = current("color_temp_kelvin").unwrap_or((min + max) / 2);
+        let delta = (max - min) * step_pct as u32 / 100;
+        // warmer is a lower Kelvin value
+        let value = match warmer {
+            true => value.saturating_sub(delta),
+            false => value + delta,
+        };
+        return ("color_temp_kelvin", value.clamp(min, max) as u16);
+    }
+
+    let (min_mireds, max_mireds) = light_mired_range(ha_attr);
+    let (min, max) = (min_mireds as u32, max_mireds as u32);
+    let value = current("color_temp").unwrap_or((min + max) / 2);
+    let delta = (max - min) * step_pct as u32 / 100;
+    // warmer is a higher mired value
+    let value = match warmer {
+        true => value + delta,
+        false => value.saturating_sub(delta),
+    };
+    ("color_temp", value.clamp(min, max) as u16)
+}
+
+/// Convert the hue (0..360) and saturation (0..100) color to the color mode supported by the
+/// light.
+///
+/// The `hs` color mode is used if supported or if the supported color modes are unknown.
+fn light_color(
+    hue: f64,
+    saturation: f64,
+    ha_attr: Option<&Map<String, Value>>,
+) -> (&'static str, Value) {
+    let color_modes: Vec<&str> = ha_attr
+        .and_then(|attr| attr.get("supported_color_modes"))
+        .and_then(|v| v.as_array())
+        .map(|modes| modes.iter().filter_map(|v| v.as_str()).collect())
+        .unwrap_or_default();
+    let supports = |mode: &str| color_modes.contains(&mode);
+
+    if color_modes.is_empty() || supports("hs") {
+        ("hs_color", json!([hue, saturation]))
+    } else if supports("xy") {
+        let (x, y) = color::hs_to_xy(hue, saturation);
+        ("xy_color", json!([x, y]))
+    } else if supports("rgbww") {
+        let (r, g, b) = color::hs_to_rgb(hue, saturation);
+        let (min_kelvin, max_kelvin) = ha_attr
+            .map(light_kelvin_range)
+            .unwrap_or((color::DEFAULT_MIN_KELVIN, color::DEFAULT_MAX_KELVIN));
+        let (r, g, b, cw, ww) = color::rgb_to_rgbww(r, g, b, min_kelvin, max_kelvin);
+        ("rgbww_color", json!([r, g, b, cw, ww]))
+    } else if supports("rgbw") {
+        let (r, g, b) = color::hs_to_rgb(hue, saturation);
+        let (r, g, b, w) = color::rgb_to_rgbw(r, g, b);
+        ("rgbw_color", json!([r, g, b, w]))
+    } else if supports("rgb") {
+        let (r, g, b) = color::hs_to_rgb(hue, saturation);
+        ("rgb_color", json!([r, g, b]))
+    } else {
+        ("hs_color", json!([hue, saturation]))
+    }
+}
+
+/// Convert the remote color temperature percentage to the color temperature range of the light.
+///
+/// Kelvin values are used if the light provides a Kelvin range, otherwise mireds.
+fn light_color_temp(
+    color_temp_pct: u64,
+    ha_attr: Option<&Map<String, Value>>,
+) -> Result<(&'static str, u16), ServiceError> {
+    if let Some((min_kelvin, max_kelvin)) = ha_attr.and_then(light_color_temp_kelvin_range) {
+        let kelvin = color_temp_percent_to_kelvin(color_temp_pct, min_kelvin, max_kelvin)?;
+        return Ok(("color_temp_kelvin", kelvin));
+    }
+
+    let (min_mireds, max_mireds) = ha_attr
+        .map(light_mired_range)
+        .unwrap_or((color::DEFAULT_MIN_MIREDS, color::DEFAULT_MAX_MIREDS));
+    let color_temp = color_temp_percent_to_mired(color_temp_pct, min_mireds, max_mireds)?;
+    Ok(("color_temp", color_temp))
+}
+
+fn color_temp_percent_to_kelvin(
+    value: u64,
+    min_kelvin: u16,
+    max_kelvin: u16,
+) -> Result<u16, ServiceError> {
+    if max_kelvin <= min_kelvin {
+        return Err(ServiceError::BadRequest(format!(
+            "Invalid min_color_temp_kelvin or max_color_temp_kelvin value! min={}, max={}",
+            min_kelvin, max_kelvin
+        )));
+    }
+    if value <= 100 {
+        Ok(max_kelvin - (value as u32 * (max_kelvin - min_kelvin) as u32 / 100) as u16)
+    } else {
+        Err(ServiceError::BadRequest(format!(
+            "Invalid color_temperature value {}: Valid: 0..100",
+            value
+        )))
+    }
 }
 
 fn color_temp_percent_to_mired(
@@ -60,7 +407,7 @@ fn color_temp_percent_to_mired(
         )));
     }
     if value <= 100 {
-        Ok(value as u16 * (max_mireds - min_mireds) / 100 + min_mireds)
+        Ok((value as u32 * (max_mireds - min_mireds) as u32 / 100) as u16 + min_mireds)
     } else {
         Err(ServiceError::BadRequest(format!(
             "Invalid color_temperature value {}: Valid: 0..100",
@@ -71,9 +418,310 @@ fn color_temp_percent_to_mired(
 
 #[cfg(test)]
 mod tests {
-    use crate::client::service::light::color_temp_percent_to_mired;
+    use crate::client::entity::map_light_attributes;
+    use crate::client::model::EventState;
+    use crate::client::service::light::{
+        color_temp_percent_to_kelvin, color_temp_percent_to_mired, handle_light, light_color,
+        light_color_temp,
+    };
+    use crate::configuration::LightSettings;
     use crate::errors::ServiceError;
     use rstest::rstest;
+    use serde_json::{json, Value};
+    use uc_api::intg::EntityCommand;
+    use uc_api::EntityType;
+
+    fn new_entity_command(cmd_id: impl Into<String>, params: Value) -> EntityCommand {
+        EntityCommand {
+            device_id: None,
+            entity_type: EntityType::Light,
+            entity_id: "light.living_room".into(),
+            cmd_id: cmd_id.into(),
+            params: params.as_object().cloned(),
+        }
+    }
+
+    fn new_state(attributes: Value) -> EventState {
+        EventState {
+            state: "on".into(),
+            attributes: attributes.as_object().cloned(),
+        }
+    }
+
+    #[rstest]
+    #[case("on", Value::Null, "turn_on", Some(json!({})))]
+    #[case("off", Value::Null, "turn_off", None)]
+    #[case("toggle", Value::Null, "toggle", None)]
+    #[case("on", json!({ "brightness": 128, "transition": 2.5 }), "turn_on", Some(json!({ "brightness": 128, "transition": 2.5 })))]
+    #[case("off", json!({ "transition": 5 }), "turn_off", Some(json!({ "transition": 5.0 })))]
+    #[case("toggle", json!({ "transition": 1 }), "toggle", Some(json!({ "transition": 1.0 })))]
+    fn light_cmd_returns_service_with_transition(
+        #[case] cmd_id: &str,
+        #[case] params: Value,
+        #[case] service: &str,
+        #[case] expected: Option<Value>,
+    ) {
+        let cmd = new_entity_command(cmd_id, params);
+        let result = handle_light(&cmd, None, None);
+
+        assert_eq!(Ok((service.to_string(), expected)), result);
+    }
+
+    #[rstest]
+    #[case("on", json!({}), Some(json!({ "transition": 1.5 })))]
+    #[case("off", json!({}), Some(json!({ "transition": 1.5 })))]
+    #[case("off", json!({ "transition": 0 }), Some(json!({ "transition": 0.0 })))]
+    fn light_cmd_uses_default_transition(
+        #[case] cmd_id: &str,
+        #[case] params: Value,
+        #[case] expected: Option<Value>,
+    ) {
+        let cmd = new_entity_command(cmd_id, params);
+        let state = new_state(json!({ "supported_features": 32 }));
+        let settings = LightSettings {
+            transition: Some(1.5),
+        };
+        let result = handle_light(&cmd, Some(&state), Some(&settings));
+
+        assert!(
+            matches!(result, Ok((_, ref data)) if data == &expected),
+            "{result:?}"
+        );
+    }
+
+    #[test]
+    fn default_transition_requires_transition_support() {
+        let cmd = new_entity_command("off", Value::Null);
+        let state = new_state(json!({ "supported_features": 4 }));
+        let settings = LightSettings {
+            transition: Some(1.5),
+        };
+        let result = handle_light(&cmd, Some(&state), Some(&settings));
+
+        assert_eq!(Ok(("turn_off".to_string(), None)), result);
+    }
+
+    #[rstest]
+    #[case(json!({ "color_mode": "rgb", "rgb_color": [192, 64, 32] }), 128, 96)]
+    #[case(json!({ "color_mode": "rgbw", "rgbw_color": [64, 32, 16, 128] }), 255, 128)]
+    #[case(json!({ "color_mode": "rgbww", "rgbww_color": [255, 64, 32, 0, 0] }), 77, 77)]
+    #[case(json!({ "color_mode": "hs", "hs_color": [30, 50] }), 128, 128)]
+    fn brightness_cmd_is_inverse_of_state_brightness(
+        #[case] ha_attr: Value,
+        #[case] ha_brightness: u64,
+        #[case] brightness: u64,
+    ) {
+        let mut ha_attr = ha_attr.as_object().cloned().unwrap();
+        ha_attr.insert("brightness".into(), ha_brightness.into());
+        let state = new_state(Value::Object(ha_attr.clone()));
+
+        let attributes = map_light_attributes("light.test", "on", Some(&mut ha_attr)).unwrap();
+        assert_eq!(Some(&json!(brightness)), attributes.get("brightness"));
+
+        let cmd = new_entity_command("on", json!({ "brightness": brightness }));
+        let result = handle_light(&cmd, Some(&state), None);
+        assert_eq!(
+            Ok((
+                "turn_on".to_string(),
+                Some(json!({ "brightness": ha_brightness }))
+            )),
+            result
+        );
+    }
+
+    #[rstest]
+    #[case(json!({ "brightness": 128, "hue": 120, "saturation": 255 }))]
+    #[case(json!({ "brightness": 128, "color_temperature": 50 }))]
+    fn brightness_cmd_with_new_color_ignores_current_color(#[case] params: Value) {
+        let cmd = new_entity_command("on", params);
+        let state = new_state(json!({
+            "color_mode": "rgb",
+            "rgb_color": [128, 0, 0],
+            "supported_color_modes": ["rgb", "color_temp"]
+        }));
+        let result = handle_light(&cmd, Some(&state), None);
+
+        assert!(
+            matches!(result, Ok((_, Some(ref data))) if data.get("brightness") == Some(&json!(128))),
+            "{result:?}"
+        );
+    }
+
+    #[test]
+    fn brightness_cmd_in_rgb_mode_is_limited() {
+        let cmd = new_entity_command("on", json!({ "brightness": 255 }));
+        let state = new_state(json!({ "color_mode": "rgb", "rgb_color": [128, 0, 0] }));
+        let result = handle_light(&cmd, Some(&state), None);
+
+        assert_eq!(
+            Ok(("turn_on".to_string(), Some(json!({ "brightness": 255 })))),
+            result
+        );
+    }
+
+    #[rstest]
+    #[case("effect", json!({ "effect": "Rainbow" }), json!({ "effect": "Rainbow" }))]
+    #[case("flash", Value::Null, json!({ "flash": "short" }))]
+    #[case("flash", json!({ "flash": "long" }), json!({ "flash": "long" }))]
+    fn effect_cmd_returns_turn_on(
+        #[case] cmd_id: &str,
+        #[case] params: Value,
+        #[case] expected: Value,
+    ) {
+        let cmd = new_entity_command(cmd_id, params);
+        let state =
+            new_state(json!({ "supported_features": 44, "effect_list": ["Rainbow", "Fire"] }));
+        let result = handle_light(&cmd, Some(&state), None);
+
+        assert_eq!(Ok(("turn_on".to_string(), Some(expected))), result);
+    }
+
+    #[rstest]
+    #[case("effect", json!({ "effect": "Disco" }), json!({ "supported_features": 44, "effect_list": ["Rainbow"] }))]
+    #[case("effect", json!({ "effect": "Rainbow" }), json!({ "supported_features": 40, "effect_list": ["Rainbow"] }))]
+    #[case("effect", Value::Null, json!({ "supported_features": 44 }))]
+    #[case("flash", Value::Null, json!({ "supported_features": 4 }))]
+    #[case("flash", json!({ "flash": "medium" }), json!({ "supported_features": 8 }))]
+    fn unsupported_effect_returns_bad_request(
+        #[case] cmd_id: &str,
+        #[case] params: Value,
+        #[case] attr: Value,
+    ) {
+        let cmd = new_entity_command(cmd_id, params);
+        let state = new_state(attr);
+        let result = handle_light(&cmd, Some(&state), None);
+
+        assert!(
+            matches!(result, Err(ServiceError::BadRequest(_))),
+            "Unsupported effect command must return BadRequest, but got: {:?}",
+            result
+        );
+    }
+
+    #[rstest]
+    #[case("brightness_up", Value::Null, json!({ "brightness_step_pct": 10 }))]
+    #[case("brightness_down", Value::Null, json!({ "brightness_step_pct": -10 }))]
+    #[case("brightness_up", json!({ "step": 25, "transition": 1 }), json!({ "brightness_step_pct": 25, "transition": 1.0 }))]
+    fn brightness_step_returns_brightness_step_pct(
+        #[case] cmd_id: &str,
+        #[case] params: Value,
+        #[case] expected: Value,
+    ) {
+        let cmd = new_entity_command(cmd_id, params);
+        let state = new_state(json!({ "supported_color_modes": ["brightness"] }));
+        let result = handle_light(&cmd, Some(&state), None);
+
+        assert_eq!(Ok(("turn_on".to_string(), Some(expected))), result);
+    }
+
+    #[rstest]
+    #[case("color_temp_warmer", json!({ "color_temp_kelvin": 4000 }), json!({ "color_temp_kelvin": 3550 }))]
+    #[case("color_temp_cooler", json!({ "color_temp_kelvin": 4000 }), json!({ "color_temp_kelvin": 4450 }))]
+    #[case("color_temp_warmer", json!({ "color_temp_kelvin": 2100 }), json!({ "color_temp_kelvin": 2000 }))]
+    #[case("color_temp_cooler", json!({ "color_temp_kelvin": 6400 }), json!({ "color_temp_kelvin": 6500 }))]
+    #[case("color_temp_warmer", json!({}), json!({ "color_temp_kelvin": 3800 }))]
+    fn color_temp_step_uses_current_kelvin(
+        #[case] cmd_id: &str,
+        #[case] current: Value,
+        #[case] expected: Value,
+    ) {
+        let cmd = new_entity_command(cmd_id, Value::Null);
+        let mut attr = json!({
+            "supported_color_modes": ["color_temp"],
+            "min_color_temp_kelvin": 2000,
+            "max_color_temp_kelvin": 6500
+        });
+        attr.as_object_mut()
+            .unwrap()
+            .extend(current.as_object().cloned().unwrap());
+        let state = new_state(attr);
+        let result = handle_light(&cmd, Some(&state), None);
+
+        assert_eq!(Ok(("turn_on".to_string(), Some(expected))), result);
+    }
+
+    #[rstest]
+    #[case("color_temp_warmer", json!({ "color_temp": 300 }))]
+    #[case("color_temp_cooler", json!({ "color_temp": 200 }))]
+    fn color_temp_step_uses_current_mireds(#[case] cmd_id: &str, #[case] expected: Value) {
+        let cmd = new_entity_command(cmd_id, json!({ "step": 20 }));
+        let state = new_state(json!({
+            "supported_color_modes": ["color_temp"],
+            "color_temp": 250,
+            "min_mireds": 150,
+            "max_mireds": 400
+        }));
+        let result = handle_light(&cmd, Some(&state), None);
+
+        assert_eq!(Ok(("turn_on".to_string(), Some(expected))), result);
+    }
+
+    #[rstest]
+    #[case("brightness_up", json!({ "supported_color_modes": ["onoff"] }), Value::Null)]
+    #[case("color_temp_warmer", json!({ "supported_color_modes": ["hs"] }), Value::Null)]
+    #[case("brightness_down", json!({ "supported_color_modes": ["brightness"] }), json!({ "step": 0 }))]
+    #[case("brightness_down", json!({ "supported_color_modes": ["brightness"] }), json!({ "step": 101 }))]
+    fn unsupported_step_returns_bad_request(
+        #[case] cmd_id: &str,
+        #[case] attr: Value,
+        #[case] params: Value,
+    ) {
+        let cmd = new_entity_command(cmd_id, params);
+        let state = new_state(attr);
+        let result = handle_light(&cmd, Some(&state), None);
+
+        assert!(
+            matches!(result, Err(ServiceError::BadRequest(_))),
+            "Unsupported step command must return BadRequest, but got: {:?}",
+            result
+        );
+    }
+
+    #[rstest]
+    #[case(json!({}), "color_temp", 326)]
+    #[case(json!({ "min_mireds": 250, "max_mireds": 454 }), "color_temp", 352)]
+    #[case(json!({ "min_mireds": 250, "max_mireds": 454, "min_color_temp_kelvin": 2202, "max_color_temp_kelvin": 4000 }), "color_temp_kelvin", 3101)]
+    fn light_color_temp_uses_entity_range(
+        #[case] attr: Value,
+        #[case] expected_key: &str,
+        #[case] expected: u16,
+    ) {
+        let result = light_color_temp(50, attr.as_object());
+
+        assert_eq!(Ok((expected_key, expected)), result);
+    }
+
+    #[rstest]
+    #[case(0, 6500)]
+    #[case(50, 4250)]
+    #[case(100, 2000)]
+    fn color_temp_percent_to_kelvin_returns_scaled_values(
+        #[case] input: u64,
+        #[case] expected: u16,
+    ) {
+        assert_eq!(
+            Ok(expected),
+            color_temp_percent_to_kelvin(input, 2000, 6500)
+        );
+    }
+
+    #[rstest]
+    #[case(json!({}), "hs_color", json!([30.0, 100.0]))]
+    #[case(json!({ "supported_color_modes": ["hs", "color_temp"] }), "hs_color", json!([30.0, 100.0]))]
+    #[case(json!({ "supported_color_modes": ["xy"] }), "xy_color", json!([0.611, 0.375]))]
+    #[case(json!({ "supported_color_modes": ["rgb"] }), "rgb_color", json!([255, 128, 0]))]
+    #[case(json!({ "supported_color_modes": ["rgbw"] }), "rgbw_color", json!([255, 128, 0, 0]))]
+    #[case(json!({ "supported_color_modes": ["rgbww"] }), "rgbww_color", json!([255, 128, 0, 0, 0]))]
+    fn light_color_uses_supported_color_mode(
+        #[case] attr: Value,
+        #[case] expected_key: &str,
+        #[case] expected: Value,
+    ) {
+        let (key, color) = light_color(30.0, 100.0, attr.as_object());
+
+        assert_eq!(expected_key, key);
+        assert_eq!(expected, color);
+    }
 
     #[test]
     fn color_temp_percent_to_mired_with_invalid_input_returns_err() {
@@ -115,4 +763,16 @@ mod tests {
 
         assert_eq!(Ok(expected), result);
     }
+
+    #[rstest]
+    #[case(0, 153)]
+    #[case(50, 576)]
+    #[case(99, 991)]
+    #[case(100, 1000)]
+    fn color_temp_percent_to_mired_with_wide_range_returns_scaled_values(
+        #[case] input: u64,
+        #[case] expected: u16,
+    ) {
+        assert_eq!(Ok(expected), color_temp_percent_to_mired(input, 153, 1000));
+    }
 }
@@ -3,14 +3,30 @@
 
 //! Media player entity specific HA service call logic.
 
+use crate::client::entity::{media_position_at, SUPPORT_VOLUME_STEP};
+use crate::client::model::EventState;
 use crate::client::service::{cmd_from_str, get_required_params};
+use crate::configuration::MediaPlayerSettings;
 use crate::errors::ServiceError;
 use serde_json::{json, Map, Value};
+use time::OffsetDateTime;
 use uc_api::intg::EntityCommand;
 use uc_api::MediaPlayerCommand;
 
-pub fn handle_media_player(msg: &EntityCommand) -> Result<(String, Option<Value>), ServiceError> {
+/// Default volume step in percent if volume steps are emulated.
+const DEFAULT_VOLUME_STEP: u8 = 10;
+/// Default seek step in seconds for fast-forward and rewind.
+const DEFAULT_SEEK_STEP: u32 = 10;
+
+/// Mute toggle, fast-forward, rewind and volume steps are emulated with the cached entity `state`,
+/// if the media player doesn't support them.
+pub fn handle_media_player(
+    msg: &EntityCommand,
+    state: Option<&EventState>,
+    settings: Option<&MediaPlayerSettings>,
+) -> Result<(String, Option<Value>), ServiceError> {
     let cmd: MediaPlayerCommand = cmd_from_str(&msg.cmd_id)?;
+    let ha_attr = state.and_then(|s| s.attributes.as_ref());
 
     let result = match cmd {
         MediaPlayerCommand::On => ("turn_on".into(), None),
@@ -45,12 +61,19 @@ pub fn handle_media_player(msg: &EntityCommand) -> Result<(String, Option<Value>
             }
             ("volume_set".into(), Some(data.into()))
         }
-        MediaPlayerCommand::VolumeUp => ("volume_up".into(), None),
-        MediaPlayerCommand::VolumeDown => ("volume_down".into(), None),
-        MediaPlayerCommand::FastForward
-        | MediaPlayerCommand::Rewind
-        | MediaPlayerCommand::MuteToggle => {
-            return Err(ServiceError::BadRequest("Not supported".into()))
+        MediaPlayerCommand::VolumeUp => volume_step(true, ha_attr, settings)?,
+        MediaPlayerCommand::VolumeDown => volume_step(false, ha_attr, settings)?,
+        MediaPlayerCommand::FastForward => seek_step(true, state, settings)?,
+        MediaPlayerCommand::Rewind => seek_step(false, state, settings)?,
+        MediaPlayerCommand::MuteToggle => {
+            let muted = ha_attr
+                .and_then(|attr| attr.get("is_volume_muted"))
+                .and_then(|v| v.as_bool())
+                .unwrap_or_default();
+            (
+                "volume_mute".into(),
+                Some(json!({ "is_volume_muted": !muted })),
+            )
         }
         MediaPlayerCommand::Mute => (
             "volume_mute".into(),
@@ -84,8 +107,7 @@ pub fn handle_media_player(msg: &EntityCommand) -> Result<(String, Option<Value>
             }
             ("shuffle_set".into(), Some(data.into()))
         }
-        // TODO can we find out related HA entities and forward the command to these? Would we very convenient for the user!
-        // E.g. the remote entity which usually comes with a media-player entity as for ATV or LG TV
+        // sent to the associated remote entity, see navigation module
         MediaPlayerCommand::ChannelUp
         | MediaPlayerCommand::ChannelDown
         | MediaPlayerCommand::CursorUp
@@ -129,9 +151,76 @@ pub fn handle_media_player(msg: &EntityCommand) -> Result<(String, Option<Value>
     Ok(result)
 }
 
+/// Use the volume step of the media player, or emulate it with `volume_set` if volume steps are
+/// not supported or a step size is configured.
+fn volume_step(
+    up: bool,
+    ha_attr: Option<&Map<String, Value>>,
+    settings: Option<&MediaPlayerSettings>,
+) -> Result<(String, Option<Value>), ServiceError> {
+    let step = settings.and_then(|s| s.volume_step);
+    let supports_step = ha_attr
+        .and_then(|attr| attr.get("supported_features"))
+        .and_then(|v| v.as_u64())
+        .map(|v| v as u32 & SUPPORT_VOLUME_STEP > 0)
+        .unwrap_or(true);
+    if step.is_none() && supports_step {
+        let service = if up { "volume_up" } else { "volume_down" };
+        return Ok((service.into(), None));
+    }
+
+    let volume = ha_attr
+        .and_then(|attr| attr.get("volume_level"))
+        .and_then(|v| v.as_f64())
+        .ok_or_else(|| ServiceError::BadRequest("Current volume not available".into()))?;
+    let step = step.unwrap_or(DEFAULT_VOLUME_STEP) as f64 / 100.0;
+    let volume = if up { volume + step } else { volume - step }.clamp(0.0, 1.0);
+
+    Ok((
+        "volume_set".into(),
+        Some(json!({ "volume_level": (volume * 100.0).round() / 100.0 })),
+    ))
+}
+
+/// Emulate fast-forward and rewind with a relative `media_seek` from the current position.
+fn seek_step(
+    forward: bool,
+    state: Option<&EventState>,
+    settings: Option<&MediaPlayerSettings>,
+) -> Result<(String, Option<Value>), ServiceError> {
+    let ha_attr = state.and_then(|s| s.attributes.as_ref());
+    let position = state
+        .zip(ha_attr)
+        .and_then(|(s, attr)| media_position_at(&s.state, attr, OffsetDateTime::now_utc()))
+        .ok_or_else(|| ServiceError::BadRequest("Current media position not available".into()))?;
+    let step = settings
+        .and_then(|s| s.seek_step)
+        .unwrap_or(DEFAULT_SEEK_STEP) as f64;
+
+    let mut position = if forward {
+        position + step
+    } else {
+        position - step
+    };
+    if let Some(duration) = ha_attr
+        .and_then(|attr| attr.get("media_duration"))
+        .and_then(|v| v.as_f64())
+        .filter(|v| *v > 0.0)
+    {
+        position = position.min(duration);
+    }
+
+    Ok((
+        "media_seek".into(),
+        Some(json!({ "seek_position": position.max(0.0).round() as u64 })),
+    ))
+}
+
 #[cfg(test)]
 mod tests {
+    use crate::client::model::EventState;
     use crate::client::service::media_player::handle_media_player;
+    use crate::configuration::MediaPlayerSettings;
     use crate::errors::ServiceError;
     use rstest::rstest;
     use serde_json::{json, Map, Value};
@@ -159,7 +248,7 @@ mod tests {
     #[case(json!(100), json!(1.0))]
     fn volume_cmd_returns_proper_request(#[case] volume: Value, #[case] output: Value) {
         let cmd = new_entity_command("volume", json!({ "volume": volume }));
-        let result = handle_media_player(&cmd);
+        let result = handle_media_player(&cmd, None, None);
 
         assert!(
             result.is_ok(),
@@ -182,7 +271,7 @@ mod tests {
     #[case(json!(false))]
     fn volume_cmd_with_invalid_volume_param_returns_bad_request(#[case] volume: Value) {
         let cmd = new_entity_command("volume", json!({ "volume": volume }));
-        let result = handle_media_player(&cmd);
+        let result = handle_media_player(&cmd, None, None);
 
         assert!(
             matches!(result, Err(ServiceError::BadRequest(_))),
@@ -196,7 +285,7 @@ mod tests {
     #[case(Value::Object(Map::new()))]
     fn volume_cmd_with_invalid_param_object_returns_bad_request(#[case] params: Value) {
         let cmd = new_entity_command("volume", params);
-        let result = handle_media_player(&cmd);
+        let result = handle_media_player(&cmd, None, None);
 
         assert!(
             matches!(result, Err(ServiceError::BadRequest(_))),
@@ -204,4 +293,103 @@ mod tests {
             result
         );
     }
+
+    fn new_state(state: &str, attributes: Value) -> EventState {
+        EventState {
+            state: state.into(),
+            attributes: attributes.as_object().cloned(),
+        }
+    }
+
+    #[rstest]
+    #[case(json!({ "is_volume_muted": false }), true)]
+    #[case(json!({ "is_volume_muted": true }), false)]
+    #[case(json!({}), true)]
+    fn mute_toggle_cmd_inverts_mute_state(#[case] attributes: Value, #[case] muted: bool) {
+        let cmd = new_entity_command("mute_toggle", Value::Null);
+        let state = new_state("playing", attributes);
+        let result = handle_media_player(&cmd, Some(&state), None);
+
+        assert_eq!(
+            Ok((
+                "volume_mute".to_string(),
+                Some(json!({ "is_volume_muted": muted }))
+            )),
+            result
+        );
+    }
+
+    #[rstest]
+    #[case("volume_up", json!({ "supported_features": 1024, "volume_level": 0.5 }), None, "volume_up", None)]
+    #[case("volume_down", Value::Null, None, "volume_down", None)]
+    #[case("volume_up", json!({ "supported_features": 4, "volume_level": 0.5 }), None, "volume_set", Some(json!({ "volume_level": 0.6 })))]
+    #[case("volume_down", json!({ "supported_features": 4, "volume_level": 0.05 }), None, "volume_set", Some(json!({ "volume_level": 0.0 })))]
+    #[case("volume_up", json!({ "supported_features": 1028, "volume_level": 0.5 }), Some(5), "volume_set", Some(json!({ "volume_level": 0.55 })))]
+    fn volume_step_cmd_uses_native_or_emulated_step(
+        #[case] cmd_id: &str,
+        #[case] attributes: Value,
+        #[case] volume_step: Option<u8>,
+        #[case] service: &str,
+        #[case] data: Option<Value>,
+    ) {
+        let cmd = new_entity_command(cmd_id, Value::Null);
+        let state = new_state("playing", attributes);
+        let settings = MediaPlayerSettings {
+            volume_step,
+            ..Default::default()
+        };
+        let result = handle_media_player(&cmd, Some(&state), Some(&settings));
+
+        assert_eq!(Ok((service.to_string(), data)), result);
+    }
+
+    #[rstest]
+    #[case("fast_forward", None, 70)]
+    #[case("rewind", None, 50)]
+    #[case("fast_forward", Some(30), 90)]
+    #[case("fast_forward", Some(60), 100)]
+    #[case("rewind", Some(90), 0)]
+    fn seek_step_cmd_seeks_relative_to_position(
+        #[case] cmd_id: &str,
+        #[case] seek_step: Option<u32>,
+        #[case] position: u64,
+    ) {
+        let cmd = new_entity_command(cmd_id, Value::Null);
+        let state = new_state(
+            "paused",
+            json!({ "media_position": 60, "media_duration": 100 }),
+        );
+        let settings = MediaPlayerSettings {
+            seek_step,
+            ..Default::default()
+        };
+        let result = handle_media_player(&cmd, Some(&state), Some(&settings));
+
+        assert_eq!(
+            Ok((
+                "media_seek".to_string(),
+                Some(json!({ "seek_position": position }))
+            )),
+            result
+        );
+    }
+
+    #[rstest]
+    #[case("fast_forward", json!({}))]
+    #[case("rewind", json!({ "media_duration": 100 }))]
+    #[case("volume_up", json!({ "supported_features": 4 }))]
+    fn emulated_cmd_without_state_returns_bad_request(
+        #[case] cmd_id: &str,
+        #[case] attributes: Value,
+    ) {
+        let cmd = new_entity_command(cmd_id, Value::Null);
+        let state = new_state("playing", attributes);
+        let result = handle_media_player(&cmd, Some(&state), None);
+
+        assert!(
+            matches!(result, Err(ServiceError::BadRequest(_))),
+            "Missing state must return BadRequest, but got: {:?}",
+            result
+        );
+    }
 }
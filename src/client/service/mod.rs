@@ -7,21 +7,37 @@
 //! See <https://developers.home-assistant.io/docs/api/websocket/#calling-a-service> for further
 //! information.
 
+use crate::client::entity::group_service_domain;
 use crate::client::messages::CallService;
 use crate::client::model::{CallServiceMsg, Target};
 use crate::client::HomeAssistantClient;
 use crate::errors::ServiceError;
-use actix::Handler;
+use actix::{Context, Handler};
 use log::info;
 use serde_json::{Map, Value};
 use uc_api::intg::EntityCommand;
 use uc_api::EntityType;
 
 mod climate;
+mod counter;
 mod cover;
+mod humidifier;
+mod input_datetime;
 mod light;
 mod media_player;
+mod navigation;
+mod number;
+mod remote;
+mod select;
+mod siren;
 mod switch;
+mod text;
+mod timer;
+mod update;
+mod valve;
+mod water_heater;
+
+pub(crate) use navigation::{navigation_features, paired_remote_id};
 
 impl Handler<CallService> for HomeAssistantClient {
     type Result = Result<(), ServiceError>;
@@ -39,38 +55,141 @@ impl Handler<CallService> for HomeAssistantClient {
     fn handle(&mut self, msg: CallService, ctx: &mut Self::Context) -> Self::Result {
         info!("[{}] Calling service in HomeAssistant", self.id);
 
-        // map Remote Two command name & parameters to HA service name and service_data payload
-        let (service, service_data) = match msg.command.entity_type {
-            EntityType::Button => Ok(("press".to_string(), None)),
-            EntityType::Switch => switch::handle_switch(&msg.command),
-            EntityType::Climate => climate::handle_climate(&msg.command),
-            EntityType::Cover => cover::handle_cover(&msg.command),
-            EntityType::Light => light::handle_light(&msg.command),
-            EntityType::MediaPlayer => media_player::handle_media_player(&msg.command),
-            EntityType::Sensor => Err(ServiceError::BadRequest(
-                "Sensor doesn't support sending commands to! Ignoring call".to_string(),
-            )),
-            EntityType::Activity | EntityType::Macro | EntityType::Remote => {
-                Err(ServiceError::BadRequest(format!(
-                    "{} is an internal remote-core entity",
-                    msg.command.entity_type
-                )))
-            }
-        }?;
         let domain = match msg.command.entity_id.split_once('.') {
             None => return Err(ServiceError::BadRequest("Invalid entity_id format".into())),
             Some((l, _)) => l.to_string(),
         };
 
+        // navigation commands of a media player are sent to the associated remote entity
+        if matches!(msg.command.entity_type, EntityType::MediaPlayer)
+            && navigation::is_navigation_cmd(&msg.command.cmd_id)
+        {
+            let paired_remote = navigation::paired_remote_id(&msg.command.entity_id)
+                .filter(|remote| self.entity_states.contains_key(remote));
+            let call = navigation::handle_media_player_navigation(
+                &msg.command,
+                self.entity_settings
+                    .media_players
+                    .get(&msg.command.entity_id),
+                paired_remote,
+            )?;
+            return self.send_call_service(
+                call.domain.into(),
+                call.service.into(),
+                Some(call.service_data),
+                call.entity_id,
+                ctx,
+            );
+        }
+
+        // map Remote Two command name & parameters to HA service name and service_data payload
+        let (service, service_data) = match msg.command.entity_type {
+            EntityType::Button => match domain.as_str() {
+                "update" => {
+                    let entity_id = update::update_entity_id(&msg.command.entity_id);
+                    update::handle_update_install(
+                        &msg.command,
+                        self.entity_settings.updates.get(entity_id),
+                        self.entity_states.get(entity_id),
+                    )
+                }
+                _ => Ok(("press".to_string(), None)),
+            },
+            EntityType::Switch => match domain.as_str() {
+                "siren" => siren::handle_siren(&msg.command),
+                _ => switch::handle_switch(&msg.command),
+            },
+            EntityType::Climate => match domain.as_str() {
+                "water_heater" => water_heater::handle_water_heater(
+                    &msg.command,
+                    self.entity_states.get(&msg.command.entity_id),
+                    self.temperature_conversion(),
+                ),
+                "humidifier" => humidifier::handle_humidifier(&msg.command),
+                _ => climate::handle_climate(
+                    &msg.command,
+                    self.entity_states.get(&msg.command.entity_id),
+                    self.temperature_conversion(),
+                ),
+            },
+            EntityType::Cover => match domain.as_str() {
+                "valve" => valve::handle_valve(&msg.command),
+                _ => cover::handle_cover(&msg.command),
+            },
+            EntityType::Light => light::handle_light(
+                &msg.command,
+                self.entity_states.get(&msg.command.entity_id),
+                self.entity_settings.lights.get(&msg.command.entity_id),
+            ),
+            EntityType::MediaPlayer => media_player::handle_media_player(
+                &msg.command,
+                self.entity_states.get(&msg.command.entity_id),
+                self.entity_settings
+                    .media_players
+                    .get(&msg.command.entity_id),
+            ),
+            EntityType::Sensor => match domain.as_str() {
+                "select" | "input_select" => select::handle_select(&msg.command),
+                "number" | "input_number" => number::handle_number(
+                    &msg.command,
+                    self.entity_states.get(&msg.command.entity_id),
+                ),
+                "timer" => timer::handle_timer(&msg.command),
+                "counter" => counter::handle_counter(&msg.command),
+                "input_datetime" => input_datetime::handle_input_datetime(&msg.command),
+                "text" | "input_text" => {
+                    text::handle_text(&msg.command, self.entity_states.get(&msg.command.entity_id))
+                }
+                _ => Err(ServiceError::BadRequest(
+                    "Sensor doesn't support sending commands to! Ignoring call".to_string(),
+                )),
+            },
+            EntityType::Remote => remote::handle_remote(
+                &msg.command,
+                self.entity_settings.remotes.get(&msg.command.entity_id),
+            ),
+            EntityType::Activity | EntityType::Macro => Err(ServiceError::BadRequest(format!(
+                "{} is an internal remote-core entity",
+                msg.command.entity_type
+            ))),
+        }?;
+
+        // the update install button is a virtual entity of the HA update entity
+        let entity_id = match (&msg.command.entity_type, domain.as_str()) {
+            (EntityType::Button, "update") => {
+                update::update_entity_id(&msg.command.entity_id).to_string()
+            }
+            _ => msg.command.entity_id,
+        };
+
+        // HA expands a group entity to its members in the service call target
+        let domain = match domain.as_str() {
+            "group" => group_service_domain(&msg.command.entity_type).to_string(),
+            _ => domain,
+        };
+
+        self.send_call_service(domain, service, service_data, entity_id, ctx)
+    }
+}
+
+impl HomeAssistantClient {
+    /// Send a HA `call_service` request for the given target entity.
+    fn send_call_service(
+        &mut self,
+        domain: String,
+        service: String,
+        service_data: Option<Value>,
+        entity_id: String,
+        ctx: &mut Context<HomeAssistantClient>,
+    ) -> Result<(), ServiceError> {
         let call_srv_msg = CallServiceMsg {
             id: self.new_msg_id(),
             msg_type: "call_service".to_string(),
             domain,
             service,
             service_data,
-            target: Target {
-                entity_id: msg.command.entity_id,
-            },
+            target: Target { entity_id },
+            return_response: None,
         };
 
         let msg = serde_json::to_value(call_srv_msg)?;
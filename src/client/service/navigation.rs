@@ -0,0 +1,405 @@
+// Copyright (c) 2023 Unfolded Circle ApS, Markus Zehnder <markus.z@unfoldedcircle.com>
+// SPDX-License-Identifier: MPL-2.0
+
+//! Media player navigation commands with an associated HA remote entity.
+//!
+//! HA media players don't support cursor, channel, color and menu keys. Many integrations provide
+//! an additional `remote` entity for the same device, which accepts the key names of the
+//! integration in `remote.send_command`.
+
+use crate::client::service::cmd_from_str;
+use crate::configuration::{MediaPlayerSettings, RemoteKeyMap};
+use crate::errors::ServiceError;
+use serde_json::{json, Value};
+use uc_api::intg::EntityCommand;
+use uc_api::{MediaPlayerCommand, MediaPlayerFeature};
+
+/// HA service call of a navigation command.
+#[derive(Debug, PartialEq)]
+pub(crate) struct NavigationCall {
+    pub domain: &'static str,
+    pub service: &'static str,
+    pub entity_id: String,
+    pub service_data: Value,
+}
+
+/// Check if the media player command is a navigation command.
+pub(crate) fn is_navigation_cmd(cmd_id: &str) -> bool {
+    use MediaPlayerCommand::*;
+    matches!(
+        cmd_from_str::<MediaPlayerCommand>(cmd_id),
+        Ok(ChannelUp
+            | ChannelDown
+            | CursorUp
+            | CursorDown
+            | CursorLeft
+            | CursorRight
+            | CursorEnter
+            | FunctionRed
+            | FunctionGreen
+            | FunctionYellow
+            | FunctionBlue
+            | Home
+            | Menu
+            | Back)
+    )
+}
+
+/// Get the key name of a navigation command in the given key map.
+fn keymap_key(keymap: RemoteKeyMap, cmd: MediaPlayerCommand) -> Option<&'static str> {
+    use MediaPlayerCommand::*;
+    let key = match keymap {
+        RemoteKeyMap::AndroidTv => match cmd {
+            CursorUp => "DPAD_UP",
+            CursorDown => "DPAD_DOWN",
+            CursorLeft => "DPAD_LEFT",
+            CursorRight => "DPAD_RIGHT",
+            CursorEnter => "DPAD_CENTER",
+            ChannelUp => "CHANNEL_UP",
+            ChannelDown => "CHANNEL_DOWN",
+            FunctionRed => "PROG_RED",
+            FunctionGreen => "PROG_GREEN",
+            FunctionYellow => "PROG_YELLOW",
+            FunctionBlue => "PROG_BLUE",
+            Home => "HOME",
+            Menu => "MENU",
+            Back => "BACK",
+            _ => return None,
+        },
+        RemoteKeyMap::AppleTv => match cmd {
+            CursorUp => "up",
+            CursorDown => "down",
+            CursorLeft => "left",
+            CursorRight => "right",
+            CursorEnter => "select",
+            ChannelUp => "channel_up",
+            ChannelDown => "channel_down",
+            Home => "home",
+            Menu => "top_menu",
+            Back => "menu",
+            _ => return None,
+        },
+        RemoteKeyMap::SamsungTv => match cmd {
+            CursorUp => "KEY_UP",
+            CursorDown => "KEY_DOWN",
+            CursorLeft => "KEY_LEFT",
+            CursorRight => "KEY_RIGHT",
+            CursorEnter => "KEY_ENTER",
+            ChannelUp => "KEY_CHUP",
+            ChannelDown => "KEY_CHDOWN",
+            FunctionRed => "KEY_RED",
+            FunctionGreen => "KEY_GREEN",
+            FunctionYellow => "KEY_YELLOW",
+            FunctionBlue => "KEY_CYAN",
+            Home => "KEY_HOME",
+            Menu => "KEY_MENU",
+            Back => "KEY_RETURN",
+            _ => return None,
+        },
+        RemoteKeyMap::Roku => match cmd {
+            CursorUp => "up",
+            CursorDown => "down",
+            CursorLeft => "left",
+            CursorRight => "right",
+            CursorEnter => "select",
+            ChannelUp => "channel_up",
+            ChannelDown => "channel_down",
+            Home => "home",
+            Menu => "info",
+            Back => "back",
+            _ => return None,
+        },
+        RemoteKeyMap::LgWebos => match cmd {
+            CursorUp => "UP",
+            CursorDown => "DOWN",
+            CursorLeft => "LEFT",
+            CursorRight => "RIGHT",
+            CursorEnter => "ENTER",
+            ChannelUp => "CHANNELUP",
+            ChannelDown => "CHANNELDOWN",
+            FunctionRed => "RED",
+            FunctionGreen => "GREEN",
+            FunctionYellow => "YELLOW",
+            FunctionBlue => "BLUE",
+            Home => "HOME",
+            Menu => "MENU",
+            Back => "BACK",
+            _ => return None,
+        },
+    };
+    Some(key)
+}
+
+/// Get the navigation features of a media player with all keys available in the settings.
+///
+/// Features are only available if navigation commands can be sent: with a configured remote
+/// entity, an existing paired remote entity or the LG webOS button service.
+///
+/// # Arguments
+///
+/// * `settings`: media player settings with the remote entity and key map.
+/// * `paired_remote`: remote entity with the same object id as the media player, if available.
+pub(crate) fn navigation_features(
+    settings: &MediaPlayerSettings,
+    paired_remote: Option<&str>,
+) -> Vec<MediaPlayerFeature> {
+    use MediaPlayerCommand::*;
+    if settings.remote.is_none()
+        && paired_remote.is_none()
+        && settings.keymap != Some(RemoteKeyMap::LgWebos)
+    {
+        return Vec::new();
+    }
+    let has_keys = |cmds: &[MediaPlayerCommand]| {
+        cmds.iter().all(|cmd| {
+            settings.keys.contains_key(&cmd.to_string())
+                || settings
+                    .keymap
+                    .and_then(|keymap| keymap_key(keymap, *cmd))
+                    .is_some()
+        })
+    };
+
+    [
+        (
+            MediaPlayerFeature::Dpad,
+            &[CursorUp, CursorDown, CursorLeft, CursorRight, CursorEnter][..],
+        ),
+        (MediaPlayerFeature::Home, &[Home]),
+        (MediaPlayerFeature::Menu, &[Menu]),
+        (
+            MediaPlayerFeature::ChannelSwitcher,
+            &[ChannelUp, ChannelDown],
+        ),
+        (
+            MediaPlayerFeature::ColorButtons,
+            &[FunctionRed, FunctionGreen, FunctionYellow, FunctionBlue],
+        ),
+    ]
+    .into_iter()
+    .filter(|(_, cmds)| has_keys(cmds))
+    .map(|(feature, _)| feature)
+    .collect()
+}
+
+/// Get the remote entity with the same object id as the media player.
+pub(crate) fn paired_remote_id(entity_id: &str) -> Option<String> {
+    entity_id
+        .strip_prefix("media_player.")
+        .map(|object_id| format!("remote.{object_id}"))
+}
+
+/// Map a media player navigation command to a `send_command` service call of the associated
+/// remote entity.
+///
+/// # Arguments
+///
+/// * `msg`: navigation command of the media player.
+/// * `settings`: optional media player settings with the remote entity and key map.
+/// * `paired_remote`: remote entity with the same object id as the media player, if available.
+pub(crate) fn handle_media_player_navigation(
+    msg: &EntityCommand,
+    settings: Option<&MediaPlayerSettings>,
+    paired_remote: Option<String>,
+) -> Result<NavigationCall, ServiceError> {
+    let cmd: MediaPlayerCommand = cmd_from_str(&msg.cmd_id)?;
+    let keymap = settings.and_then(|s| s.keymap);
+
+    let key = settings
+        .and_then(|s| s.keys.get(&msg.cmd_id))
+        .map(|key| key.as_str())
+        .or_else(|| keymap.and_then(|keymap| keymap_key(keymap, cmd)))
+        .ok_or_else(|| {
+            ServiceError::BadRequest(format!(
+                "Not supported: no key configured for {}",
+                msg.cmd_id
+            ))
+        })?;
+
+    match (settings.and_then(|s| s.remote.clone()), paired_remote) {
+        (Some(remote), _) | (None, Some(remote)) => Ok(NavigationCall {
+            domain: "remote",
+            service: "send_command",
+            entity_id: remote,
+            service_data: json!({ "command": key }),
+        }),
+        // the LG webOS integration doesn't provide a remote entity
+        (None, None) if keymap == Some(RemoteKeyMap::LgWebos) => Ok(NavigationCall {
+            domain: "webostv",
+            service: "button",
+            entity_id: msg.entity_id.clone(),
+            service_data: json!({ "button": key }),
+        }),
+        (None, None) => Err(ServiceError::BadRequest(
+            "Not supported: no remote entity available for navigation commands".into(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use std::collections::HashMap;
+    use uc_api::EntityType;
+
+    fn new_entity_command(cmd_id: &str) -> EntityCommand {
+        EntityCommand {
+            device_id: None,
+            entity_type: EntityType::MediaPlayer,
+            entity_id: "media_player.living_room_tv".into(),
+            cmd_id: cmd_id.into(),
+            params: None,
+        }
+    }
+
+    fn new_settings(remote: Option<&str>, keymap: Option<RemoteKeyMap>) -> MediaPlayerSettings {
+        MediaPlayerSettings {
+            remote: remote.map(String::from),
+            keymap,
+            ..Default::default()
+        }
+    }
+
+    #[rstest]
+    #[case("cursor_up", true)]
+    #[case("function_blue", true)]
+    #[case("back", true)]
+    #[case("play_pause", false)]
+    #[case("invalid", false)]
+    fn navigation_cmd_is_detected(#[case] cmd_id: &str, #[case] expected: bool) {
+        assert_eq!(expected, is_navigation_cmd(cmd_id));
+    }
+
+    #[rstest]
+    #[case(RemoteKeyMap::AndroidTv, "cursor_enter", "DPAD_CENTER")]
+    #[case(RemoteKeyMap::AppleTv, "back", "menu")]
+    #[case(RemoteKeyMap::SamsungTv, "function_blue", "KEY_CYAN")]
+    #[case(RemoteKeyMap::Roku, "channel_up", "channel_up")]
+    #[case(RemoteKeyMap::LgWebos, "home", "HOME")]
+    fn keymap_cmd_is_sent_to_remote(
+        #[case] keymap: RemoteKeyMap,
+        #[case] cmd_id: &str,
+        #[case] key: &str,
+    ) {
+        let settings = new_settings(Some("remote.tv"), Some(keymap));
+        let result =
+            handle_media_player_navigation(&new_entity_command(cmd_id), Some(&settings), None);
+
+        assert_eq!(
+            Ok(NavigationCall {
+                domain: "remote",
+                service: "send_command",
+                entity_id: "remote.tv".into(),
+                service_data: json!({ "command": key }),
+            }),
+            result
+        );
+    }
+
+    #[rstest]
+    #[case(RemoteKeyMap::AndroidTv, vec![MediaPlayerFeature::Dpad, MediaPlayerFeature::Home, MediaPlayerFeature::Menu, MediaPlayerFeature::ChannelSwitcher, MediaPlayerFeature::ColorButtons])]
+    #[case(RemoteKeyMap::AppleTv, vec![MediaPlayerFeature::Dpad, MediaPlayerFeature::Home, MediaPlayerFeature::Menu, MediaPlayerFeature::ChannelSwitcher])]
+    fn navigation_features_of_keymap(
+        #[case] keymap: RemoteKeyMap,
+        #[case] expected: Vec<MediaPlayerFeature>,
+    ) {
+        let settings = new_settings(None, Some(keymap));
+        assert_eq!(
+            expected,
+            navigation_features(&settings, Some("remote.living_room_tv"))
+        );
+    }
+
+    #[rstest]
+    #[case(Some("remote.tv"), Some(RemoteKeyMap::AndroidTv), 5)]
+    #[case(None, Some(RemoteKeyMap::LgWebos), 5)]
+    #[case(None, Some(RemoteKeyMap::AndroidTv), 0)]
+    #[case(None, None, 0)]
+    fn navigation_features_require_remote_without_paired_remote(
+        #[case] remote: Option<&str>,
+        #[case] keymap: Option<RemoteKeyMap>,
+        #[case] count: usize,
+    ) {
+        let settings = new_settings(remote, keymap);
+        assert_eq!(count, navigation_features(&settings, None).len());
+    }
+
+    #[test]
+    fn navigation_features_of_custom_keys() {
+        let mut settings = new_settings(None, None);
+        settings.keys = HashMap::from([
+            ("home".into(), "KEY_HOME".into()),
+            ("channel_up".into(), "KEY_CHUP".into()),
+        ]);
+        assert_eq!(
+            vec![MediaPlayerFeature::Home],
+            navigation_features(&settings, Some("remote.living_room_tv"))
+        );
+    }
+
+    #[test]
+    fn custom_key_overrides_keymap() {
+        let mut settings = new_settings(None, Some(RemoteKeyMap::AndroidTv));
+        settings.keys = HashMap::from([("menu".into(), "SETTINGS".into())]);
+        let result = handle_media_player_navigation(
+            &new_entity_command("menu"),
+            Some(&settings),
+            paired_remote_id("media_player.living_room_tv"),
+        );
+
+        assert_eq!(
+            Ok(NavigationCall {
+                domain: "remote",
+                service: "send_command",
+                entity_id: "remote.living_room_tv".into(),
+                service_data: json!({ "command": "SETTINGS" }),
+            }),
+            result
+        );
+    }
+
+    #[test]
+    fn lg_webos_without_remote_uses_button_service() {
+        let settings = new_settings(None, Some(RemoteKeyMap::LgWebos));
+        let result =
+            handle_media_player_navigation(&new_entity_command("cursor_up"), Some(&settings), None);
+
+        assert_eq!(
+            Ok(NavigationCall {
+                domain: "webostv",
+                service: "button",
+                entity_id: "media_player.living_room_tv".into(),
+                service_data: json!({ "button": "UP" }),
+            }),
+            result
+        );
+    }
+
+    #[rstest]
+    #[case(None, Some("remote.living_room_tv"), "cursor_up")]
+    #[case(Some(RemoteKeyMap::AndroidTv), None, "cursor_up")]
+    #[case(
+        Some(RemoteKeyMap::AppleTv),
+        Some("remote.living_room_tv"),
+        "function_red"
+    )]
+    fn missing_key_or_remote_returns_bad_request(
+        #[case] keymap: Option<RemoteKeyMap>,
+        #[case] paired_remote: Option<&str>,
+        #[case] cmd_id: &str,
+    ) {
+        let settings = new_settings(None, keymap);
+        let result = handle_media_player_navigation(
+            &new_entity_command(cmd_id),
+            Some(&settings),
+            paired_remote.map(String::from),
+        );
+
+        assert!(
+            matches!(result, Err(ServiceError::BadRequest(_))),
+            "Invalid configuration must return BadRequest, but got: {:?}",
+            result
+        );
+    }
+}
@@ -0,0 +1,195 @@
+// Copyright (c) 2023 Unfolded Circle ApS, Markus Zehnder <markus.z@unfoldedcircle.com>
+// SPDX-License-Identifier: MPL-2.0
+
+//! Number and input_number entity specific HA service call logic.
+
+use crate::client::entity::NumberRange;
+use crate::client::model::EventState;
+use crate::client::service::{cmd_from_str, get_required_params};
+use crate::errors::ServiceError;
+use serde_json::{Map, Value};
+use strum_macros::{EnumString, EnumVariantNames};
+use uc_api::intg::EntityCommand;
+
+/// Number entity commands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumString, EnumVariantNames)]
+#[strum(serialize_all = "snake_case")]
+pub enum NumberCommand {
+    SetValue,
+    Increment,
+    Decrement,
+}
+
+/// Map a number command to a HA `set_value`, `increment` or `decrement` service call.
+///
+/// The value is rounded to the step size of the entity and clamped to its value range. The `number`
+/// domain doesn't provide increment and decrement services, these are calculated from the
+/// current entity state.
+///
+/// # Arguments
+///
+/// * `msg`: Entity command of a `number` or `input_number` entity.
+/// * `state`: Last known entity state including the value range attributes.
+///
+/// returns: Result<(String, Option<Value>), ServiceError>
+pub(crate) fn handle_number(
+    msg: &EntityCommand,
+    state: Option<&EventState>,
+) -> Result<(String, Option<Value>), ServiceError> {
+    let cmd: NumberCommand = cmd_from_str(&msg.cmd_id)?;
+    let range = state
+        .and_then(|s| s.attributes.as_ref())
+        .map(NumberRange::from)
+        .unwrap_or_else(|| NumberRange::new(0.0, 100.0, 1.0));
+
+    let value = match cmd {
+        NumberCommand::SetValue => {
+            let params = get_required_params(msg)?;
+            match params.get("value").and_then(value_as_f64) {
+                Some(value) => value,
+                None => {
+                    return Err(ServiceError::BadRequest(
+                        "Invalid or missing params.value attribute".into(),
+                    ))
+                }
+            }
+        }
+        NumberCommand::Increment | NumberCommand::Decrement
+            if msg.entity_id.starts_with("input_number.") =>
+        {
+            let service = if cmd == NumberCommand::Increment {
+                "increment"
+            } else {
+                "decrement"
+            };
+            return Ok((service.into(), None));
+        }
+        NumberCommand::Increment | NumberCommand::Decrement => {
+            let current = state
+                .and_then(|s| s.state.parse::<f64>().ok())
+                .ok_or_else(|| {
+                    ServiceError::BadRequest(format!(
+                        "Current value of {} is not available",
+                        msg.entity_id
+                    ))
+                })?;
+            if cmd == NumberCommand::Increment {
+                current + range.step
+            } else {
+                current - range.step
+            }
+        }
+    };
+
+    let mut data = Map::new();
+    data.insert("value".into(), range.json_value(range.round(value)));
+
+    Ok(("set_value".into(), Some(data.into())))
+}
+
+/// Numeric values might also be sent as string from the remote.
+fn value_as_f64(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::client::model::EventState;
+    use crate::client::service::number::handle_number;
+    use crate::errors::ServiceError;
+    use rstest::rstest;
+    use serde_json::{json, Value};
+    use uc_api::intg::EntityCommand;
+    use uc_api::EntityType;
+
+    fn new_entity_command(entity_id: &str, cmd_id: &str, params: Value) -> EntityCommand {
+        EntityCommand {
+            device_id: None,
+            entity_type: EntityType::Sensor,
+            entity_id: entity_id.into(),
+            cmd_id: cmd_id.into(),
+            params: params.as_object().cloned(),
+        }
+    }
+
+    fn new_state(state: &str) -> EventState {
+        EventState {
+            state: state.into(),
+            attributes: json!({ "min": -6, "max": 6, "step": 0.5, "mode": "slider" })
+                .as_object()
+                .cloned(),
+        }
+    }
+
+    #[rstest]
+    #[case(json!(2.3), json!(2.5))]
+    #[case(json!(2.2), json!(2.0))]
+    #[case(json!("-1.5"), json!(-1.5))]
+    #[case(json!(10), json!(6.0))]
+    #[case(json!(-7), json!(-6.0))]
+    fn set_value_is_rounded_to_step(#[case] value: Value, #[case] expected: Value) {
+        let cmd = new_entity_command("number.bass_level", "set_value", json!({ "value": value }));
+        let state = new_state("0");
+        let result = handle_number(&cmd, Some(&state));
+
+        assert_eq!(
+            Ok(("set_value".to_string(), Some(json!({ "value": expected })))),
+            result
+        );
+    }
+
+    #[rstest]
+    #[case("increment", "1.5", json!(2.0))]
+    #[case("decrement", "1.5", json!(1.0))]
+    #[case("increment", "6.0", json!(6.0))]
+    #[case("decrement", "-6.0", json!(-6.0))]
+    fn number_increment_decrement_uses_current_value(
+        #[case] cmd_id: &str,
+        #[case] state: &str,
+        #[case] expected: Value,
+    ) {
+        let cmd = new_entity_command("number.bass_level", cmd_id, Value::Null);
+        let state = new_state(state);
+        let result = handle_number(&cmd, Some(&state));
+
+        assert_eq!(
+            Ok(("set_value".to_string(), Some(json!({ "value": expected })))),
+            result
+        );
+    }
+
+    #[rstest]
+    #[case("increment")]
+    #[case("decrement")]
+    fn input_number_increment_decrement_uses_ha_service(#[case] cmd_id: &str) {
+        let cmd = new_entity_command("input_number.delay", cmd_id, Value::Null);
+        let result = handle_number(&cmd, None);
+
+        assert_eq!(Ok((cmd_id.to_string(), None)), result);
+    }
+
+    #[rstest]
+    #[case("set_value", Value::Null, Some("1"))]
+    #[case("set_value", json!({ "value": "foo" }), Some("1"))]
+    #[case("increment", Value::Null, Some("unavailable"))]
+    #[case("decrement", Value::Null, None)]
+    fn invalid_request_returns_bad_request(
+        #[case] cmd_id: &str,
+        #[case] params: Value,
+        #[case] state: Option<&str>,
+    ) {
+        let cmd = new_entity_command("number.bass_level", cmd_id, params);
+        let state = state.map(new_state);
+        let result = handle_number(&cmd, state.as_ref());
+
+        assert!(
+            matches!(result, Err(ServiceError::BadRequest(_))),
+            "Invalid value must return BadRequest, but got: {:?}",
+            result
+        );
+    }
+}
@@ -0,0 +1,244 @@
+// Copyright (c) 2023 Unfolded Circle ApS, Markus Zehnder <markus.z@unfoldedcircle.com>
+// SPDX-License-Identifier: MPL-2.0
+
+//! Remote entity specific HA service call logic.
+
+use crate::client::service::{cmd_from_str, get_required_params};
+use crate::configuration::RemoteSettings;
+use crate::errors::ServiceError;
+use serde_json::{Map, Value};
+use strum_macros::{EnumString, EnumVariantNames};
+use uc_api::intg::EntityCommand;
+
+/// Remote entity commands.
+///
+/// Mapped to the HA `remote.turn_on`, `remote.turn_off`, `remote.toggle` and
+/// `remote.send_command` services.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumString, EnumVariantNames)]
+#[strum(serialize_all = "snake_case")]
+pub enum RemoteCommand {
+    On,
+    Off,
+    Toggle,
+    SendCmd,
+    SendCmdSequence,
+}
+
+pub(crate) fn handle_remote(
+    msg: &EntityCommand,
+    settings: Option<&RemoteSettings>,
+) -> Result<(String, Option<Value>), ServiceError> {
+    let cmd: RemoteCommand = cmd_from_str(&msg.cmd_id)?;
+
+    let result = match cmd {
+        RemoteCommand::On => {
+            let mut data = Map::new();
+            if let Some(activity) = msg
+                .params
+                .as_ref()
+                .and_then(|p| p.get("activity"))
+                .and_then(|v| v.as_str())
+            {
+                data.insert("activity".into(), activity.into());
+            }
+            (
+                "turn_on".into(),
+                if data.is_empty() {
+                    None
+                } else {
+                    Some(data.into())
+                },
+            )
+        }
+        RemoteCommand::Off => ("turn_off".into(), None),
+        RemoteCommand::Toggle => ("toggle".into(), None),
+        RemoteCommand::SendCmd => {
+            let params = get_required_params(msg)?;
+            let command = match params.get("command").and_then(|v| v.as_str()) {
+                Some(command) if !command.is_empty() => command,
+                _ => {
+                    return Err(ServiceError::BadRequest(
+                        "Invalid or missing params.command attribute".into(),
+                    ))
+                }
+            };
+            let mut data = send_command_data(params, settings)?;
+            data.insert("command".into(), command.into());
+            if let Some(hold) = params.get("hold").and_then(|v| v.as_u64()) {
+                data.insert("hold_secs".into(), (hold as f64 / 1000_f64).into());
+            }
+            ("send_command".into(), Some(data.into()))
+        }
+        RemoteCommand::SendCmdSequence => {
+            let params = get_required_params(msg)?;
+            let sequence = params
+                .get("sequence")
+                .and_then(|v| v.as_array())
+                .map(|v| {
+                    v.iter()
+                        .filter_map(|c| c.as_str())
+                        .map(|c| Value::String(c.into()))
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
+            if sequence.is_empty() {
+                return Err(ServiceError::BadRequest(
+                    "Invalid or missing params.sequence attribute".into(),
+                ));
+            }
+            let mut data = send_command_data(params, settings)?;
+            data.insert("command".into(), sequence.into());
+            ("send_command".into(), Some(data.into()))
+        }
+    };
+
+    Ok(result)
+}
+
+/// Create the common `remote.send_command` service data for the `device`, `repeat` and `delay`
+/// parameters.
+fn send_command_data(
+    params: &Map<String, Value>,
+    settings: Option<&RemoteSettings>,
+) -> Result<Map<String, Value>, ServiceError> {
+    let mut data = Map::new();
+
+    // a device parameter in the command overrides the configured device
+    if let Some(device) = params.get("device").and_then(|v| v.as_str()) {
+        data.insert("device".into(), device.into());
+    } else if let Some(device) = settings.and_then(|s| s.device.as_ref()) {
+        data.insert("device".into(), device.clone().into());
+    }
+    if let Some(repeat) = params.get("repeat") {
+        match repeat.as_u64() {
+            Some(repeat @ 1..=20) => {
+                data.insert("num_repeats".into(), repeat.into());
+            }
+            _ => {
+                return Err(ServiceError::BadRequest(format!(
+                    "Invalid params.repeat value {}: Valid: 1..20",
+                    repeat
+                )))
+            }
+        }
+    }
+    if let Some(delay) = params.get("delay").and_then(|v| v.as_u64()) {
+        data.insert("delay_secs".into(), (delay as f64 / 1000_f64).into());
+    }
+
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::client::service::remote::handle_remote;
+    use crate::configuration::RemoteSettings;
+    use crate::errors::ServiceError;
+    use rstest::rstest;
+    use serde_json::{json, Value};
+    use uc_api::intg::EntityCommand;
+    use uc_api::EntityType;
+
+    fn new_entity_command(cmd_id: impl Into<String>, params: Value) -> EntityCommand {
+        EntityCommand {
+            device_id: None,
+            entity_type: EntityType::Remote,
+            entity_id: "remote.harmony_hub".into(),
+            cmd_id: cmd_id.into(),
+            params: params.as_object().cloned(),
+        }
+    }
+
+    #[rstest]
+    #[case("on", "turn_on")]
+    #[case("off", "turn_off")]
+    #[case("toggle", "toggle")]
+    fn simple_cmd_returns_service_without_data(#[case] cmd_id: &str, #[case] service: &str) {
+        let cmd = new_entity_command(cmd_id, Value::Null);
+        let result = handle_remote(&cmd, None);
+
+        assert_eq!(Ok((service.to_string(), None)), result);
+    }
+
+    #[test]
+    fn on_cmd_with_activity_returns_activity_data() {
+        let cmd = new_entity_command("on", json!({ "activity": "Watch TV" }));
+        let result = handle_remote(&cmd, None);
+
+        assert_eq!(
+            Ok((
+                "turn_on".to_string(),
+                Some(json!({ "activity": "Watch TV" }))
+            )),
+            result
+        );
+    }
+
+    #[test]
+    fn send_cmd_uses_configured_device() {
+        let settings = RemoteSettings {
+            commands: vec![],
+            device: Some("TV".into()),
+        };
+        let cmd = new_entity_command(
+            "send_cmd",
+            json!({ "command": "VolumeUp", "repeat": 3, "delay": 500, "hold": 1500 }),
+        );
+        let result = handle_remote(&cmd, Some(&settings));
+
+        assert_eq!(
+            Ok((
+                "send_command".to_string(),
+                Some(json!({
+                    "command": "VolumeUp",
+                    "device": "TV",
+                    "num_repeats": 3,
+                    "delay_secs": 0.5,
+                    "hold_secs": 1.5
+                }))
+            )),
+            result
+        );
+    }
+
+    #[test]
+    fn send_cmd_sequence_returns_command_list() {
+        let cmd = new_entity_command(
+            "send_cmd_sequence",
+            json!({ "sequence": ["DPAD_DOWN", "DPAD_CENTER"], "device": "Shield" }),
+        );
+        let result = handle_remote(&cmd, None);
+
+        assert_eq!(
+            Ok((
+                "send_command".to_string(),
+                Some(json!({
+                    "command": ["DPAD_DOWN", "DPAD_CENTER"],
+                    "device": "Shield"
+                }))
+            )),
+            result
+        );
+    }
+
+    #[rstest]
+    #[case("send_cmd", Value::Null)]
+    #[case("send_cmd", json!({}))]
+    #[case("send_cmd", json!({ "command": "" }))]
+    #[case("send_cmd", json!({ "command": "Mute", "repeat": 0 }))]
+    #[case("send_cmd_sequence", json!({ "sequence": [] }))]
+    #[case("send_cmd_sequence", json!({ "sequence": "Mute" }))]
+    fn send_cmd_with_invalid_params_returns_bad_request(
+        #[case] cmd_id: &str,
+        #[case] params: Value,
+    ) {
+        let cmd = new_entity_command(cmd_id, params);
+        let result = handle_remote(&cmd, None);
+
+        assert!(
+            matches!(result, Err(ServiceError::BadRequest(_))),
+            "Invalid value must return BadRequest, but got: {:?}",
+            result
+        );
+    }
+}
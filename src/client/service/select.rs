@@ -0,0 +1,118 @@
+// Copyright (c) 2023 Unfolded Circle ApS, Markus Zehnder <markus.z@unfoldedcircle.com>
+// SPDX-License-Identifier: MPL-2.0
+
+//! Select and input_select entity specific HA service call logic.
+
+use crate::client::service::{cmd_from_str, get_required_params};
+use crate::errors::ServiceError;
+use serde_json::{Map, Value};
+use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
+use uc_api::intg::EntityCommand;
+
+/// Select entity commands.
+///
+/// The command names are identical to the `select` and `input_select` service names in HA.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, AsRefStr, EnumString, EnumVariantNames)]
+pub enum SelectCommand {
+    #[strum(serialize = "select_option")]
+    Option,
+    #[strum(serialize = "select_next")]
+    Next,
+    #[strum(serialize = "select_previous")]
+    Previous,
+    #[strum(serialize = "select_first")]
+    First,
+    #[strum(serialize = "select_last")]
+    Last,
+}
+
+pub(crate) fn handle_select(msg: &EntityCommand) -> Result<(String, Option<Value>), ServiceError> {
+    let cmd: SelectCommand = cmd_from_str(&msg.cmd_id)?;
+
+    let data = match cmd {
+        SelectCommand::Option => {
+            let mut data = Map::new();
+            let params = get_required_params(msg)?;
+            if let Some(option) = params.get("option").and_then(|v| v.as_str()) {
+                data.insert("option".into(), option.into());
+            } else {
+                return Err(ServiceError::BadRequest(
+                    "Invalid or missing params.option attribute".into(),
+                ));
+            }
+            Some(data.into())
+        }
+        SelectCommand::Next | SelectCommand::Previous => {
+            // cycle through the options by default, as the input_select services in HA
+            match msg
+                .params
+                .as_ref()
+                .and_then(|p| p.get("cycle"))
+                .and_then(|v| v.as_bool())
+            {
+                Some(cycle) => {
+                    let mut data = Map::new();
+                    data.insert("cycle".into(), cycle.into());
+                    Some(data.into())
+                }
+                None => None,
+            }
+        }
+        SelectCommand::First | SelectCommand::Last => None,
+    };
+
+    Ok((cmd.as_ref().to_string(), data))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::client::service::select::handle_select;
+    use crate::errors::ServiceError;
+    use rstest::rstest;
+    use serde_json::{json, Value};
+    use uc_api::intg::EntityCommand;
+    use uc_api::EntityType;
+
+    fn new_entity_command(cmd_id: impl Into<String>, params: Value) -> EntityCommand {
+        EntityCommand {
+            device_id: None,
+            entity_type: EntityType::Sensor,
+            entity_id: "input_select.house_mode".into(),
+            cmd_id: cmd_id.into(),
+            params: params.as_object().cloned(),
+        }
+    }
+
+    #[rstest]
+    #[case("select_next", Value::Null, None)]
+    #[case("select_next", json!({ "cycle": false }), Some(json!({ "cycle": false })))]
+    #[case("select_previous", json!({ "cycle": true }), Some(json!({ "cycle": true })))]
+    #[case("select_first", Value::Null, None)]
+    #[case("select_last", Value::Null, None)]
+    #[case("select_option", json!({ "option": "Night" }), Some(json!({ "option": "Night" })))]
+    fn select_cmd_returns_proper_request(
+        #[case] cmd_id: &str,
+        #[case] params: Value,
+        #[case] data: Option<Value>,
+    ) {
+        let cmd = new_entity_command(cmd_id, params);
+        let result = handle_select(&cmd);
+
+        assert_eq!(Ok((cmd_id.to_string(), data)), result);
+    }
+
+    #[rstest]
+    #[case(Value::Null)]
+    #[case(json!({}))]
+    #[case(json!({ "option": 1 }))]
+    fn select_option_with_invalid_params_returns_bad_request(#[case] params: Value) {
+        let cmd = new_entity_command("select_option", params);
+        let result = handle_select(&cmd);
+
+        assert!(
+            matches!(result, Err(ServiceError::BadRequest(_))),
+            "Invalid value must return BadRequest, but got: {:?}",
+            result
+        );
+    }
+}
@@ -0,0 +1,129 @@
+// Copyright (c) 2023 Unfolded Circle ApS, Markus Zehnder <markus.z@unfoldedcircle.com>
+// SPDX-License-Identifier: MPL-2.0
+
+//! Siren entity specific HA service call logic.
+
+use crate::client::service::cmd_from_str;
+use crate::errors::ServiceError;
+use serde_json::{Map, Value};
+use uc_api::intg::EntityCommand;
+use uc_api::SwitchCommand;
+
+pub(crate) fn handle_siren(msg: &EntityCommand) -> Result<(String, Option<Value>), ServiceError> {
+    let cmd: SwitchCommand = cmd_from_str(&msg.cmd_id)?;
+
+    let result = match cmd {
+        SwitchCommand::On => {
+            let mut data = Map::new();
+            if let Some(params) = msg.params.as_ref() {
+                // tone is either a tone name or a numeric tone id
+                if let Some(tone) = params.get("tone") {
+                    if !(tone.is_string() || tone.is_u64()) {
+                        return Err(ServiceError::BadRequest(
+                            "Invalid params.tone attribute".into(),
+                        ));
+                    }
+                    data.insert("tone".into(), tone.clone());
+                }
+                if let Some(duration) = params.get("duration") {
+                    match duration.as_u64() {
+                        Some(duration) => data.insert("duration".into(), duration.into()),
+                        None => {
+                            return Err(ServiceError::BadRequest(
+                                "Invalid params.duration attribute".into(),
+                            ))
+                        }
+                    };
+                }
+                // volume in percent as in the media-player entity, HA uses 0.0..1.0
+                if let Some(volume) = params.get("volume") {
+                    match volume.as_u64() {
+                        Some(volume @ 0..=100) => {
+                            data.insert("volume_level".into(), (volume as f64 / 100_f64).into())
+                        }
+                        _ => {
+                            return Err(ServiceError::BadRequest(
+                                "Invalid params.volume attribute. Valid: 0..100".into(),
+                            ))
+                        }
+                    };
+                }
+            }
+            (
+                "turn_on".into(),
+                if data.is_empty() {
+                    None
+                } else {
+                    Some(data.into())
+                },
+            )
+        }
+        SwitchCommand::Off => ("turn_off".into(), None),
+        SwitchCommand::Toggle => ("toggle".into(), None),
+    };
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::client::service::siren::handle_siren;
+    use crate::errors::ServiceError;
+    use rstest::rstest;
+    use serde_json::{json, Value};
+    use uc_api::intg::EntityCommand;
+    use uc_api::EntityType;
+
+    fn new_entity_command(cmd_id: impl Into<String>, params: Value) -> EntityCommand {
+        EntityCommand {
+            device_id: None,
+            entity_type: EntityType::Switch,
+            entity_id: "siren.alarm".into(),
+            cmd_id: cmd_id.into(),
+            params: params.as_object().cloned(),
+        }
+    }
+
+    #[rstest]
+    #[case("on", "turn_on")]
+    #[case("off", "turn_off")]
+    #[case("toggle", "toggle")]
+    fn simple_cmd_returns_service_without_data(#[case] cmd_id: &str, #[case] service: &str) {
+        let cmd = new_entity_command(cmd_id, Value::Null);
+        let result = handle_siren(&cmd);
+
+        assert_eq!(Ok((service.to_string(), None)), result);
+    }
+
+    #[test]
+    fn on_cmd_with_params_returns_turn_on_data() {
+        let cmd = new_entity_command(
+            "on",
+            json!({ "tone": "fire", "duration": 10, "volume": 50 }),
+        );
+        let result = handle_siren(&cmd);
+
+        assert_eq!(
+            Ok((
+                "turn_on".to_string(),
+                Some(json!({ "tone": "fire", "duration": 10, "volume_level": 0.5 }))
+            )),
+            result
+        );
+    }
+
+    #[rstest]
+    #[case(json!({ "tone": true }))]
+    #[case(json!({ "duration": "10s" }))]
+    #[case(json!({ "volume": 101 }))]
+    fn on_cmd_with_invalid_params_returns_bad_request(#[case] params: Value) {
+        let cmd = new_entity_command("on", params);
+        let result = handle_siren(&cmd);
+
+        assert!(
+            matches!(result, Err(ServiceError::BadRequest(_))),
+            "Invalid value must return BadRequest, but got: {:?}",
+            result
+        );
+    }
+}
@@ -0,0 +1,147 @@
+// Copyright (c) 2023 Unfolded Circle ApS, Markus Zehnder <markus.z@unfoldedcircle.com>
+// SPDX-License-Identifier: MPL-2.0
+
+//! Text and input_text entity specific HA service call logic.
+//!
+//! The new value is validated with the length restrictions and pattern of the HA entity before
+//! calling HA. Otherwise an invalid value would only be logged in HA without any feedback.
+
+use crate::client::model::EventState;
+use crate::client::service::{cmd_from_str, get_required_params};
+use crate::errors::ServiceError;
+use log::warn;
+use regex::Regex;
+use serde_json::{json, Map, Value};
+use strum_macros::{EnumString, EnumVariantNames};
+use uc_api::intg::EntityCommand;
+
+/// Text entity commands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumString, EnumVariantNames)]
+#[strum(serialize_all = "snake_case")]
+pub enum TextCommand {
+    SetValue,
+}
+
+pub(crate) fn handle_text(
+    msg: &EntityCommand,
+    state: Option<&EventState>,
+) -> Result<(String, Option<Value>), ServiceError> {
+    let cmd: TextCommand = cmd_from_str(&msg.cmd_id)?;
+
+    let result = match cmd {
+        TextCommand::SetValue => {
+            let params = get_required_params(msg)?;
+            let value = params
+                .get("value")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| {
+                    ServiceError::BadRequest("Invalid or missing params.value attribute".into())
+                })?;
+            if let Some(ha_attr) = state.and_then(|s| s.attributes.as_ref()) {
+                validate_text(value, ha_attr)?;
+            }
+            ("set_value".into(), Some(json!({ "value": value })))
+        }
+    };
+
+    Ok(result)
+}
+
+/// Validate a text value with the `min`, `max` and `pattern` attributes of the HA entity.
+fn validate_text(value: &str, ha_attr: &Map<String, Value>) -> Result<(), ServiceError> {
+    let len = value.chars().count() as u64;
+    if let Some(min) = ha_attr.get("min").and_then(|v| v.as_u64()) {
+        if len < min {
+            return Err(ServiceError::BadRequest(format!(
+                "Value is too short: {len} characters, minimum length: {min}"
+            )));
+        }
+    }
+    if let Some(max) = ha_attr.get("max").and_then(|v| v.as_u64()) {
+        if len > max {
+            return Err(ServiceError::BadRequest(format!(
+                "Value is too long: {len} characters, maximum length: {max}"
+            )));
+        }
+    }
+
+    if let Some(pattern) = ha_attr.get("pattern").and_then(|v| v.as_str()) {
+        // HA uses Python's `re.match`, which only anchors at the beginning of the value
+        match Regex::new(&format!("^(?:{pattern})")) {
+            Ok(re) if !re.is_match(value) => {
+                return Err(ServiceError::BadRequest(format!(
+                    "Value doesn't match pattern: {pattern}"
+                )));
+            }
+            Ok(_) => {}
+            Err(e) => warn!("Not supported text pattern {pattern}, skipping validation: {e}"),
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::client::model::EventState;
+    use crate::client::service::text::handle_text;
+    use crate::errors::ServiceError;
+    use rstest::rstest;
+    use serde_json::{json, Value};
+    use uc_api::intg::EntityCommand;
+    use uc_api::EntityType;
+
+    fn new_entity_command(value: Value) -> EntityCommand {
+        EntityCommand {
+            device_id: None,
+            entity_type: EntityType::Sensor,
+            entity_id: "input_text.message".into(),
+            cmd_id: "set_value".into(),
+            params: json!({ "value": value }).as_object().cloned(),
+        }
+    }
+
+    fn new_state(attributes: Value) -> EventState {
+        EventState {
+            state: "".into(),
+            attributes: attributes.as_object().cloned(),
+        }
+    }
+
+    #[rstest]
+    #[case("hello", json!({ "min": 0, "max": 100, "pattern": null }))]
+    #[case("", json!({ "min": 0, "max": 100 }))]
+    #[case("äöü", json!({ "min": 3, "max": 3 }))]
+    #[case("AB-123", json!({ "min": 0, "max": 10, "pattern": "[A-Z]{2}-\\d+" }))]
+    #[case("anything", json!({ "min": 0, "max": 100, "pattern": "(?<=x)y" }))]
+    fn set_value_with_valid_value(#[case] value: &str, #[case] attributes: Value) {
+        let cmd = new_entity_command(value.into());
+        let state = new_state(attributes);
+        let result = handle_text(&cmd, Some(&state));
+
+        assert_eq!(
+            Ok(("set_value".to_string(), Some(json!({ "value": value })))),
+            result
+        );
+    }
+
+    #[rstest]
+    #[case(json!("hi"), json!({ "min": 3, "max": 100 }))]
+    #[case(json!("hello world"), json!({ "min": 0, "max": 5 }))]
+    #[case(json!("ab-123"), json!({ "min": 0, "max": 10, "pattern": "[A-Z]{2}-\\d+" }))]
+    #[case(json!(42), json!({ "min": 0, "max": 10 }))]
+    fn set_value_with_invalid_value_returns_bad_request(
+        #[case] value: Value,
+        #[case] attributes: Value,
+    ) {
+        let cmd = new_entity_command(value);
+        let state = new_state(attributes);
+        let result = handle_text(&cmd, Some(&state));
+
+        assert!(
+            matches!(result, Err(ServiceError::BadRequest(_))),
+            "Invalid value must return BadRequest, but got: {:?}",
+            result
+        );
+    }
+}
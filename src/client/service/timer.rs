@@ -0,0 +1,110 @@
+// Copyright (c) 2023 Unfolded Circle ApS, Markus Zehnder <markus.z@unfoldedcircle.com>
+// SPDX-License-Identifier: MPL-2.0
+
+//! Timer entity specific HA service call logic.
+
+use crate::client::entity::parse_duration;
+use crate::client::service::cmd_from_str;
+use crate::errors::ServiceError;
+use serde_json::{json, Value};
+use strum_macros::{EnumString, EnumVariantNames};
+use uc_api::intg::EntityCommand;
+
+/// Timer entity commands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumString, EnumVariantNames)]
+#[strum(serialize_all = "snake_case")]
+pub enum TimerCommand {
+    Start,
+    Pause,
+    Cancel,
+    Finish,
+}
+
+pub(crate) fn handle_timer(msg: &EntityCommand) -> Result<(String, Option<Value>), ServiceError> {
+    let cmd: TimerCommand = cmd_from_str(&msg.cmd_id)?;
+
+    let result = match cmd {
+        TimerCommand::Start => {
+            // optional duration: number of seconds or `HH:MM:SS`
+            let duration = msg.params.as_ref().and_then(|p| p.get("duration"));
+            match duration {
+                None => ("start".into(), None),
+                Some(Value::Number(seconds)) if seconds.is_u64() => {
+                    ("start".into(), Some(json!({ "duration": seconds })))
+                }
+                Some(Value::String(duration)) if parse_duration(duration).is_some() => {
+                    ("start".into(), Some(json!({ "duration": duration })))
+                }
+                Some(duration) => {
+                    return Err(ServiceError::BadRequest(format!(
+                        "Invalid params.duration value {duration}: Valid: seconds or HH:MM:SS"
+                    )))
+                }
+            }
+        }
+        TimerCommand::Pause => ("pause".into(), None),
+        TimerCommand::Cancel => ("cancel".into(), None),
+        TimerCommand::Finish => ("finish".into(), None),
+    };
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::client::service::timer::handle_timer;
+    use crate::errors::ServiceError;
+    use rstest::rstest;
+    use serde_json::{json, Value};
+    use uc_api::intg::EntityCommand;
+    use uc_api::EntityType;
+
+    fn new_entity_command(cmd_id: impl Into<String>, params: Value) -> EntityCommand {
+        EntityCommand {
+            device_id: None,
+            entity_type: EntityType::Sensor,
+            entity_id: "timer.tea".into(),
+            cmd_id: cmd_id.into(),
+            params: params.as_object().cloned(),
+        }
+    }
+
+    #[rstest]
+    #[case("start", "start")]
+    #[case("pause", "pause")]
+    #[case("cancel", "cancel")]
+    #[case("finish", "finish")]
+    fn simple_cmd_returns_service_without_data(#[case] cmd_id: &str, #[case] service: &str) {
+        let cmd = new_entity_command(cmd_id, Value::Null);
+        let result = handle_timer(&cmd);
+
+        assert_eq!(Ok((service.to_string(), None)), result);
+    }
+
+    #[rstest]
+    #[case(json!(300))]
+    #[case(json!("00:05:00"))]
+    fn start_with_duration(#[case] duration: Value) {
+        let cmd = new_entity_command("start", json!({ "duration": duration }));
+        let result = handle_timer(&cmd);
+
+        assert_eq!(
+            Ok(("start".to_string(), Some(json!({ "duration": duration })))),
+            result
+        );
+    }
+
+    #[rstest]
+    #[case(json!(-1))]
+    #[case(json!("5 minutes"))]
+    fn start_with_invalid_duration_returns_bad_request(#[case] duration: Value) {
+        let cmd = new_entity_command("start", json!({ "duration": duration }));
+        let result = handle_timer(&cmd);
+
+        assert!(
+            matches!(result, Err(ServiceError::BadRequest(_))),
+            "Invalid value must return BadRequest, but got: {:?}",
+            result
+        );
+    }
+}
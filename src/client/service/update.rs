@@ -0,0 +1,124 @@
+// Copyright (c) 2023 Unfolded Circle ApS, Markus Zehnder <markus.z@unfoldedcircle.com>
+// SPDX-License-Identifier: MPL-2.0
+
+//! Update entity specific HA service call logic.
+
+use crate::client::entity::update_supports_backup;
+use crate::client::model::EventState;
+use crate::configuration::UpdateSettings;
+use crate::errors::ServiceError;
+use serde_json::{json, Value};
+use uc_api::intg::EntityCommand;
+
+/// Get the HA update entity_id of an install button entity.
+pub(crate) fn update_entity_id(button_id: &str) -> &str {
+    button_id.strip_suffix(".install").unwrap_or(button_id)
+}
+
+/// Handle a button press of the update install button.
+///
+/// A backup is only requested if configured and supported by the update entity.
+pub(crate) fn handle_update_install(
+    msg: &EntityCommand,
+    settings: Option<&UpdateSettings>,
+    state: Option<&EventState>,
+) -> Result<(String, Option<Value>), ServiceError> {
+    if msg.cmd_id != "press" {
+        return Err(ServiceError::BadRequest(format!(
+            "Invalid cmd_id: {}. Valid commands: press",
+            msg.cmd_id
+        )));
+    }
+    if state.map(|s| s.state.as_str()) == Some("off") {
+        return Err(ServiceError::BadRequest("No update available".into()));
+    }
+
+    let backup = settings.map(|s| s.backup).unwrap_or_default()
+        && state
+            .and_then(|s| s.attributes.as_ref())
+            .map(update_supports_backup)
+            .unwrap_or_default();
+
+    Ok((
+        "install".into(),
+        if backup {
+            Some(json!({ "backup": true }))
+        } else {
+            None
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::client::model::EventState;
+    use crate::client::service::update::{handle_update_install, update_entity_id};
+    use crate::configuration::UpdateSettings;
+    use crate::errors::ServiceError;
+    use rstest::rstest;
+    use serde_json::{json, Value};
+    use uc_api::intg::EntityCommand;
+    use uc_api::EntityType;
+
+    fn new_entity_command(cmd_id: &str) -> EntityCommand {
+        EntityCommand {
+            device_id: None,
+            entity_type: EntityType::Button,
+            entity_id: "update.home_assistant_core_update.install".into(),
+            cmd_id: cmd_id.into(),
+            params: None,
+        }
+    }
+
+    fn new_state(state: &str, supported_features: u32) -> EventState {
+        EventState {
+            state: state.into(),
+            attributes: json!({ "supported_features": supported_features })
+                .as_object()
+                .cloned(),
+        }
+    }
+
+    #[test]
+    fn update_entity_id_removes_install_suffix() {
+        assert_eq!(
+            "update.home_assistant_core_update",
+            update_entity_id("update.home_assistant_core_update.install")
+        );
+    }
+
+    #[rstest]
+    #[case(false, 1 | 8, None)]
+    #[case(true, 1, None)]
+    #[case(true, 1 | 8, Some(json!({ "backup": true })))]
+    fn install_with_optional_backup(
+        #[case] backup: bool,
+        #[case] supported_features: u32,
+        #[case] expected: Option<Value>,
+    ) {
+        let cmd = new_entity_command("press");
+        let settings = UpdateSettings {
+            install_button: true,
+            backup,
+        };
+        let state = new_state("on", supported_features);
+        let result = handle_update_install(&cmd, Some(&settings), Some(&state));
+
+        assert_eq!(Ok(("install".to_string(), expected)), result);
+    }
+
+    #[rstest]
+    #[case("toggle", "on")]
+    #[case("press", "off")]
+    fn invalid_install_request_returns_bad_request(#[case] cmd_id: &str, #[case] state: &str) {
+        let cmd = new_entity_command(cmd_id);
+        let state = new_state(state, 1);
+        let result = handle_update_install(&cmd, None, Some(&state));
+
+        assert!(
+            matches!(result, Err(ServiceError::BadRequest(_))),
+            "Invalid request must return BadRequest, but got: {:?}",
+            result
+        );
+    }
+}
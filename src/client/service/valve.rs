@@ -0,0 +1,95 @@
+// Copyright (c) 2023 Unfolded Circle ApS, Markus Zehnder <markus.z@unfoldedcircle.com>
+// SPDX-License-Identifier: MPL-2.0
+
+//! Valve entity specific HA service call logic.
+
+use crate::client::service::{cmd_from_str, get_required_params};
+use crate::errors::ServiceError;
+use serde_json::{json, Value};
+use uc_api::intg::EntityCommand;
+use uc_api::CoverCommand;
+
+pub(crate) fn handle_valve(msg: &EntityCommand) -> Result<(String, Option<Value>), ServiceError> {
+    let cmd: CoverCommand = cmd_from_str(&msg.cmd_id)?;
+
+    let result = match cmd {
+        CoverCommand::Open => ("open_valve".into(), None),
+        CoverCommand::Close => ("close_valve".into(), None),
+        CoverCommand::Stop => ("stop_valve".into(), None),
+        CoverCommand::Position => {
+            let params = get_required_params(msg)?;
+            if let Some(pos @ 0..=100) = params.get("position").and_then(|v| v.as_u64()) {
+                (
+                    "set_valve_position".into(),
+                    Some(json!({ "position": pos })),
+                )
+            } else {
+                return Err(ServiceError::BadRequest(
+                    "Invalid or missing params.position attribute. Valid: 0..100".into(),
+                ));
+            }
+        }
+    };
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::client::service::valve::handle_valve;
+    use crate::errors::ServiceError;
+    use rstest::rstest;
+    use serde_json::{json, Value};
+    use uc_api::intg::EntityCommand;
+    use uc_api::EntityType;
+
+    fn new_entity_command(cmd_id: impl Into<String>, params: Value) -> EntityCommand {
+        EntityCommand {
+            device_id: None,
+            entity_type: EntityType::Cover,
+            entity_id: "valve.garden_irrigation".into(),
+            cmd_id: cmd_id.into(),
+            params: params.as_object().cloned(),
+        }
+    }
+
+    #[rstest]
+    #[case("open", "open_valve")]
+    #[case("close", "close_valve")]
+    #[case("stop", "stop_valve")]
+    fn simple_cmd_returns_service_without_data(#[case] cmd_id: &str, #[case] service: &str) {
+        let cmd = new_entity_command(cmd_id, Value::Null);
+        let result = handle_valve(&cmd);
+
+        assert_eq!(Ok((service.to_string(), None)), result);
+    }
+
+    #[test]
+    fn position_cmd_returns_set_valve_position() {
+        let cmd = new_entity_command("position", json!({ "position": 40 }));
+        let result = handle_valve(&cmd);
+
+        assert_eq!(
+            Ok((
+                "set_valve_position".to_string(),
+                Some(json!({ "position": 40 }))
+            )),
+            result
+        );
+    }
+
+    #[rstest]
+    #[case(Value::Null)]
+    #[case(json!({ "position": 101 }))]
+    #[case(json!({ "position": "50" }))]
+    fn position_cmd_with_invalid_params_returns_bad_request(#[case] params: Value) {
+        let cmd = new_entity_command("position", params);
+        let result = handle_valve(&cmd);
+
+        assert!(
+            matches!(result, Err(ServiceError::BadRequest(_))),
+            "Invalid value must return BadRequest, but got: {:?}",
+            result
+        );
+    }
+}
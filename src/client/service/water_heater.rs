@@ -0,0 +1,284 @@
+// Copyright (c) 2023 Unfolded Circle ApS, Markus Zehnder <markus.z@unfoldedcircle.com>
+// SPDX-License-Identifier: MPL-2.0
+
+//! Water heater entity specific HA service call logic.
+
+use crate::client::entity::{
+    temperature_to_ha, TemperatureConversion, WATER_HEATER_SUPPORT_ON_OFF,
+};
+use crate::client::model::EventState;
+use crate::client::service::{cmd_from_str, get_required_params};
+use crate::errors::ServiceError;
+use serde_json::{json, Value};
+use std::str::FromStr;
+use strum_macros::{EnumString, EnumVariantNames};
+use uc_api::intg::EntityCommand;
+use uc_api::ClimateCommand;
+
+/// Water heater specific commands in addition to the climate entity commands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumString, EnumVariantNames)]
+#[strum(serialize_all = "snake_case")]
+pub enum WaterHeaterCommand {
+    OperationMode,
+    AwayMode,
+}
+
+pub(crate) fn handle_water_heater(
+    msg: &EntityCommand,
+    state: Option<&EventState>,
+    conversion: Option<TemperatureConversion>,
+) -> Result<(String, Option<Value>), ServiceError> {
+    if let Ok(cmd) = WaterHeaterCommand::from_str(&msg.cmd_id) {
+        let params = get_required_params(msg)?;
+        let result = match cmd {
+            WaterHeaterCommand::OperationMode => {
+                let mode = match params.get("operation_mode").and_then(|v| v.as_str()) {
+                    Some(mode) if !mode.is_empty() => mode,
+                    _ => {
+                        return Err(ServiceError::BadRequest(
+                            "Invalid or missing params.operation_mode attribute".into(),
+                        ))
+                    }
+                };
+                (
+                    "set_operation_mode".into(),
+                    Some(json!({ "operation_mode": mode })),
+                )
+            }
+            WaterHeaterCommand::AwayMode => {
+                let away_mode = params
+                    .get("away_mode")
+                    .and_then(|v| v.as_bool())
+                    .ok_or_else(|| {
+                        ServiceError::BadRequest(
+                            "Invalid or missing params.away_mode attribute".into(),
+                        )
+                    })?;
+                (
+                    "set_away_mode".into(),
+                    Some(json!({ "away_mode": away_mode })),
+                )
+            }
+        };
+        return Ok(result);
+    }
+
+    let cmd: ClimateCommand = cmd_from_str(&msg.cmd_id)?;
+
+    let result = match cmd {
+        ClimateCommand::On => water_heater_on_off(true, state)?,
+        ClimateCommand::Off => water_heater_on_off(false, state)?,
+        ClimateCommand::HvacMode => {
+            let params = get_required_params(msg)?;
+            match params.get("hvac_mode").and_then(|v| v.as_str()) {
+                Some("OFF") => water_heater_on_off(false, state)?,
+                Some("HEAT") | Some("AUTO") => water_heater_on_off(true, state)?,
+                mode => {
+                    return Err(ServiceError::BadRequest(format!(
+                        "Invalid or missing params.hvac_mode attribute: {}. Valid: OFF, HEAT, AUTO",
+                        mode.unwrap_or_default()
+                    )));
+                }
+            }
+        }
+        ClimateCommand::TargetTemperature => {
+            let params = get_required_params(msg)?;
+            if let Some(temp) = params.get("temperature").and_then(|v| v.as_f64()) {
+                let ha_attr = state.and_then(|s| s.attributes.as_ref());
+                (
+                    "set_temperature".into(),
+                    Some(json!({ "temperature": temperature_to_ha(temp, conversion, ha_attr) })),
+                )
+            } else {
+                return Err(ServiceError::BadRequest(
+                    "Invalid or missing params.temperature attribute".into(),
+                ));
+            }
+        }
+    };
+
+    Ok(result)
+}
+
+/// Turn the water heater on or off.
+///
+/// Older water heater integrations don't support `turn_on` and `turn_off`, but provide an `off`
+/// operation mode. These are turned on with the first other operation mode.
+fn water_heater_on_off(
+    on: bool,
+    state: Option<&EventState>,
+) -> Result<(String, Option<Value>), ServiceError> {
+    let ha_attr = match state.and_then(|s| s.attributes.as_ref()) {
+        Some(ha_attr) => ha_attr,
+        None => {
+            let service = if on { "turn_on" } else { "turn_off" };
+            return Ok((service.into(), None));
+        }
+    };
+    let supported_features = ha_attr
+        .get("supported_features")
+        .and_then(|v| v.as_u64())
+        .unwrap_or_default() as u32;
+    if supported_features & WATER_HEATER_SUPPORT_ON_OFF > 0 {
+        let service = if on { "turn_on" } else { "turn_off" };
+        return Ok((service.into(), None));
+    }
+
+    let mode = if on {
+        ha_attr
+            .get("operation_list")
+            .and_then(|v| v.as_array())
+            .and_then(|list| {
+                list.iter()
+                    .filter_map(|v| v.as_str())
+                    .find(|mode| *mode != "off")
+            })
+            .ok_or_else(|| {
+                ServiceError::BadRequest("Water heater has no operation mode to turn on".into())
+            })?
+    } else {
+        "off"
+    };
+
+    Ok((
+        "set_operation_mode".into(),
+        Some(json!({ "operation_mode": mode })),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::client::model::EventState;
+    use crate::client::service::water_heater::handle_water_heater;
+    use crate::errors::ServiceError;
+    use rstest::rstest;
+    use serde_json::{json, Value};
+    use uc_api::intg::EntityCommand;
+    use uc_api::EntityType;
+
+    fn new_entity_command(cmd_id: impl Into<String>, params: Value) -> EntityCommand {
+        EntityCommand {
+            device_id: None,
+            entity_type: EntityType::Climate,
+            entity_id: "water_heater.boiler".into(),
+            cmd_id: cmd_id.into(),
+            params: params.as_object().cloned(),
+        }
+    }
+
+    #[rstest]
+    #[case("on", Value::Null, "turn_on")]
+    #[case("off", Value::Null, "turn_off")]
+    #[case("hvac_mode", json!({ "hvac_mode": "OFF" }), "turn_off")]
+    #[case("hvac_mode", json!({ "hvac_mode": "HEAT" }), "turn_on")]
+    #[case("hvac_mode", json!({ "hvac_mode": "AUTO" }), "turn_on")]
+    fn on_off_cmd_returns_service_without_data(
+        #[case] cmd_id: &str,
+        #[case] params: Value,
+        #[case] service: &str,
+    ) {
+        let cmd = new_entity_command(cmd_id, params);
+        let result = handle_water_heater(&cmd, None, None);
+
+        assert_eq!(Ok((service.to_string(), None)), result);
+    }
+
+    fn new_state(attributes: Value) -> EventState {
+        EventState {
+            state: "eco".into(),
+            attributes: attributes.as_object().cloned(),
+        }
+    }
+
+    #[rstest]
+    #[case("on", Value::Null, "turn_on")]
+    #[case("off", Value::Null, "turn_off")]
+    #[case("hvac_mode", json!({ "hvac_mode": "OFF" }), "turn_off")]
+    #[case("hvac_mode", json!({ "hvac_mode": "HEAT" }), "turn_on")]
+    fn on_off_cmd_with_on_off_support_returns_turn_on_off(
+        #[case] cmd_id: &str,
+        #[case] params: Value,
+        #[case] service: &str,
+    ) {
+        let cmd = new_entity_command(cmd_id, params);
+        let state = new_state(json!({
+            "supported_features": 15,
+            "operation_list": ["off", "eco", "electric"]
+        }));
+        let result = handle_water_heater(&cmd, Some(&state), None);
+
+        assert_eq!(Ok((service.to_string(), None)), result);
+    }
+
+    #[rstest]
+    #[case("on", Value::Null, "eco")]
+    #[case("off", Value::Null, "off")]
+    #[case("hvac_mode", json!({ "hvac_mode": "OFF" }), "off")]
+    #[case("hvac_mode", json!({ "hvac_mode": "HEAT" }), "eco")]
+    #[case("hvac_mode", json!({ "hvac_mode": "AUTO" }), "eco")]
+    fn on_off_cmd_without_on_off_support_returns_operation_mode(
+        #[case] cmd_id: &str,
+        #[case] params: Value,
+        #[case] operation_mode: &str,
+    ) {
+        let cmd = new_entity_command(cmd_id, params);
+        let state = new_state(json!({
+            "supported_features": 7,
+            "operation_list": ["off", "eco", "electric"]
+        }));
+        let result = handle_water_heater(&cmd, Some(&state), None);
+
+        assert_eq!(
+            Ok((
+                "set_operation_mode".to_string(),
+                Some(json!({ "operation_mode": operation_mode }))
+            )),
+            result
+        );
+    }
+
+    #[test]
+    fn on_cmd_without_on_off_support_and_operation_mode_returns_bad_request() {
+        let cmd = new_entity_command("on", Value::Null);
+        let state = new_state(json!({ "supported_features": 3, "operation_list": ["off"] }));
+        let result = handle_water_heater(&cmd, Some(&state), None);
+
+        assert!(
+            matches!(result, Err(ServiceError::BadRequest(_))),
+            "Missing operation mode must return BadRequest, but got: {:?}",
+            result
+        );
+    }
+
+    #[rstest]
+    #[case("target_temperature", json!({ "temperature": 55.5 }), "set_temperature")]
+    #[case("operation_mode", json!({ "operation_mode": "eco" }), "set_operation_mode")]
+    #[case("away_mode", json!({ "away_mode": true }), "set_away_mode")]
+    fn cmd_with_params_returns_service_data(
+        #[case] cmd_id: &str,
+        #[case] params: Value,
+        #[case] service: &str,
+    ) {
+        let cmd = new_entity_command(cmd_id, params.clone());
+        let result = handle_water_heater(&cmd, None, None);
+
+        assert_eq!(Ok((service.to_string(), Some(params))), result);
+    }
+
+    #[rstest]
+    #[case("hvac_mode", json!({ "hvac_mode": "COOL" }))]
+    #[case("target_temperature", Value::Null)]
+    #[case("operation_mode", json!({ "operation_mode": "" }))]
+    #[case("away_mode", json!({ "away_mode": "on" }))]
+    #[case("fan_mode", json!({ "fan_mode": "low" }))]
+    fn invalid_request_returns_bad_request(#[case] cmd_id: &str, #[case] params: Value) {
+        let cmd = new_entity_command(cmd_id, params);
+        let result = handle_water_heater(&cmd, None, None);
+
+        assert!(
+            matches!(result, Err(ServiceError::BadRequest(_))),
+            "Invalid value must return BadRequest, but got: {:?}",
+            result
+        );
+    }
+}
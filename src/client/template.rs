@@ -0,0 +1,101 @@
+// Copyright (c) 2023 Unfolded Circle ApS, Markus Zehnder <markus.z@unfoldedcircle.com>
+// SPDX-License-Identifier: MPL-2.0
+
+//! Template sensor handling with HA `render_template` subscriptions.
+//!
+//! See <https://developers.home-assistant.io/docs/api/websocket/#render-template> for further
+//! information.
+
+use actix::Context;
+use log::{debug, error, warn};
+use serde_json::{json, Map, Value};
+
+use crate::client::entity::{template_event_value, template_sensor_entity_change};
+use crate::client::messages::EntityEvent;
+use crate::client::HomeAssistantClient;
+
+impl HomeAssistantClient {
+    /// Subscribe to all configured template sensors.
+    ///
+    /// Each template is rendered in a separate subscription, identified by the request id.
+    pub(crate) fn subscribe_templates(&mut self, ctx: &mut Context<HomeAssistantClient>) {
+        for index in 0..self.entity_settings.template_sensors.len() {
+            let id = self.new_msg_id();
+            let template = self.entity_settings.template_sensors[index]
+                .template
+                .clone();
+            self.template_subscriptions.insert(id, index);
+            if let Err(e) = self.send_json(
+                json!({
+                    "id": id,
+                    "type": "render_template",
+                    "template": template,
+                    "report_errors": true
+                }),
+                ctx,
+            ) {
+                error!("[{}] Error sending render_template to HA: {:?}", self.id, e);
+                return;
+            }
+        }
+    }
+
+    /// Handle the result message of a `render_template` subscription request.
+    pub(crate) fn handle_template_result(
+        &mut self,
+        index: usize,
+        success: bool,
+        msg: &Map<String, Value>,
+    ) {
+        if success {
+            return;
+        }
+        error!(
+            "[{}] Template sensor subscription failed: {:?}",
+            self.id,
+            msg.get("error")
+        );
+        self.update_template_sensor(index, None);
+    }
+
+    /// Handle a `render_template` subscription event with the rendered template or an error.
+    pub(crate) fn handle_template_event(&mut self, index: usize, event: Value) {
+        match template_event_value(&event) {
+            Ok(Some(value)) => self.update_template_sensor(index, Some(value)),
+            Ok(None) => warn!("[{}] Template sensor warning: {:?}", self.id, event),
+            Err(e) => {
+                error!("[{}] Template sensor error: {e}", self.id);
+                self.update_template_sensor(index, None);
+            }
+        }
+    }
+
+    /// Store the rendered template value and send the sensor change. `None` marks the sensor as
+    /// unavailable.
+    fn update_template_sensor(&mut self, index: usize, value: Option<Value>) {
+        let settings = match self.entity_settings.template_sensors.get(index) {
+            Some(settings) => settings,
+            None => return,
+        };
+        debug!(
+            "[{}] Template sensor {} = {:?}",
+            self.id, settings.id, value
+        );
+
+        let entity_change = template_sensor_entity_change(settings, value.clone());
+        match value {
+            Some(value) => self.template_values.insert(index, value),
+            None => self.template_values.remove(&index),
+        };
+
+        if let Err(e) = self.controller_actor.try_send(EntityEvent {
+            client_id: self.id.clone(),
+            entity_change,
+        }) {
+            error!(
+                "[{}] Error sending template sensor change: {:?}",
+                self.id, e
+            );
+        }
+    }
+}
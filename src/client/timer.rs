@@ -0,0 +1,63 @@
+// Copyright (c) 2023 Unfolded Circle ApS, Markus Zehnder <markus.z@unfoldedcircle.com>
+// SPDX-License-Identifier: MPL-2.0
+
+//! Timer entity countdown.
+//!
+//! HA doesn't send state changes while a timer is running. The remaining time of active timers is
+//! calculated and sent every second.
+
+use std::time::Duration;
+
+use actix::{AsyncContext, Context};
+use log::error;
+use serde_json::{Map, Value};
+use time::OffsetDateTime;
+
+use crate::client::entity::{timer_countdown_entity_change, timer_finishes_at};
+use crate::client::messages::EntityEvent;
+use crate::client::HomeAssistantClient;
+
+/// Countdown update interval of active timers.
+const TIMER_COUNTDOWN_INTERVAL: Duration = Duration::from_secs(1);
+
+impl HomeAssistantClient {
+    /// Start the countdown interval for active timer entities.
+    pub(crate) fn timer_countdown(&self, ctx: &mut Context<Self>) {
+        ctx.run_interval(TIMER_COUNTDOWN_INTERVAL, |act, _ctx| {
+            if act.active_timers.is_empty() {
+                return;
+            }
+
+            let now = OffsetDateTime::now_utc();
+            // the finished state is sent by HA
+            act.active_timers
+                .retain(|_, finishes_at| *finishes_at > now);
+
+            for (entity_id, finishes_at) in act.active_timers.iter() {
+                if let Err(e) = act.controller_actor.try_send(EntityEvent {
+                    client_id: act.id.clone(),
+                    entity_change: timer_countdown_entity_change(entity_id, *finishes_at, now),
+                }) {
+                    error!("[{}] Error sending timer countdown: {:?}", act.id, e);
+                }
+            }
+        });
+    }
+
+    /// Add an active timer for the countdown, or remove it if the timer is no longer active.
+    pub(crate) fn update_active_timer(
+        &mut self,
+        entity_id: &str,
+        state: &str,
+        ha_attr: Option<&Map<String, Value>>,
+    ) {
+        match ha_attr.and_then(timer_finishes_at) {
+            Some(finishes_at) if state == "active" => {
+                self.active_timers.insert(entity_id.into(), finishes_at);
+            }
+            _ => {
+                self.active_timers.remove(entity_id);
+            }
+        }
+    }
+}
@@ -0,0 +1,153 @@
+// Copyright (c) 2023 Unfolded Circle ApS, Markus Zehnder <markus.z@unfoldedcircle.com>
+// SPDX-License-Identifier: MPL-2.0
+
+//! Weather entity handling.
+//!
+//! A HA weather entity is split into multiple sensor entities and the optional weather forecast
+//! is retrieved with the `weather.get_forecasts` service.
+
+use actix::Context;
+use log::{debug, error, warn};
+use serde_json::{Map, Value};
+
+use crate::client::entity::{
+    convert_forecast_temperatures, weather_event_to_entity_changes, weather_forecast_entity_change,
+    weather_temperature_unit,
+};
+use crate::client::messages::EntityEvent;
+use crate::client::model::{CallServiceMsg, EventData, Target};
+use crate::client::HomeAssistantClient;
+use crate::configuration::ForecastType;
+use crate::errors::ServiceError;
+
+impl HomeAssistantClient {
+    /// Handle a weather entity `state_changed` event.
+    ///
+    /// Each weather attribute is sent as a separate sensor entity change. If the forecast is enabled
+    /// for the entity, an updated forecast is requested, unless a request is still pending.
+    pub(crate) fn handle_weather_event(
+        &mut self,
+        mut data: EventData,
+        ctx: &mut Context<HomeAssistantClient>,
+    ) -> Result<(), ServiceError> {
+        let entity_id = data.entity_id.clone();
+        self.entity_states
+            .insert(entity_id.clone(), data.new_state.clone());
+        self.convert_temperatures(
+            "weather",
+            &mut data.new_state.state,
+            data.new_state.attributes.as_mut(),
+        );
+
+        let sensors = self
+            .weather_sensors
+            .get(&entity_id)
+            .cloned()
+            .unwrap_or_default();
+        for entity_change in weather_event_to_entity_changes(data, &sensors)? {
+            self.controller_actor.try_send(EntityEvent {
+                client_id: self.id.clone(),
+                entity_change,
+            })?;
+        }
+
+        if let Some(forecast) = self
+            .entity_settings
+            .weather
+            .get(&entity_id)
+            .and_then(|s| s.forecast)
+        {
+            if self.forecast_requests.values().any(|id| id == &entity_id) {
+                debug!(
+                    "[{}] Skipping forecast request for {entity_id}: request pending",
+                    self.id
+                );
+            } else {
+                self.request_weather_forecast(entity_id, forecast, ctx)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Request the weather forecast of a weather entity.
+    ///
+    /// The response is handled in [`Self::handle_weather_forecast_result`].
+    pub(crate) fn request_weather_forecast(
+        &mut self,
+        entity_id: String,
+        forecast: ForecastType,
+        ctx: &mut Context<HomeAssistantClient>,
+    ) -> Result<(), ServiceError> {
+        let id = self.new_msg_id();
+        let call_srv_msg = CallServiceMsg {
+            id,
+            msg_type: "call_service".to_string(),
+            domain: "weather".to_string(),
+            service: "get_forecasts".to_string(),
+            service_data: Some(serde_json::json!({ "type": forecast })),
+            target: Target {
+                entity_id: entity_id.clone(),
+            },
+            return_response: Some(true),
+        };
+
+        self.forecast_requests.insert(id, entity_id);
+        let msg = serde_json::to_value(call_srv_msg)?;
+        self.send_json(msg, ctx)
+    }
+
+    /// Handle the `weather.get_forecasts` result message and send the forecast sensor change.
+    pub(crate) fn handle_weather_forecast_result(
+        &mut self,
+        entity_id: &str,
+        success: bool,
+        msg: &mut Map<String, Value>,
+    ) {
+        if !success {
+            error!(
+                "[{}] get_forecasts request for {entity_id} failed: {:?}",
+                self.id,
+                msg.get("error")
+            );
+            return;
+        }
+
+        // result.response.<entity_id>.forecast: [...]
+        let mut forecast = match msg
+            .get_mut("result")
+            .and_then(|v| v.get_mut("response"))
+            .and_then(|v| v.get_mut(entity_id))
+            .and_then(|v| v.get_mut("forecast"))
+            .and_then(|v| v.as_array_mut())
+        {
+            Some(forecast) => std::mem::take(forecast),
+            None => {
+                warn!(
+                    "[{}] Missing forecast data in get_forecasts result for {entity_id}",
+                    self.id
+                );
+                return;
+            }
+        };
+
+        // the forecast is in the temperature unit of the weather entity
+        let weather_unit = self
+            .entity_states
+            .get(entity_id)
+            .and_then(|s| s.attributes.as_ref())
+            .and_then(weather_temperature_unit);
+        if let (Some(weather_unit), Some(unit)) =
+            (weather_unit, self.entity_settings.temperature_unit)
+        {
+            convert_forecast_temperatures(&mut forecast, weather_unit, unit);
+        }
+
+        if let Err(e) = self.controller_actor.try_send(EntityEvent {
+            client_id: self.id.clone(),
+            entity_change: weather_forecast_entity_change(entity_id, forecast),
+        }) {
+            error!("[{}] Error sending weather forecast: {:?}", self.id, e);
+        }
+    }
+}
@@ -59,6 +59,8 @@ pub const ENV_DISABLE_CERT_VERIFICATION: &str = "UC_DISABLE_CERT_VERIFICATION";
 pub struct Settings {
     pub integration: IntegrationSettings,
     pub hass: HomeAssistantSettings,
+    #[serde(default)]
+    pub entities: EntitySettings,
 }
 
 #[derive(serde::Deserialize, serde::Serialize)]
@@ -130,6 +132,190 @@ impl Default for HomeAssistantSettings {
     }
 }
 
+/// Optional Home Assistant entity specific settings.
+///
+/// These settings are only read from the configuration file and are not part of the driver setup.
+#[derive(Clone, Default, serde::Deserialize, serde::Serialize)]
+pub struct EntitySettings {
+    /// Remote entity settings. Key: HA `remote` entity_id
+    #[serde(default)]
+    pub remotes: HashMap<String, RemoteSettings>,
+    /// Light entity settings. Key: HA `light` entity_id
+    #[serde(default)]
+    pub lights: HashMap<String, LightSettings>,
+    /// Media player entity settings. Key: HA `media_player` entity_id
+    #[serde(default)]
+    pub media_players: HashMap<String, MediaPlayerSettings>,
+    /// Weather entity settings. Key: HA `weather` entity_id
+    #[serde(default)]
+    pub weather: HashMap<String, WeatherSettings>,
+    /// Update entity settings. Key: HA `update` entity_id
+    #[serde(default)]
+    pub updates: HashMap<String, UpdateSettings>,
+    /// Camera snapshot settings. Key: HA `camera` entity_id
+    #[serde(default)]
+    pub cameras: HashMap<String, CameraSettings>,
+    /// Event entity settings. Key: HA `event` entity_id
+    #[serde(default)]
+    pub events: HashMap<String, EventEntitySettings>,
+    /// Preferred temperature unit of climate entities, weather entities and temperature sensors.
+    /// Attribute sensors are not converted. Default: unit system of Home Assistant.
+    pub temperature_unit: Option<TemperatureUnit>,
+    /// List the member entity_ids of group entities in the `members` entity option.
+    #[serde(default)]
+    pub group_members: bool,
+    /// Additional sensor entities created from entity attributes.
+    #[serde(default)]
+    pub attribute_sensors: Vec<AttributeSensorSettings>,
+    /// Virtual sensor entities rendered from HA templates.
+    #[serde(default)]
+    pub template_sensors: Vec<TemplateSensorSettings>,
+}
+
+/// Home Assistant `remote` entity settings.
+#[derive(Clone, Default, serde::Deserialize, serde::Serialize)]
+pub struct RemoteSettings {
+    /// Command names exposed as simple commands. Sent as `command` in `remote.send_command`.
+    #[serde(default)]
+    pub commands: Vec<String>,
+    /// Optional `device` parameter for `remote.send_command`, e.g. a Harmony or Broadlink device.
+    pub device: Option<String>,
+}
+
+/// Home Assistant `light` entity settings.
+#[derive(Clone, Default, serde::Deserialize, serde::Serialize)]
+pub struct LightSettings {
+    /// Default transition in seconds for light commands without a `transition` parameter.
+    pub transition: Option<f64>,
+}
+
+/// Home Assistant `media_player` entity settings.
+#[derive(Clone, Default, serde::Deserialize, serde::Serialize)]
+pub struct MediaPlayerSettings {
+    /// Volume step in percent for the volume up and down commands. If set, the volume is always
+    /// changed with `volume_set` instead of the volume step of the media player.
+    pub volume_step: Option<u8>,
+    /// Seek step in seconds for the fast-forward and rewind commands.
+    pub seek_step: Option<u32>,
+    /// Associated HA `remote` entity for navigation commands. Default: remote entity with the same
+    /// object id, e.g. `remote.living_room_tv` for `media_player.living_room_tv`.
+    pub remote: Option<String>,
+    /// Key map of the HA integration for navigation commands. The key map is not derived from the
+    /// remote entity: it's required for navigation commands, unless all keys are defined in `keys`.
+    pub keymap: Option<RemoteKeyMap>,
+    /// Custom key names of navigation commands, overriding the key map.
+    /// Key: command name, e.g. `cursor_up`.
+    #[serde(default)]
+    pub keys: HashMap<String, String>,
+}
+
+/// Built-in key maps for media player navigation commands.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RemoteKeyMap {
+    /// Android TV Remote integration.
+    AndroidTv,
+    /// Apple TV integration.
+    AppleTv,
+    /// Samsung Smart TV integration.
+    SamsungTv,
+    /// Roku integration.
+    Roku,
+    /// LG webOS TV integration. Without remote entity, the `webostv.button` service is used.
+    LgWebos,
+}
+
+/// Home Assistant `weather` entity settings.
+#[derive(Clone, Default, serde::Deserialize, serde::Serialize)]
+pub struct WeatherSettings {
+    /// Retrieve the weather forecast of the given type with the `weather.get_forecasts` service.
+    pub forecast: Option<ForecastType>,
+}
+
+/// Sensor entity created from an attribute of a Home Assistant entity.
+///
+/// The sensor is updated whenever the state of the parent entity changes.
+#[derive(Clone, Default, serde::Deserialize, serde::Serialize)]
+pub struct AttributeSensorSettings {
+    /// HA entity_id of the parent entity. Any entity type can be used.
+    pub entity_id: String,
+    /// Attribute name. Nested values are accessed with a dot separated path, e.g. `forecast.0.temperature`.
+    pub attribute: String,
+    /// Optional sensor name. Default: parent entity name with the attribute label.
+    pub name: Option<String>,
+    /// Optional sensor label. Default: attribute name.
+    pub label: Option<String>,
+    /// Optional unit of measurement.
+    pub unit: Option<String>,
+}
+
+/// Virtual sensor entity with a value rendered from a HA template.
+///
+/// The template is rendered in HA with a `render_template` subscription and updated whenever a
+/// referenced entity changes.
+#[derive(Clone, Default, serde::Deserialize, serde::Serialize)]
+pub struct TemplateSensorSettings {
+    /// Unique sensor identifier. The sensor entity_id is `template.<id>`.
+    pub id: String,
+    /// Sensor name.
+    pub name: String,
+    /// Jinja template, e.g. `{{ states.light | selectattr('state','eq','on') | list | count }}`
+    pub template: String,
+    /// Optional sensor label.
+    pub label: Option<String>,
+    /// Optional unit of measurement.
+    pub unit: Option<String>,
+}
+
+/// Home Assistant `update` entity settings.
+#[derive(Clone, Default, serde::Deserialize, serde::Serialize)]
+pub struct UpdateSettings {
+    /// Expose a button entity `<entity_id>.install` to install the update.
+    #[serde(default)]
+    pub install_button: bool,
+    /// Create a backup before installing the update, if supported by the update entity.
+    #[serde(default)]
+    pub backup: bool,
+}
+
+/// Home Assistant `camera` entity settings.
+#[derive(Clone, Default, serde::Deserialize, serde::Serialize)]
+pub struct CameraSettings {
+    /// Show the snapshot as media image of this media_player or sensor entity, instead of exposing
+    /// the camera as separate media player entity.
+    pub attach_to: Option<String>,
+    /// Snapshot refresh interval in seconds. 0 = only refresh when HA rotates the access token.
+    #[serde(default)]
+    pub refresh_interval: u64,
+    /// Refresh the snapshot whenever this entity changes to `on`, e.g. a motion binary_sensor.
+    pub motion_entity: Option<String>,
+}
+
+/// Home Assistant `event` entity settings.
+#[derive(Clone, Default, serde::Deserialize, serde::Serialize)]
+pub struct EventEntitySettings {
+    /// Send an `entity_notification` event to the remote whenever the event fires.
+    #[serde(default)]
+    pub notification: bool,
+}
+
+/// Weather forecast type of the `weather.get_forecasts` service.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ForecastType {
+    Daily,
+    Hourly,
+    TwiceDaily,
+}
+
+/// Temperature unit for the temperature conversion of climate entities and sensors.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TemperatureUnit {
+    Celsius,
+    Fahrenheit,
+}
+
 #[serde_as]
 #[derive(Clone, serde::Deserialize, serde::Serialize)]
 pub struct ReconnectSettings {
@@ -73,6 +73,7 @@ impl Handler<ConnectMsg> for Controller {
         let token = self.settings.hass.token.clone();
         let client_address = ctx.address();
         let heartbeat = self.settings.hass.heartbeat;
+        let entity_settings = self.settings.entities.clone();
 
         Box::pin(
             async move {
@@ -88,8 +89,15 @@ impl Handler<ConnectMsg> for Controller {
                 info!("Connected to: {url} ({heartbeat})");
 
                 let (sink, stream) = framed.split();
-                let addr =
-                    HomeAssistantClient::start(url, client_address, token, sink, stream, heartbeat);
+                let addr = HomeAssistantClient::start(
+                    url,
+                    client_address,
+                    token,
+                    sink,
+                    stream,
+                    heartbeat,
+                    entity_settings,
+                );
 
                 Ok(addr)
             }
@@ -3,13 +3,14 @@
 
 //! Actix message handler for Home Assistant events.
 
-use crate::client::messages::{AvailableEntities, EntityEvent};
+use crate::client::messages::{AvailableEntities, EntityEvent, EntityNotification};
 use crate::controller::handler::{SubscribeHaEventsMsg, UnsubscribeHaEventsMsg};
 use crate::controller::{Controller, OperationModeState, SendWsMessage};
 use crate::errors::ServiceError;
 use crate::util::DeserializeMsgData;
 use actix::Handler;
 use log::{debug, error};
+use serde_json::json;
 use uc_api::intg::ws::AvailableEntitiesMsgData;
 use uc_api::intg::{EntityChange, SubscribeEvents};
 use uc_api::ws::{EventCategory, WsMessage};
@@ -30,6 +31,27 @@ impl Handler<EntityEvent> for Controller {
     }
 }
 
+impl Handler<EntityNotification> for Controller {
+    type Result = ();
+
+    fn handle(&mut self, msg: EntityNotification, _ctx: &mut Self::Context) -> Self::Result {
+        let msg_data = json!({
+            "entity_id": msg.entity_id,
+            "data": msg.data
+        });
+        for session in self.sessions.keys() {
+            self.send_r2_msg(
+                WsMessage::event(
+                    "entity_notification",
+                    EventCategory::Entity,
+                    msg_data.clone(),
+                ),
+                session,
+            );
+        }
+    }
+}
+
 impl Handler<AvailableEntities> for Controller {
     type Result = ();
 
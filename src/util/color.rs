@@ -0,0 +1,443 @@
+// Copyright (c) 2023 Unfolded Circle ApS, Markus Zehnder <markus.z@unfoldedcircle.com>
+// SPDX-License-Identifier: MPL-2.0
+
+//! Color conversion functions between the different HA light color modes.
+//!
+//! The conversions follow the implementation in Home Assistant `homeassistant/util/color.py` to
+//! get the same results as the HA frontend.
+
+/// Default minimal color temperature in Kelvin of a HA light.
+pub const DEFAULT_MIN_KELVIN: u16 = 2000;
+/// Default maximal color temperature in Kelvin of a HA light.
+pub const DEFAULT_MAX_KELVIN: u16 = 6535;
+/// Default minimal color temperature in mireds of a HA light.
+pub const DEFAULT_MIN_MIREDS: u16 = 153;
+/// Default maximal color temperature in mireds of a HA light.
+pub const DEFAULT_MAX_MIREDS: u16 = 500;
+
+/// Convert an RGB color to hue (0..360), saturation (0..100) and value (0..100).
+pub fn rgb_to_hsv(r: u8, g: u8, b: u8) -> (f64, f64, f64) {
+    let (r, g, b) = (r as f64 / 255.0, g as f64 / 255.0, b as f64 / 255.0);
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    if max == min {
+        return (0.0, 0.0, round3(max * 100.0));
+    }
+
+    let delta = max - min;
+    let rc = (max - r) / delta;
+    let gc = (max - g) / delta;
+    let bc = (max - b) / delta;
+    let h = if r == max {
+        bc - gc
+    } else if g == max {
+        2.0 + rc - bc
+    } else {
+        4.0 + gc - rc
+    };
+    let h = (h / 6.0).rem_euclid(1.0);
+
+    (
+        round3(h * 360.0),
+        round3(delta / max * 100.0),
+        round3(max * 100.0),
+    )
+}
+
+/// Convert hue (0..360), saturation (0..100) and value (0..100) to an RGB color.
+pub fn hsv_to_rgb(h: f64, s: f64, v: f64) -> (u8, u8, u8) {
+    let (h, s, v) = (h / 360.0, s / 100.0, v / 100.0);
+    let (r, g, b) = if s == 0.0 {
+        (v, v, v)
+    } else {
+        let i = (h * 6.0).floor();
+        let f = h * 6.0 - i;
+        let p = v * (1.0 - s);
+        let q = v * (1.0 - s * f);
+        let t = v * (1.0 - s * (1.0 - f));
+        match (i as i64).rem_euclid(6) {
+            0 => (v, t, p),
+            1 => (q, v, p),
+            2 => (p, v, t),
+            3 => (p, q, v),
+            4 => (t, p, v),
+            _ => (v, p, q),
+        }
+    };
+
+    (to_u8(r * 255.0), to_u8(g * 255.0), to_u8(b * 255.0))
+}
+
+/// Convert hue (0..360) and saturation (0..100) to an RGB color with full brightness.
+pub fn hs_to_rgb(h: f64, s: f64) -> (u8, u8, u8) {
+    hsv_to_rgb(h, s, 100.0)
+}
+
+/// Convert a CIE 1931 xy color to hue (0..360) and saturation (0..100).
+pub fn xy_to_hs(x: f64, y: f64) -> (f64, f64) {
+    let (r, g, b) = xy_to_rgb(x, y);
+    let (h, s, _) = rgb_to_hsv(r, g, b);
+    (h, s)
+}
+
+/// Convert hue (0..360) and saturation (0..100) to a CIE 1931 xy color.
+pub fn hs_to_xy(h: f64, s: f64) -> (f64, f64) {
+    let (r, g, b) = hs_to_rgb(h, s);
+    rgb_to_xy(r, g, b)
+}
+
+/// Convert a CIE 1931 xy color to an RGB color with full brightness.
+fn xy_to_rgb(x: f64, y: f64) -> (u8, u8, u8) {
+    let brightness = 1.0;
+    let (cx, cz) = if y == 0.0 {
+        (0.0, 0.0)
+    } else {
+        (brightness / y * x, brightness / y * (1.0 - x - y))
+    };
+
+    // wide RGB D65 conversion
+    let r = cx * 1.656492 - brightness * 0.354851 - cz * 0.255038;
+    let g = -cx * 0.707196 + brightness * 1.655397 + cz * 0.036152;
+    let b = cx * 0.051713 - brightness * 0.121364 + cz * 1.011530;
+
+    // reverse gamma correction and negative components to zero
+    let [r, g, b] = [r, g, b].map(|c| {
+        let c = if c <= 0.0031308 {
+            12.92 * c
+        } else {
+            1.055 * c.powf(1.0 / 2.4) - 0.055
+        };
+        c.max(0.0)
+    });
+
+    // if one component is greater than 1, weight components by that value
+    let max = r.max(g).max(b);
+    let [r, g, b] = if max > 1.0 {
+        [r / max, g / max, b / max]
+    } else {
+        [r, g, b]
+    };
+
+    ((r * 255.0) as u8, (g * 255.0) as u8, (b * 255.0) as u8)
+}
+
+/// Convert an RGB color to a CIE 1931 xy color.
+fn rgb_to_xy(r: u8, g: u8, b: u8) -> (f64, f64) {
+    if r as u16 + g as u16 + b as u16 == 0 {
+        return (0.0, 0.0);
+    }
+
+    // gamma correction
+    let [r, g, b] = [r, g, b].map(|c| {
+        let c = c as f64 / 255.0;
+        if c > 0.04045 {
+            ((c + 0.055) / 1.055).powf(2.4)
+        } else {
+            c / 12.92
+        }
+    });
+
+    // wide RGB D65 conversion
+    let cx = r * 0.664511 + g * 0.154324 + b * 0.162028;
+    let cy = r * 0.283881 + g * 0.668433 + b * 0.047685;
+    let cz = r * 0.000088 + g * 0.072310 + b * 0.986039;
+
+    let sum = cx + cy + cz;
+    (round3(cx / sum), round3(cy / sum))
+}
+
+/// Convert an RGBW color to an RGB color by adding the white channel.
+pub fn rgbw_to_rgb(r: u8, g: u8, b: u8, w: u8) -> (u8, u8, u8) {
+    let rgb = [r, g, b].map(|c| c as f64 + w as f64);
+    let [r, g, b] = match_max_scale(&[r, g, b, w], rgb);
+    (r, g, b)
+}
+
+/// Convert an RGB color to an RGBW color by extracting the white channel.
+pub fn rgb_to_rgbw(r: u8, g: u8, b: u8) -> (u8, u8, u8, u8) {
+    let w = r.min(g).min(b);
+    let rgbw = [r - w, g - w, b - w, w].map(|c| c as f64);
+    let [r2, g2, b2, w] = match_max_scale(&[r, g, b], rgbw);
+    (r2, g2, b2, w)
+}
+
+/// Convert an RGBWW color to an RGB color by adding the cold and warm white channels.
+///
+/// The color temperature of the white channels is calculated from the color temperature range
+/// of the light.
+pub fn rgbww_to_rgb(rgbww: (u8, u8, u8, u8, u8), min_kelvin: u16, max_kelvin: u16) -> (u8, u8, u8) {
+    let (r, g, b, cw, ww) = rgbww;
+    let max_mireds = kelvin_to_mired(min_kelvin as f64);
+    let min_mireds = kelvin_to_mired(max_kelvin as f64);
+    let mired_range = max_mireds - min_mireds;
+
+    let ct_ratio = if cw as u16 + ww as u16 == 0 {
+        0.5
+    } else {
+        ww as f64 / (cw as f64 + ww as f64)
+    };
+    let color_temp_mired = min_mireds + ct_ratio * mired_range;
+    let color_temp_kelvin = if color_temp_mired > 0.0 {
+        mired_to_kelvin(color_temp_mired)
+    } else {
+        0.0
+    };
+
+    let (w_r, w_g, w_b) = color_temperature_to_rgb(color_temp_kelvin);
+    let white_level = cw.max(ww) as f64 / 255.0;
+    let rgb = [
+        r as f64 + w_r * white_level,
+        g as f64 + w_g * white_level,
+        b as f64 + w_b * white_level,
+    ];
+
+    let [r, g, b] = match_max_scale(&[r, g, b, cw, ww], rgb);
+    (r, g, b)
+}
+
+/// Convert an RGB color to an RGBWW color by extracting the white channels.
+///
+/// The white is extracted with the color temperature in the middle of the color temperature
+/// range of the light and equally distributed to the cold and warm white channels.
+pub fn rgb_to_rgbww(r: u8, g: u8, b: u8, min_kelvin: u16, max_kelvin: u16) -> (u8, u8, u8, u8, u8) {
+    let max_mireds = kelvin_to_mired(min_kelvin as f64);
+    let min_mireds = kelvin_to_mired(max_kelvin as f64);
+    let mired_midpoint = min_mireds + (max_mireds - min_mireds) / 2.0;
+    let (w_r, w_g, w_b) = color_temperature_to_rgb(mired_to_kelvin(mired_midpoint));
+
+    // ratio of the midpoint white in the input rgb channels
+    let ratio = |c: u8, w: f64| if w > 0.0 { c as f64 / w } else { 0.0 };
+    let white_level = ratio(r, w_r).min(ratio(g, w_g)).min(ratio(b, w_b));
+
+    let white = (white_level * 255.0).round();
+    let rgbww = [
+        r as f64 - w_r * white_level,
+        g as f64 - w_g * white_level,
+        b as f64 - w_b * white_level,
+        white,
+        white,
+    ];
+
+    let [r, g, b, cw, ww] = match_max_scale(&[r, g, b], rgbww);
+    (r, g, b, cw, ww)
+}
+
+/// Get the RGB color of a color temperature in Kelvin.
+///
+/// Based on the algorithm of Tanner Helland:
+/// <https://tannerhelland.com/2012/09/18/convert-temperature-rgb-algorithm-code.html>
+fn color_temperature_to_rgb(kelvin: f64) -> (f64, f64, f64) {
+    let t = kelvin.clamp(1000.0, 40000.0) / 100.0;
+
+    let red = if t <= 66.0 {
+        255.0
+    } else {
+        329.698727446 * (t - 60.0).powf(-0.1332047592)
+    };
+    let green = if t <= 66.0 {
+        99.4708025861 * t.ln() - 161.1195681661
+    } else {
+        288.1221695283 * (t - 60.0).powf(-0.0755148492)
+    };
+    let blue = if t >= 66.0 {
+        255.0
+    } else if t <= 19.0 {
+        0.0
+    } else {
+        138.5177312231 * (t - 10.0).ln() - 305.0447927307
+    };
+
+    (
+        red.clamp(0.0, 255.0),
+        green.clamp(0.0, 255.0),
+        blue.clamp(0.0, 255.0),
+    )
+}
+
+fn kelvin_to_mired(kelvin: f64) -> f64 {
+    (1_000_000.0 / kelvin).floor()
+}
+
+fn mired_to_kelvin(mired: f64) -> f64 {
+    (1_000_000.0 / mired).floor()
+}
+
+/// Scale the output colors to match the maximum value of the input colors.
+///
+/// This ensures that the output doesn't overflow and that the full channel range is used.
+fn match_max_scale<const N: usize>(input: &[u8], output: [f64; N]) -> [u8; N] {
+    let max_in = input.iter().copied().max().unwrap_or_default() as f64;
+    let max_out = output.iter().copied().fold(0.0, f64::max);
+    let factor = if max_out == 0.0 {
+        0.0
+    } else {
+        max_in / max_out
+    };
+    output.map(|c| to_u8(c * factor))
+}
+
+fn to_u8(value: f64) -> u8 {
+    value.round().clamp(0.0, 255.0) as u8
+}
+
+fn round3(value: f64) -> f64 {
+    (value * 1000.0).round() / 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn assert_close(expected: f64, actual: f64, tolerance: f64) {
+        assert!(
+            (expected - actual).abs() <= tolerance,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[rstest]
+    #[case((255, 0, 0), (0.0, 100.0, 100.0))]
+    #[case((0, 255, 0), (120.0, 100.0, 100.0))]
+    #[case((0, 0, 255), (240.0, 100.0, 100.0))]
+    #[case((255, 255, 255), (0.0, 0.0, 100.0))]
+    #[case((0, 0, 0), (0.0, 0.0, 0.0))]
+    #[case((192, 64, 32), (12.0, 83.333, 75.294))]
+    #[case((255, 0, 128), (329.882, 100.0, 100.0))]
+    fn rgb_to_hsv_returns_hue_saturation_value(
+        #[case] rgb: (u8, u8, u8),
+        #[case] expected: (f64, f64, f64),
+    ) {
+        assert_eq!(expected, rgb_to_hsv(rgb.0, rgb.1, rgb.2));
+    }
+
+    #[rstest]
+    #[case((0.0, 100.0, 100.0), (255, 0, 0))]
+    #[case((120.0, 100.0, 100.0), (0, 255, 0))]
+    #[case((240.0, 100.0, 100.0), (0, 0, 255))]
+    #[case((360.0, 100.0, 100.0), (255, 0, 0))]
+    #[case((0.0, 0.0, 100.0), (255, 255, 255))]
+    #[case((12.0, 83.333, 75.294), (192, 64, 32))]
+    fn hsv_to_rgb_returns_rgb(#[case] hsv: (f64, f64, f64), #[case] expected: (u8, u8, u8)) {
+        assert_eq!(expected, hsv_to_rgb(hsv.0, hsv.1, hsv.2));
+    }
+
+    #[rstest]
+    #[case((255, 0, 0))]
+    #[case((0, 255, 0))]
+    #[case((0, 0, 255))]
+    #[case((255, 128, 0))]
+    #[case((64, 224, 208))]
+    #[case((255, 255, 255))]
+    #[case((192, 64, 32))]
+    fn rgb_hsv_round_trip(#[case] rgb: (u8, u8, u8)) {
+        let (h, s, v) = rgb_to_hsv(rgb.0, rgb.1, rgb.2);
+        assert_eq!(rgb, hsv_to_rgb(h, s, v));
+    }
+
+    #[rstest]
+    #[case((0.701, 0.299), (0.0, 100.0))]
+    #[case((0.172, 0.747), (120.0, 100.0))]
+    #[case((0.136, 0.04), (242.6, 100.0))]
+    #[case((0.323, 0.329), (0.0, 0.0))]
+    fn xy_to_hs_returns_hue_saturation(#[case] xy: (f64, f64), #[case] expected: (f64, f64)) {
+        let (h, s) = xy_to_hs(xy.0, xy.1);
+        assert_close(expected.0, h, 1.5);
+        assert_close(expected.1, s, 1.5);
+    }
+
+    #[rstest]
+    #[case((0.0, 100.0), (0.701, 0.299))]
+    #[case((120.0, 100.0), (0.172, 0.747))]
+    #[case((240.0, 100.0), (0.136, 0.04))]
+    #[case((0.0, 0.0), (0.323, 0.329))]
+    fn hs_to_xy_returns_xy(#[case] hs: (f64, f64), #[case] expected: (f64, f64)) {
+        assert_eq!(expected, hs_to_xy(hs.0, hs.1));
+    }
+
+    #[rstest]
+    #[case((30.0, 100.0))]
+    #[case((60.0, 50.0))]
+    #[case((200.0, 80.0))]
+    #[case((300.0, 30.0))]
+    fn hs_xy_round_trip(#[case] hs: (f64, f64)) {
+        let (x, y) = hs_to_xy(hs.0, hs.1);
+        let (h, s) = xy_to_hs(x, y);
+        assert_close(hs.0, h, 2.0);
+        assert_close(hs.1, s, 2.0);
+    }
+
+    #[rstest]
+    #[case((255, 0, 0, 0), (255, 0, 0))]
+    #[case((0, 0, 0, 255), (255, 255, 255))]
+    #[case((128, 0, 0, 128), (128, 64, 64))]
+    #[case((0, 0, 0, 0), (0, 0, 0))]
+    fn rgbw_to_rgb_adds_white(#[case] rgbw: (u8, u8, u8, u8), #[case] expected: (u8, u8, u8)) {
+        assert_eq!(expected, rgbw_to_rgb(rgbw.0, rgbw.1, rgbw.2, rgbw.3));
+    }
+
+    #[rstest]
+    #[case((255, 0, 0), (255, 0, 0, 0))]
+    #[case((255, 255, 255), (0, 0, 0, 255))]
+    #[case((255, 128, 128), (253, 0, 0, 255))]
+    #[case((0, 0, 0), (0, 0, 0, 0))]
+    fn rgb_to_rgbw_extracts_white(#[case] rgb: (u8, u8, u8), #[case] expected: (u8, u8, u8, u8)) {
+        assert_eq!(expected, rgb_to_rgbw(rgb.0, rgb.1, rgb.2));
+    }
+
+    #[rstest]
+    #[case((255, 0, 0))]
+    #[case((255, 128, 128))]
+    #[case((64, 224, 208))]
+    #[case((255, 255, 255))]
+    fn rgb_rgbw_round_trip(#[case] rgb: (u8, u8, u8)) {
+        let (r, g, b, w) = rgb_to_rgbw(rgb.0, rgb.1, rgb.2);
+        let result = rgbw_to_rgb(r, g, b, w);
+        assert_close(rgb.0 as f64, result.0 as f64, 1.0);
+        assert_close(rgb.1 as f64, result.1 as f64, 1.0);
+        assert_close(rgb.2 as f64, result.2 as f64, 1.0);
+    }
+
+    #[rstest]
+    #[case((255, 0, 0, 0, 0), (255, 0, 0))]
+    #[case((0, 0, 0, 0, 0), (0, 0, 0))]
+    #[case((0, 0, 0, 255, 0), (255, 255, 251))]
+    #[case((0, 0, 0, 0, 255), (255, 137, 14))]
+    #[case((0, 0, 0, 255, 255), (255, 179, 114))]
+    fn rgbww_to_rgb_adds_white(
+        #[case] rgbww: (u8, u8, u8, u8, u8),
+        #[case] expected: (u8, u8, u8),
+    ) {
+        assert_eq!(
+            expected,
+            rgbww_to_rgb(rgbww, DEFAULT_MIN_KELVIN, DEFAULT_MAX_KELVIN)
+        );
+    }
+
+    #[rstest]
+    #[case((255, 0, 0), (255, 0, 0, 0, 0))]
+    #[case((0, 0, 0), (0, 0, 0, 0, 0))]
+    #[case((255, 179, 114), (0, 0, 0, 255, 255))]
+    fn rgb_to_rgbww_extracts_white(
+        #[case] rgb: (u8, u8, u8),
+        #[case] expected: (u8, u8, u8, u8, u8),
+    ) {
+        assert_eq!(
+            expected,
+            rgb_to_rgbww(rgb.0, rgb.1, rgb.2, DEFAULT_MIN_KELVIN, DEFAULT_MAX_KELVIN)
+        );
+    }
+
+    #[rstest]
+    #[case((255, 0, 0))]
+    #[case((255, 128, 64))]
+    #[case((64, 224, 208))]
+    #[case((200, 200, 255))]
+    fn rgb_rgbww_round_trip(#[case] rgb: (u8, u8, u8)) {
+        let rgbww = rgb_to_rgbww(rgb.0, rgb.1, rgb.2, DEFAULT_MIN_KELVIN, DEFAULT_MAX_KELVIN);
+        let result = rgbww_to_rgb(rgbww, DEFAULT_MIN_KELVIN, DEFAULT_MAX_KELVIN);
+        assert_close(rgb.0 as f64, result.0 as f64, 2.0);
+        assert_close(rgb.1 as f64, result.1 as f64, 2.0);
+        assert_close(rgb.2 as f64, result.2 as f64, 2.0);
+    }
+}
@@ -4,6 +4,7 @@
 //! Common utility functions.
 
 mod certificates;
+pub mod color;
 mod env;
 mod from_msg_data;
 pub mod json;